@@ -0,0 +1,82 @@
+#![no_main]
+
+use chipy8::chip8::{Chip8, Platform, Quirks};
+use chipy8::rom::Rom;
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+const MAX_STEPS: usize = 10_000;
+
+const PLATFORMS: [Platform; 5] = [
+    Platform::Chip8,
+    Platform::Chip48,
+    Platform::Schip,
+    Platform::Xochip,
+    Platform::HiresChip8,
+];
+
+/// A machine in whatever state, running under whatever quirks. Nothing is
+/// clamped, a state the interpreter can't run from is its to refuse
+#[derive(Debug, Arbitrary)]
+struct Input {
+    /// index into the platforms
+    platform: u8,
+    /// quirks in place of the platform's, in the order of `Quirks`' fields
+    quirks: Option<[bool; 7]>,
+    start_address: u16,
+    rom: Vec<u8>,
+    /// bytes written over memory from the start, fonts and all
+    memory: Vec<u8>,
+    registers: [u8; 16],
+    i: u16,
+    program_counter: u16,
+    stack: [u16; 16],
+    stack_pointer: u8,
+    delay: u8,
+    sound: u8,
+    keys: u16,
+    hires: bool,
+    two_page: bool,
+}
+
+// Sets up a machine as the input says and runs it until it fails, any panic is
+// a bug
+fuzz_target!(|input: Input| {
+    let platform = PLATFORMS[input.platform as usize % PLATFORMS.len()];
+    let mut builder = Chip8::builder(Rom::from_bytes("fuzz", input.rom))
+        .platform(platform)
+        .start_address(input.start_address);
+    if let Some(quirks) = input.quirks {
+        builder = builder.quirks(Quirks {
+            shift_uses_vy: quirks[0],
+            load_store_increments_i: quirks[1],
+            jump_uses_vx: quirks[2],
+            vf_reset: quirks[3],
+            wrap_sprites: quirks[4],
+            wrap_memory: quirks[5],
+            display_wait: quirks[6],
+        });
+    }
+    // a start address or rom the builder refuses is not the interpreter's to run
+    let Ok(mut chip8) = builder.build() else {
+        return;
+    };
+    let len = input.memory.len().min(chip8.memory.len());
+    chip8.memory[..len].copy_from_slice(&input.memory[..len]);
+    chip8.registers = input.registers;
+    chip8.i = input.i;
+    chip8.program_counter = input.program_counter;
+    chip8.stack = input.stack;
+    chip8.stack_pointer = input.stack_pointer;
+    chip8.delay = input.delay;
+    chip8.sound = input.sound;
+    chip8.keys = input.keys;
+    chip8.hires = input.hires;
+    chip8.two_page = input.two_page;
+    for _ in 0..MAX_STEPS {
+        if chip8.step().is_err() {
+            break;
+        }
+        chip8.tick_timers();
+    }
+});
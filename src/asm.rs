@@ -0,0 +1,250 @@
+//! Assembler
+//!
+//! Reads the mnemonics [`Instruction`] prints back into a rom that loads at the
+//! program start, so test roms can be written without outside tools.
+//!
+//! ```text
+//! ; comments run to the end of the line
+//! speed = 2              ; a constant
+//! start:                 ; a label, the address of what follows
+//!     LD I, ball         ; operands can name labels and constants
+//!     DRW V0, V1, 3
+//!     ADD V0, speed
+//!     JP start
+//! ball:
+//!     db 0x3c, 0x42, 0x3c
+//! ```
+//!
+//! Numbers are decimal, `0x` hex or `0b` binary. `db` and `dw` lay down bytes and
+//! big endian words. Mnemonics and register names are case insensitive.
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+};
+
+use crate::chip8::{Instruction, PROGRAM_START};
+
+/// What a line of source lays down
+enum Item<'a> {
+    Instruction(&'a str, Vec<&'a str>),
+    Bytes(Vec<&'a str>),
+    Words(Vec<&'a str>),
+}
+
+enum Operand {
+    V(u8),
+    I,
+    /// `[I]`, memory at I
+    AtI,
+    Dt,
+    St,
+    K,
+    F,
+    Hf,
+    B,
+    /// SUPER-CHIP's persistent flags
+    R,
+    Value(u16),
+}
+
+/// Assemble `source` into the bytes of a rom
+pub fn assemble(source: &str) -> Result<Vec<u8>, Error> {
+    let invalid = |line: usize, reason: String| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("line {}: {reason}", line + 1),
+        )
+    };
+
+    // first pass: where every label lands
+    let mut symbols: HashMap<&str, u16> = HashMap::new();
+    let mut items = Vec::new();
+    let mut address = PROGRAM_START as u16;
+    for (n, line) in source.lines().enumerate() {
+        let mut line = line.split(';').next().unwrap_or_default().trim();
+        if let Some((name, rest)) = line.split_once(':') {
+            if is_name(name.trim()) {
+                define(&mut symbols, name.trim(), address).map_err(|e| invalid(n, e))?;
+                line = rest.trim();
+            }
+        }
+        if line.is_empty() {
+            continue;
+        }
+        if let Some((name, value)) = line.split_once('=') {
+            let value = self::value(value.trim(), &symbols).map_err(|e| invalid(n, e))?;
+            define(&mut symbols, name.trim(), value).map_err(|e| invalid(n, e))?;
+            continue;
+        }
+        let (mnemonic, operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let operands: Vec<&str> = match operands.trim() {
+            "" => Vec::new(),
+            operands => operands.split(',').map(str::trim).collect(),
+        };
+        let item = match mnemonic.to_ascii_lowercase().as_str() {
+            "db" => Item::Bytes(operands),
+            "dw" => Item::Words(operands),
+            _ => Item::Instruction(mnemonic, operands),
+        };
+        address += match &item {
+            Item::Instruction(..) => 2,
+            Item::Bytes(bytes) => bytes.len() as u16,
+            Item::Words(words) => 2 * words.len() as u16,
+        };
+        items.push((n, item));
+    }
+
+    // second pass: everything resolves now
+    let mut rom = Vec::new();
+    for (n, item) in items {
+        match item {
+            Item::Instruction(mnemonic, operands) => {
+                let operands = operands
+                    .iter()
+                    .map(|operand| self::operand(operand, &symbols))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| invalid(n, e))?;
+                let instruction = instruction(mnemonic, &operands).map_err(|e| invalid(n, e))?;
+                rom.extend(instruction.encode().to_be_bytes());
+            }
+            Item::Bytes(bytes) => {
+                for byte in bytes {
+                    let byte = value(byte, &symbols).and_then(|v| fits(v, 0xFF));
+                    rom.push(byte.map_err(|e| invalid(n, e))? as u8);
+                }
+            }
+            Item::Words(words) => {
+                for word in words {
+                    let word = value(word, &symbols).map_err(|e| invalid(n, e))?;
+                    rom.extend(word.to_be_bytes());
+                }
+            }
+        }
+    }
+    Ok(rom)
+}
+
+fn is_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+fn define<'a>(
+    symbols: &mut HashMap<&'a str, u16>,
+    name: &'a str,
+    value: u16,
+) -> Result<(), String> {
+    if !is_name(name) {
+        return Err(format!("`{name}` isn't a valid name"));
+    }
+    match symbols.insert(name, value) {
+        Some(_) => Err(format!("`{name}` is already defined")),
+        None => Ok(()),
+    }
+}
+
+/// A number, or the value of a label or constant
+fn value(text: &str, symbols: &HashMap<&str, u16>) -> Result<u16, String> {
+    let number = if let Some(hex) = text.strip_prefix("0x") {
+        u16::from_str_radix(hex, 16)
+    } else if let Some(binary) = text.strip_prefix("0b") {
+        u16::from_str_radix(binary, 2)
+    } else if text.starts_with(|c: char| c.is_ascii_digit()) {
+        text.parse()
+    } else {
+        return symbols
+            .get(text)
+            .copied()
+            .ok_or_else(|| format!("unknown name `{text}`"));
+    };
+    number.map_err(|_| format!("bad number `{text}`"))
+}
+
+fn operand(text: &str, symbols: &HashMap<&str, u16>) -> Result<Operand, String> {
+    let upper = text.to_ascii_uppercase();
+    Ok(match upper.as_str() {
+        "I" => Operand::I,
+        "[I]" => Operand::AtI,
+        "DT" => Operand::Dt,
+        "ST" => Operand::St,
+        "K" => Operand::K,
+        "F" => Operand::F,
+        "HF" => Operand::Hf,
+        "B" => Operand::B,
+        "R" => Operand::R,
+        register if register.len() == 2 && register.starts_with('V') => {
+            match u8::from_str_radix(&register[1..], 16) {
+                Ok(x) => Operand::V(x),
+                Err(_) => Operand::Value(value(text, symbols)?),
+            }
+        }
+        _ => Operand::Value(value(text, symbols)?),
+    })
+}
+
+/// `value` if it is at most `max`
+fn fits(value: u16, max: u16) -> Result<u16, String> {
+    match value <= max {
+        true => Ok(value),
+        false => Err(format!("{value:#x} doesn't fit in {max:#x}")),
+    }
+}
+
+fn instruction(mnemonic: &str, operands: &[Operand]) -> Result<Instruction, String> {
+    use Instruction::*;
+    use Operand::{Value, V};
+    let byte = |value: u16| fits(value, 0xFF).map(|v| v as u8);
+    let nibble = |value: u16| fits(value, 0xF).map(|v| v as u8);
+    let address = |value: u16| fits(value, 0xFFF);
+    Ok(match (mnemonic.to_ascii_uppercase().as_str(), operands) {
+        ("CLS", []) => Cls,
+        ("RET", []) => Ret,
+        ("SCD", [Value(n)]) => Scd(nibble(*n)?),
+        ("SCR", []) => Scr,
+        ("SCL", []) => Scl,
+        ("EXIT", []) => Exit,
+        ("LOW", []) => Low,
+        ("HIGH", []) => High,
+        ("JP", [Value(a)]) => Jp(address(*a)?),
+        ("JP", [V(0), Value(a)]) => JpV0(address(*a)?),
+        ("CALL", [Value(a)]) => Call(address(*a)?),
+        ("SE", [V(x), Value(kk)]) => SeVx(*x, byte(*kk)?),
+        ("SE", [V(x), V(y)]) => SeVxVy(*x, *y),
+        ("SNE", [V(x), Value(kk)]) => SneVx(*x, byte(*kk)?),
+        ("SNE", [V(x), V(y)]) => SneVxVy(*x, *y),
+        ("LD", [V(x), Value(kk)]) => LdVx(*x, byte(*kk)?),
+        ("LD", [V(x), V(y)]) => LdVxVy(*x, *y),
+        ("LD", [Operand::I, Value(a)]) => LdI(address(*a)?),
+        ("LD", [V(x), Operand::Dt]) => LdVxDt(*x),
+        ("LD", [V(x), Operand::K]) => LdVxK(*x),
+        ("LD", [Operand::Dt, V(x)]) => LdDtVx(*x),
+        ("LD", [Operand::St, V(x)]) => LdStVx(*x),
+        ("LD", [Operand::F, V(x)]) => LdFVx(*x),
+        ("LD", [Operand::Hf, V(x)]) => LdHfVx(*x),
+        ("LD", [Operand::B, V(x)]) => LdBVx(*x),
+        ("LD", [Operand::AtI, V(x)]) => LdIVx(*x),
+        ("LD", [V(x), Operand::AtI]) => LdVxI(*x),
+        ("LD", [Operand::R, V(x)]) => LdRVx(*x),
+        ("LD", [V(x), Operand::R]) => LdVxR(*x),
+        ("ADD", [V(x), Value(kk)]) => AddVx(*x, byte(*kk)?),
+        ("ADD", [V(x), V(y)]) => AddVxVy(*x, *y),
+        ("ADD", [Operand::I, V(x)]) => AddIVx(*x),
+        ("OR", [V(x), V(y)]) => Or(*x, *y),
+        ("AND", [V(x), V(y)]) => And(*x, *y),
+        ("XOR", [V(x), V(y)]) => Xor(*x, *y),
+        ("SUB", [V(x), V(y)]) => Sub(*x, *y),
+        ("SUBN", [V(x), V(y)]) => Subn(*x, *y),
+        // Vy only matters with the shift quirk, leaving it out shifts Vx either way
+        ("SHR", [V(x)]) => Shr(*x, *x),
+        ("SHR", [V(x), V(y)]) => Shr(*x, *y),
+        ("SHL", [V(x)]) => Shl(*x, *x),
+        ("SHL", [V(x), V(y)]) => Shl(*x, *y),
+        ("RND", [V(x), Value(kk)]) => Rnd(*x, byte(*kk)?),
+        ("DRW", [V(x), V(y), Value(n)]) => Drw(*x, *y, nibble(*n)?),
+        ("SKP", [V(x)]) => Skp(*x),
+        ("SKNP", [V(x)]) => Sknp(*x),
+        _ => return Err(format!("no `{mnemonic}` instruction takes these operands")),
+    })
+}
@@ -1,33 +1,107 @@
-use std::io::{Bytes, Read};
-
-use chipy8::chip8::Chip8;
+use chipy8::chip8::{Chip8, Instruction, Tracer};
 use chipy8::cli::Cli;
+use chipy8::config::Config;
+use chipy8::debugger;
+use chipy8::keymap::Keymap;
+use chipy8::library::{Library, RomSettings, ROM_EXTENSIONS};
 use chipy8::rom::Rom;
+use chipy8::session::Session;
+use chipy8::sound::Beeper;
+use chipy8::timing::Clock;
 use clap::Parser;
-use iced::widget::{canvas, column, container, image, text, Container};
+use iced::keyboard::{self, Key};
+use iced::widget::image::FilterMethod;
+use iced::widget::{
+    button, canvas, column, container, image, row, slider, text, Column, Container,
+};
 use iced::Length::Fill;
-use iced::{mouse, Center, Color, Rectangle, Renderer, Task, Theme};
+use iced::{
+    event, mouse, time, window, Center, Element, Event, Font, Rectangle, Renderer, Subscription,
+    Task, Theme,
+};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const FRAME: Duration = Duration::from_micros(16_667);
+
+/// Frames run at once to catch up after the window stalled, a second's worth
+const MAX_CATCH_UP: u32 = 60;
+
+/// Instructions listed in the Program panel, the PC's in the middle
+const PROGRAM_ROWS: usize = 16;
+
+/// The speed slider's range, as the terminal frontend's 60 to 60000 a second
+const MIN_PER_FRAME: u32 = 1;
+const MAX_PER_FRAME: u32 = 1000;
 
 pub fn main() -> iced::Result {
     let cli = Cli::parse();
-
-    let rom = Rom::new(cli.rom_path).unwrap();
+    let mut status = None;
+    // what can't be read is left out and shown, rather than keeping the window shut
+    let config = Config::load(cli.config.as_deref()).unwrap_or_else(|e| {
+        status = Some(format!("Could not read the config file: {e}"));
+        Config::default()
+    });
+    let library = Library::load(None).unwrap_or_else(|e| {
+        status = Some(format!("Could not read the library: {e}"));
+        Library::default()
+    });
+    // without a rom to open, one is picked with Open
+    let rom_path = Session::rom_to_open(&cli.rom_paths).ok();
+    let speed = cli.speed_with(&RomSettings::default(), &config);
+    let mute = cli.mute || config.mute;
+    let mut app = Chippy8 {
+        chip8: None,
+        keymap: Keymap::default(),
+        mode: Mode::Running,
+        instructions_per_frame: per_frame(speed),
+        frames: Clock::timers(Instant::now()),
+        beeper: if mute { None } else { Beeper::new() },
+        panels: Panels::default(),
+        status,
+        cli,
+        config,
+        library,
+    };
+    if let Some(path) = rom_path {
+        match app.load(path) {
+            Ok(()) => app.trace(),
+            Err(error) => app.status = Some(error),
+        }
+    }
     iced::application("Chippy-8", Chippy8::update, Chippy8::view)
+        .subscription(Chippy8::subscription)
         .theme(|_| Theme::Ferra)
-        .run_with(|| {
-            (
-                Chippy8 {
-                    chip8: Chip8::new(rom),
-                    mode: Mode::Running,
-                },
-                Task::done(Message::Tick),
-            )
-        })
+        .run_with(move || (app, Task::none()))
+}
+
+/// A 60th of `speed` instructions a second, in the slider's range
+fn per_frame(speed: u32) -> u32 {
+    (speed / 60).clamp(MIN_PER_FRAME, MAX_PER_FRAME)
 }
 
 struct Chippy8 {
-    chip8: Chip8,
+    /// the machine, once a rom is loaded
+    chip8: Option<Chip8>,
+    /// keys for the keypad, from --keymap, the rom's remembered one or the config
+    /// file
+    keymap: Keymap,
     mode: Mode,
+    /// a 60th of the instructions per second, from --speed, the rom's remembered
+    /// speed or the config file
+    instructions_per_frame: u32,
+    /// the 60Hz frames due, each a frame of instructions and a tick of the timers
+    frames: Clock,
+    beeper: Option<Beeper>,
+    /// debug panels open beside the display
+    panels: Panels,
+    /// the last error, shown under the controls
+    status: Option<String>,
+    /// the options a rom is loaded with
+    cli: Cli,
+    config: Config,
+    /// how each rom was last played in the terminal frontend
+    library: Library,
 }
 
 enum Mode {
@@ -35,13 +109,94 @@ enum Mode {
     Paused,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The debug panels beside the display, each collapsed to its title or open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Panel {
+    Registers,
+    Timers,
+    Stack,
+    Program,
+}
+
+#[derive(Debug, Default)]
+struct Panels {
+    registers: bool,
+    timers: bool,
+    stack: bool,
+    program: bool,
+}
+
+impl Panels {
+    fn open_mut(&mut self, panel: Panel) -> &mut bool {
+        match panel {
+            Panel::Registers => &mut self.registers,
+            Panel::Timers => &mut self.timers,
+            Panel::Stack => &mut self.stack,
+            Panel::Program => &mut self.program,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 enum Message {
     ToggleMode,
+    /// one instruction, while paused
+    Step,
+    Reset,
+    /// instructions per frame, from the slider
+    Speed(u32),
     Tick,
+    KeyDown(Key),
+    KeyUp(Key),
+    /// show the file dialog
+    Open,
+    /// the rom picked in it, if one was
+    Opened(Option<PathBuf>),
+    /// a file dropped on the window, to run straight away
+    Dropped(PathBuf),
+    /// open or collapse a debug panel
+    TogglePanel(Panel),
 }
 
 impl Chippy8 {
+    /// Load the rom at `path` in place of the one running, set up as the command
+    /// line asks, then as it was last played, then as the config file says, and
+    /// remember it for next time
+    fn load(&mut self, path: PathBuf) -> Result<(), String> {
+        let rom = Rom::new(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let settings = self.library.settings(rom.hash());
+        let keymap = self.cli.keymap_with(rom.name(), &settings, &self.config);
+        let speed = self.cli.speed_with(&settings, &self.config);
+        let chip8 = self
+            .cli
+            .builder_with(rom, &settings, &self.config)
+            .build()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        self.chip8 = Some(chip8);
+        self.keymap = keymap;
+        self.instructions_per_frame = per_frame(speed);
+        self.status = None;
+        self.frames = Clock::timers(Instant::now());
+        if let Some(beeper) = &self.beeper {
+            beeper.set(false);
+        }
+        if let Err(e) = Session::save_opened(&path) {
+            eprintln!("Could not save the session: {e}");
+        }
+        Ok(())
+    }
+
+    /// Trace the loaded rom to the file given with --trace
+    fn trace(&mut self) {
+        let (Some(path), Some(chip8)) = (&self.cli.trace, &mut self.chip8) else {
+            return;
+        };
+        match Tracer::create(path) {
+            Ok(tracer) => chip8.tracer = Some(tracer),
+            Err(e) => self.status = Some(format!("Could not trace to {}: {e}", path.display())),
+        }
+    }
+
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::ToggleMode => {
@@ -49,38 +204,249 @@ impl Chippy8 {
                     Mode::Running => Mode::Paused,
                     Mode::Paused => Mode::Running,
                 };
+                // no backlog of frames builds up while paused
+                self.frames = Clock::timers(Instant::now());
+                if let Some(beeper) = &self.beeper {
+                    beeper.set(false);
+                }
+                Task::none()
+            }
+            Message::Step => {
+                if let Some(Err(error)) = self.chip8.as_mut().map(Chip8::step) {
+                    self.status = Some(error.to_string());
+                }
+                Task::none()
+            }
+            Message::Reset => {
+                if let Some(chip8) = &mut self.chip8 {
+                    chip8.reset();
+                }
+                Task::none()
+            }
+            Message::Speed(instructions_per_frame) => {
+                self.instructions_per_frame = instructions_per_frame;
+                Task::none()
+            }
+            Message::KeyDown(key) => {
+                if let (Some(key), Some(chip8)) = (self.keypad(&key), &mut self.chip8) {
+                    chip8.key_down(key);
+                }
+                Task::none()
+            }
+            Message::KeyUp(key) => {
+                if let (Some(key), Some(chip8)) = (self.keypad(&key), &mut self.chip8) {
+                    chip8.key_up(key);
+                }
+                Task::none()
+            }
+            Message::Open => Task::perform(
+                rfd::AsyncFileDialog::new()
+                    .set_title("Open a rom")
+                    .add_filter("CHIP-8 roms", &ROM_EXTENSIONS)
+                    .pick_file(),
+                |file| Message::Opened(file.map(|file| file.path().to_path_buf())),
+            ),
+            Message::Opened(None) => Task::none(),
+            Message::Opened(Some(path)) => {
+                if let Err(error) = self.load(path) {
+                    self.status = Some(error);
+                }
+                Task::none()
+            }
+            Message::Dropped(path) => {
+                match self.load(path) {
+                    Ok(()) => self.mode = Mode::Running,
+                    Err(error) => self.status = Some(error),
+                }
+                Task::none()
+            }
+            Message::TogglePanel(panel) => {
+                let open = self.panels.open_mut(panel);
+                *open = !*open;
                 Task::none()
             }
             Message::Tick => {
-                println!("{:?}", self.chip8);
-                self.chip8.step();
-                Task::done(Message::Tick)
+                let Some(chip8) = &mut self.chip8 else {
+                    return Task::none();
+                };
+                let due = self.frames.due(Instant::now()).min(MAX_CATCH_UP);
+                for _ in 0..due {
+                    let summary = chip8.step_frame(self.instructions_per_frame);
+                    if let Some(error) = summary.error {
+                        self.status = Some(error.to_string());
+                        self.mode = Mode::Paused;
+                        break;
+                    }
+                }
+                if let Some(beeper) = &self.beeper {
+                    beeper.set(matches!(self.mode, Mode::Running) && chip8.sound > 0);
+                }
+                Task::none()
             }
         }
     }
 
-    fn view(&self) -> Container<Message> {
-        container(
+    /// The keypad key `key` is for, by the character it types. Letters count
+    /// whether shift is held or not.
+    fn keypad(&self, key: &Key) -> Option<u8> {
+        let Key::Character(typed) = key else {
+            return None;
+        };
+        let c = typed.chars().next()?;
+        self.keymap
+            .key(c)
+            .or_else(|| self.keymap.key(c.to_ascii_lowercase()))
+    }
+
+    /// Open a rom, pause or resume, step while paused, reset, and the speed
+    fn controls(&self) -> iced::widget::Row<'_, Message> {
+        let (toggle, step) = match self.mode {
+            Mode::Running => ("Pause", None),
+            Mode::Paused => ("Resume", Some(Message::Step)),
+        };
+        let step = step.filter(|_| self.chip8.is_some());
+        let speed = slider(
+            MIN_PER_FRAME..=MAX_PER_FRAME,
+            self.instructions_per_frame,
+            Message::Speed,
+        )
+        .width(200);
+        row![
+            button("Open…").on_press(Message::Open),
+            button(toggle).on_press(Message::ToggleMode),
+            button("Step").on_press_maybe(step),
+            button("Reset").on_press(Message::Reset),
+            speed,
+            text(format!("{} per frame", self.instructions_per_frame)),
+        ]
+        .spacing(10)
+        .align_y(Center)
+    }
+
+    /// `panel`'s title, a button collapsing it, over `content` while it's open
+    fn panel<'a>(
+        &self,
+        panel: Panel,
+        title: &'a str,
+        open: bool,
+        content: impl FnOnce() -> Element<'a, Message>,
+    ) -> Column<'a, Message> {
+        let arrow = if open { "▾" } else { "▸" };
+        let title = button(text(format!("{arrow} {title}")))
+            .on_press(Message::TogglePanel(panel))
+            .style(button::text);
+        match open {
+            true => column![title, content()],
+            false => column![title],
+        }
+        .spacing(4)
+    }
+
+    /// V0 to VF, I and the PC; the timers; and the calls in progress
+    fn state_panels<'a>(&self, chip8: &'a Chip8) -> Column<'a, Message> {
+        let mono = |s: String| text(s).font(Font::MONOSPACE);
+        let registers = self.panel(Panel::Registers, "Registers", self.panels.registers, || {
+            let rows = chip8.registers.chunks(4).enumerate().map(|(row, values)| {
+                let line = values
+                    .iter()
+                    .enumerate()
+                    .map(|(x, v)| format!("V{:X} {v:02x}", row * 4 + x))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                mono(line).into()
+            });
+            Column::with_children(rows)
+                .push(mono(format!("I  {:03x}", chip8.i)))
+                .push(mono(format!("PC {:03x}", chip8.program_counter)))
+                .into()
+        });
+        let timers = self.panel(Panel::Timers, "Timers", self.panels.timers, || {
             column![
-                text(self.chip8.rom.name()).size(50),
-                canvas(Circle {
-                    radius: 50.0,
-                    chip8: &self.chip8
-                })
+                mono(format!("DT {:3}", chip8.delay)),
+                mono(format!("ST {:3}", chip8.sound)),
             ]
+            .into()
+        });
+        let stack = self.panel(Panel::Stack, "Stack", self.panels.stack, || {
+            let frames = debugger::call_stack(chip8);
+            match frames.is_empty() {
+                true => mono("empty".to_string()).into(),
+                false => {
+                    Column::with_children(frames.iter().map(|frame| mono(frame.to_string()).into()))
+                        .into()
+                }
+            }
+        });
+        column![registers, timers, stack].spacing(10).width(240)
+    }
+
+    /// The instructions around the PC
+    fn program_panel<'a>(&self, chip8: &'a Chip8) -> Column<'a, Message> {
+        let program = self.panel(Panel::Program, "Program", self.panels.program, || {
+            let pc = chip8.program_counter as usize;
+            let memory = chip8.memory.len();
+            let start = debugger::program_window(pc, PROGRAM_ROWS / 2, PROGRAM_ROWS, memory);
+            let lines = (start..memory.saturating_sub(1))
+                .step_by(2)
+                .take(PROGRAM_ROWS)
+                .map(|address| {
+                    let opcode =
+                        u16::from_be_bytes([chip8.memory[address], chip8.memory[address + 1]]);
+                    let marker = if address == pc { '>' } else { ' ' };
+                    let instruction = Instruction::decode(opcode);
+                    let line = format!("{marker} {address:03x} {opcode:04x} {instruction}");
+                    text(line).font(Font::MONOSPACE).into()
+                });
+            Column::with_children(lines).into()
+        });
+        program.width(260)
+    }
+
+    /// The keyboard, files dropped on the window, and a tick each frame while
+    /// running
+    fn subscription(&self) -> Subscription<Message> {
+        let frames = match self.mode {
+            Mode::Running => time::every(FRAME).map(|_| Message::Tick),
+            Mode::Paused => Subscription::none(),
+        };
+        Subscription::batch([
+            frames,
+            keyboard::on_key_press(|key, _| Some(Message::KeyDown(key))),
+            keyboard::on_key_release(|key, _| Some(Message::KeyUp(key))),
+            event::listen_with(|event, _, _| match event {
+                Event::Window(window::Event::FileDropped(path)) => Some(Message::Dropped(path)),
+                _ => None,
+            }),
+        ])
+    }
+
+    fn view(&self) -> Container<'_, Message> {
+        container(
+            match &self.chip8 {
+                Some(chip8) => column![
+                    text(chip8.rom.name()).size(50),
+                    row![
+                        self.state_panels(chip8),
+                        canvas(Circle { chip8 }).width(Fill).height(Fill),
+                        self.program_panel(chip8),
+                    ]
+                    .spacing(20),
+                    self.controls(),
+                ],
+                None => column![text("No rom loaded, open one").size(30), self.controls()],
+            }
+            .push_maybe(self.status.as_deref().map(text))
             .padding(20)
             .align_x(Center),
         )
         .center_x(Fill)
         .center_y(Fill)
-        .into()
     }
 }
 
 // First, we define the data we need for drawing
 #[derive(Debug)]
 struct Circle<'a> {
-    radius: f32,
     chip8: &'a Chip8,
 }
 
@@ -110,27 +476,31 @@ impl<'a, Message> canvas::Program<Message> for Circle<'a> {
         //let img = image::Handle::from_path("ferris.png");
         //let img_bytes = self.chip8.display.iter().flat_map(|p|[0xFF,])
 
-        let pixel_string = &self
-            .chip8
-            .display
-            .iter()
-            .map(|r| format!("{:08b}", r))
-            .collect::<Vec<_>>()
-            .join("");
-        let img_bits: Vec<u8> = pixel_string
-            .chars()
-            .into_iter()
-            .flat_map(|c| match c {
-                '0' => [0x00, 0x00, 0x00, 0xFF],
-                '1' => [0xFF, 0xFF, 0xFF, 0xFF],
-                _ => [0xFF, 0x00, 0x00, 0xFF],
+        let (width, height) = (self.chip8.width(), self.chip8.height());
+        let img_bits: Vec<u8> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| match self.chip8.pixel(x, y) {
+                false => [0x00, 0x00, 0x00, 0xFF],
+                true => [0xFF, 0xFF, 0xFF, 0xFF],
             })
             .collect();
-        println!("{}", img_bits.len());
-        let img = image::Handle::from_rgba(64, 32, img_bits);
+        let img = image::Handle::from_rgba(width as u32, height as u32, img_bits);
+        // the largest whole number of points a pixel of the display, whatever its
+        // mode, fits in
+        let scale = (bounds.width / width as f32)
+            .min(bounds.height / height as f32)
+            .floor()
+            .max(1.);
+        let size = iced::Size::new(width as f32 * scale, height as f32 * scale);
+        // centered, black bars around it
+        let top_left = iced::Point {
+            x: ((bounds.width - size.width) / 2.).max(0.).floor(),
+            y: ((bounds.height - size.height) / 2.).max(0.).floor(),
+        };
+        frame.fill_rectangle(iced::Point::ORIGIN, bounds.size(), iced::Color::BLACK);
         frame.draw_image(
-            Rectangle::new(iced::Point { x: 0., y: 0. }, iced::Size::new(64., 32.)),
-            &img,
+            Rectangle::new(top_left, size),
+            canvas::Image::new(img).filter_method(FilterMethod::Nearest),
         );
 
         // Then, we produce the geometry
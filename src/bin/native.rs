@@ -0,0 +1,243 @@
+//! Minimal native frontend: a plain window, the framebuffer and the keypad.
+//! No widgets, just softbuffer blits driven by the winit event loop.
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use chipy8::chip8::{Chip8, Tracer, VIP_CYCLES_PER_SECOND};
+use chipy8::cli::Cli;
+use chipy8::rom::Rom;
+use chipy8::session::Session;
+use chipy8::sound::Beeper;
+use chipy8::timing::Clock;
+use clap::Parser;
+use softbuffer::{Context, Surface};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Window, WindowId};
+
+const FRAME: Duration = Duration::from_micros(16_667);
+const SCALE: u32 = 10;
+
+const FOREGROUND: u32 = 0x00FF_FFFF;
+const BACKGROUND: u32 = 0x0000_0000;
+
+/// Same layout as the terminal frontend, by physical key position
+const KEYS: [KeyCode; 16] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::KeyQ,
+    KeyCode::KeyW,
+    KeyCode::KeyE,
+    KeyCode::KeyR,
+    KeyCode::KeyA,
+    KeyCode::KeyS,
+    KeyCode::KeyD,
+    KeyCode::KeyF,
+    KeyCode::KeyZ,
+    KeyCode::KeyX,
+    KeyCode::KeyC,
+    KeyCode::KeyV,
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let rom_path = Session::rom_to_open(&cli.rom_paths)?;
+    let mut chip8 = cli.builder(Rom::new(&rom_path)?).build()?;
+    if let Some(path) = &cli.trace {
+        chip8.tracer = Some(Tracer::create(path)?);
+    }
+    chip8.set_profiling(cli.profile);
+    let flags_path = chip8.default_flags_path();
+    if let Some(path) = &flags_path {
+        chip8.load_flags(path)?;
+    }
+
+    let speed = match cli.vip_timing {
+        true => VIP_CYCLES_PER_SECOND,
+        false => cli.speed(),
+    };
+
+    let event_loop = EventLoop::new()?;
+    let mut native = Native {
+        chip8,
+        paused: cli.paused,
+        speed,
+        instructions: Clock::new(Instant::now(), speed),
+        vip_cycles: cli.vip_timing.then_some(0),
+        timers: Clock::timers(Instant::now()),
+        window: None,
+        next_frame: Instant::now(),
+        beeper: if cli.mute { None } else { Beeper::new() },
+    };
+    event_loop.run_app(&mut native)?;
+    if let Some(path) = &flags_path {
+        native.chip8.save_flags(path)?;
+    }
+    if let Some(profile) = native.chip8.profile() {
+        println!("{profile}");
+    }
+    Session::save_opened(&rom_path)?;
+    Ok(())
+}
+
+type WindowSurface = Surface<Rc<Window>, Rc<Window>>;
+
+struct Native {
+    chip8: Chip8,
+    paused: bool,
+    /// instructions per second, VIP cycles with --vip-timing
+    speed: u32,
+    instructions: Clock,
+    /// with --vip-timing, cycles due but not yet spent
+    vip_cycles: Option<u32>,
+    timers: Clock,
+    window: Option<(Rc<Window>, WindowSurface)>,
+    next_frame: Instant,
+    beeper: Option<Beeper>,
+}
+
+impl ApplicationHandler for Native {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let attributes = Window::default_attributes()
+            .with_title(format!("chipy8 - {}", self.chip8.rom.name()))
+            // two page hi-res is 64x64 from the start
+            .with_inner_size(LogicalSize::new(
+                self.chip8.width() as u32 * SCALE,
+                self.chip8.height() as u32 * SCALE,
+            ));
+        let window = Rc::new(event_loop.create_window(attributes).unwrap());
+        let context = Context::new(window.clone()).unwrap();
+        let surface = Surface::new(&context, window.clone()).unwrap();
+        self.window = Some((window, surface));
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::KeyboardInput { event, .. } => {
+                let pressed = event.state == ElementState::Pressed;
+                match event.physical_key {
+                    PhysicalKey::Code(KeyCode::Escape) if pressed => event_loop.exit(),
+                    PhysicalKey::Code(KeyCode::Space) if pressed => self.paused = !self.paused,
+                    PhysicalKey::Code(KeyCode::Backspace) if pressed => self.chip8.reset(),
+                    PhysicalKey::Code(code) => {
+                        if let Some(key) = KEYS.iter().position(|k| *k == code) {
+                            if !pressed {
+                                self.chip8.key_up(key as u8);
+                            } else if !event.repeat {
+                                self.chip8.key_down(key as u8);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            WindowEvent::RedrawRequested => self.present(),
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // catches up on ticks missed while the loop was blocked, e.g. by a window drag
+        let due = self.timers.due(Instant::now());
+        if !self.paused {
+            for _ in 0..due.min(u8::MAX.into()) {
+                self.chip8.tick_timers();
+            }
+        }
+        if let Some(beeper) = &self.beeper {
+            beeper.set(!self.paused && self.chip8.sound > 0);
+        }
+        if Instant::now() >= self.next_frame {
+            // the instructions that fell due since the last frame, at most a second's worth
+            let due = self.instructions.due(Instant::now()).min(self.speed);
+            let mut budget = due + self.vip_cycles.unwrap_or(0);
+            loop {
+                // with VIP timing an instruction waits for the cycles it takes
+                let cost = self.vip_cycles.map_or(1, |_| self.chip8.vip_cycles());
+                if budget < cost {
+                    break;
+                }
+                if self.paused || self.is_waiting() {
+                    // no backlog builds up while nothing can run
+                    self.instructions = Clock::new(Instant::now(), self.speed);
+                    budget = 0;
+                    break;
+                }
+                budget -= cost;
+                if let Err(error) = self.chip8.step() {
+                    self.paused = true;
+                    if let Some((window, _)) = &self.window {
+                        window.set_title(&format!("chipy8 - {error}"));
+                    }
+                    break;
+                }
+            }
+            self.vip_cycles = self.vip_cycles.map(|_| budget);
+            // the window only needs redrawing when the display changed
+            if let Some((window, _)) = &self.window {
+                if !self.chip8.take_dirty().is_empty() {
+                    window.request_redraw();
+                }
+            }
+            self.next_frame = Instant::now() + FRAME;
+        }
+        // nothing changes until the next key event, so sleep until it arrives
+        event_loop.set_control_flow(match self.is_idle() {
+            true => ControlFlow::Wait,
+            false => ControlFlow::WaitUntil(self.next_frame.min(self.timers.next())),
+        });
+    }
+}
+
+impl Native {
+    /// Blocked in Fx0A until a key goes down or comes back up
+    fn is_waiting(&self) -> bool {
+        self.chip8.is_waiting_for_key()
+    }
+
+    /// Nothing will change until the next key event
+    fn is_idle(&self) -> bool {
+        self.paused || (self.is_waiting() && self.chip8.delay == 0 && self.chip8.sound == 0)
+    }
+
+    /// Blit the display at the largest integer scale that fits, centered
+    fn present(&mut self) {
+        let Some((window, surface)) = &mut self.window else {
+            return;
+        };
+        let size = window.inner_size();
+        let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        else {
+            return;
+        };
+        surface.resize(width, height).unwrap();
+
+        let (width, height) = (size.width as usize, size.height as usize);
+        let (columns, rows) = (self.chip8.width(), self.chip8.height());
+        let scale = (width / columns).min(height / rows).max(1);
+        let left = width.saturating_sub(columns * scale) / 2;
+        let top = height.saturating_sub(rows * scale) / 2;
+
+        let mut buffer = surface.buffer_mut().unwrap();
+        for (i, pixel) in buffer.iter_mut().enumerate() {
+            let (x, y) = (i % width, i / width);
+            let lit = x >= left && y >= top && {
+                let (cx, cy) = ((x - left) / scale, (y - top) / scale);
+                cx < columns && cy < rows && self.chip8.pixel(cx, cy)
+            };
+            *pixel = if lit { FOREGROUND } else { BACKGROUND };
+        }
+        buffer.present().unwrap();
+    }
+}
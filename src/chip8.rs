@@ -1,17 +1,82 @@
 #![allow(arithmetic_overflow)]
-use std::fmt;
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format, vec,
+    vec::Vec,
+};
+use core::{fmt, mem};
 
-use drawille::Canvas;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::rom::Rom;
+
+mod breakpoints;
+mod builder;
+mod condition;
+mod coverage;
+mod cycles;
+mod error;
+#[cfg(feature = "std")]
+mod flags;
+mod frame;
+mod framebuffer;
+#[cfg(feature = "std")]
+mod hooks;
+mod instruction;
+#[cfg(feature = "std")]
+mod profile;
+mod quirks;
+mod random;
+mod save_state;
+#[cfg(feature = "std")]
+mod trace;
+mod watchpoints;
+pub use breakpoints::RunOutcome;
+pub use builder::Chip8Builder;
+pub use condition::{Comparison, Condition, Operand};
+pub use coverage::{Coverage, Usage};
+pub use cycles::{VIP_CYCLES_PER_FRAME, VIP_CYCLES_PER_SECOND};
+pub use error::Chip8Error;
+pub use frame::FrameSummary;
+#[cfg(feature = "std")]
+pub use hooks::Hooks;
+pub use instruction::Instruction;
+#[cfg(feature = "std")]
+pub use profile::{FamilyStats, Profile};
+pub use quirks::{Platform, Quirks};
+#[cfg(feature = "std")]
+pub use random::RandomSource;
+pub(crate) use save_state::hex;
+#[cfg(feature = "std")]
+pub use trace::{TraceEntry, TraceSink, Tracer};
+pub use watchpoints::Watchpoint;
+
 /// The first 512 bytes are resevered for the interpreter
-const PROGRAM_START: usize = 0x200;
-const MEMORY_SIZE: usize = 4096;
+pub const PROGRAM_START: usize = 0x200;
+/// The ETI 660's interpreter takes more room, its roms load here
+pub const ETI_660_START: usize = 0x600;
+/// The most memory there is, as much as 12 bit addresses reach
+pub const MEMORY_SIZE: usize = 4096;
 
 pub const WIDTH_PIX: usize = 64;
 pub const HEIGHT_PIX: usize = 32;
-const WIDTH_BYTE: usize = 8;
-const HEIGHT_BYTE: usize = 32;
+/// SUPER-CHIP's hi-res mode doubles both dimensions
+pub const HIRES_WIDTH_PIX: usize = 128;
+pub const HIRES_HEIGHT_PIX: usize = 64;
+/// Whether memory of `size` bytes holds the fonts and no more than 12 bit
+/// addresses reach
+fn is_valid_memory_size(size: usize) -> bool {
+    (BIG_CHARACTERS_START + BIG_CHARACTERS.len()..=MEMORY_SIZE).contains(&size)
+}
+
+/// Two page hi-res CHIP-8 programs begin with a jump to its interpreter extension
+/// and really start here
+const TWO_PAGE_START: u16 = 0x2C0;
+/// The display buffer always has room for hi-res, lo-res uses its top left corner
+pub const WIDTH_BYTE: usize = HIRES_WIDTH_PIX / 8;
+pub const HEIGHT_BYTE: usize = HIRES_HEIGHT_PIX;
 
 /// characters 0..f
 /// 5 row tall, 8 pixles wide 
@@ -35,498 +100,746 @@ const CHARACTERS:[u8;5*16] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80, //f
 ];
 
-enum Register {
-    Main(u8),
-    ProgramCounter,
-    Sprite,
-    Delay,
-    Sound,
+/// SUPER-CHIP's large characters 0..f, right after the small ones
+/// 10 rows tall, 8 pixels wide
+const BIG_CHARACTERS_START: usize = CHARACTERS.len();
+#[rustfmt::skip]
+const BIG_CHARACTERS:[u8;10*16] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, //0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, //1
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, //2
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, //3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, //4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, //5
+    0x3E, 0x7C, 0xE0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, //6
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, //7
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, //8
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, //9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, //a
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, //b
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, //c
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, //d
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, //e
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, //f
+];
+
+/// What drives the 60Hz delay and sound timers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimerSource {
+    /// the frontend calls [`Chip8::tick_timers`] on its own 60Hz schedule
+    #[default]
+    External,
+    /// `step` ticks them 60 times for every this many instructions, so their speed
+    /// follows the instruction count rather than the wall clock
+    Instructions(u32),
+}
+
+/// Whether the machine is executing instructions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum State {
+    #[default]
+    Running,
+    /// blocked in Fx0A until a key is pressed and released, which then goes in
+    /// `register`. `key` is the one pressed, once there is one
+    WaitingForKey { register: u8, key: Option<u8> },
+    /// drew a sprite with the `display_wait` quirk, idle until the next timer tick
+    WaitingForVblank,
+    /// jumped to itself or ran SUPER-CHIP's exit, the program is over and nothing
+    /// more runs
+    Halted,
+}
+
+/// What a successful `step` did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    Executed,
+    /// a sprite was drawn, `collision` if it erased any pixel
+    Drew {
+        collision: bool,
+    },
+    /// still blocked in Fx0A, nothing but the timers moved
+    WaitingForKey,
+    /// still waiting out the frame after a draw
+    WaitingForVblank,
+    /// halted, nothing ran
+    Halted,
 }
 
 /// Chip 8 emulator state
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Chip8 {
-    pub memory: [u8; MEMORY_SIZE],
+    #[serde(with = "hex")]
+    pub memory: Vec<u8>,
     pub registers: [u8; 16],
     /// register for storing memory addresses
     pub i: u16,
-    pub input: u8,
+    /// the keypad, bit k set while key k is held
+    pub keys: u16,
+    /// running, or blocked in Fx0A
+    pub state: State,
     /// these two registers are auto decremented at 60hz
     pub delay: u8,
     pub sound: u8,
+    pub timer_source: TimerSource,
+    /// progress towards the next tick with `TimerSource::Instructions`
+    timer_phase: u32,
+    /// VIP cycles the last frame ran over by, see [`Chip8::step_vip_frame`]
+    #[serde(default)]
+    cycle_overrun: u32,
 
     pub program_counter: u16,
+    /// where the rom was loaded and the program began
+    #[serde(default = "default_start")]
+    pub start_address: u16,
     /// the stack stores the address that should be returned to
     pub stack: [u16; 16],
     pub stack_pointer: u8,
+    /// SUPER-CHIP's persistent flags, HP-48 RPL user flags on the original
+    #[serde(default)]
+    pub flags: [u8; 16],
 
+    /// rows of `WIDTH_BYTE` bytes, one bit per pixel with the msb leftmost
+    #[serde(with = "hex")]
     pub display: [u8; WIDTH_BYTE * HEIGHT_BYTE],
+    /// SUPER-CHIP 128x64 mode
+    pub hires: bool,
+    /// two page hi-res CHIP-8, 64x64
+    #[serde(default)]
+    pub two_page: bool,
+    #[serde(skip)]
+    shown: framebuffer::Shown,
     pub rom: Rom,
-    canvas: Canvas,
+    pub quirks: Quirks,
+    /// source of Cxkk's random bytes
+    #[serde(with = "save_state::rng")]
+    rng: ChaCha8Rng,
+    /// replaces `rng` when set, see [`Chip8::with_random`]
+    #[cfg(feature = "std")]
+    #[serde(skip)]
+    random: Option<RandomSource>,
+    /// addresses [`Chip8::run_until_break`] stops at
+    #[serde(skip)]
+    pub breakpoints: BTreeSet<u16>,
+    /// what the breakpoints that have one wait for, see [`Chip8::set_breakpoint`]
+    #[serde(skip)]
+    conditions: BTreeMap<u16, Condition>,
+    #[serde(skip)]
+    pub watchpoints: Vec<Watchpoint>,
+    /// index of the first watchpoint the last step fired
+    #[serde(skip)]
+    pub watch_hit: Option<usize>,
+    /// the first already executed address the last step wrote to
+    #[serde(skip)]
+    pub self_modified: Option<u16>,
+    /// gets every instruction executed
+    #[cfg(feature = "std")]
+    #[serde(skip)]
+    pub tracer: Option<Tracer>,
+    /// callbacks for what happens while stepping, see [`Chip8::on_display_update`]
+    #[cfg(feature = "std")]
+    #[serde(skip)]
+    pub hooks: Hooks,
+    /// per opcode family counts and times, while profiling
+    #[cfg(feature = "std")]
+    #[serde(skip)]
+    profile: Option<Profile>,
+    /// addresses executed and drawn from
+    #[serde(skip)]
+    coverage: Coverage,
 }
 
-struct Instruction {
-    n1: u8,
-    n2: u8,
-    n3: u8,
-    n4: u8,
+/// An rng seeded from the operating system's entropy
+#[cfg(feature = "std")]
+fn new_rng() -> ChaCha8Rng {
+    ChaCha8Rng::from_entropy()
 }
 
-impl Instruction {
-    fn exec(self, state: &mut Chip8) {}
-    fn display(self, state: Chip8) {}
+/// Without std there's no entropy to seed from, embedders wanting different
+/// numbers each run pass their own seed to [`Chip8::with_seed`]
+#[cfg(not(feature = "std"))]
+fn new_rng() -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(0)
 }
+
+fn default_start() -> u16 {
+    PROGRAM_START as u16
+}
+
 impl Chip8 {
-    ///
+    /// `rom` with everything else the default, see [`Chip8::builder`] for more.
+    /// Panics if the rom doesn't fit in memory.
     pub fn new(rom: Rom) -> Chip8 {
-        let mut memory: [u8; MEMORY_SIZE] = [0; MEMORY_SIZE];
+        Self::new_at(rom, PROGRAM_START as u16)
+    }
+
+    /// Load `rom` at `start` and begin there, for roms made for interpreters that
+    /// reserve more than 0x200 bytes, like the ETI 660's 0x600.
+    /// Panics if the rom doesn't fit in memory.
+    pub fn new_at(rom: Rom, start: u16) -> Chip8 {
+        Self::builder(rom)
+            .start_address(start)
+            .build()
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Load `rom` into `memory_size` bytes at `start`
+    fn load(rom: Rom, memory_size: usize, start: u16) -> Chip8 {
+        let mut memory = vec![0; memory_size];
 
         let rom_slice: &[u8] = rom.contents.as_slice();
-        memory[PROGRAM_START..PROGRAM_START + rom_slice.len()].copy_from_slice(rom_slice);
+        let at = start as usize;
+        memory[at..at + rom_slice.len()].copy_from_slice(rom_slice);
 
         memory[0..CHARACTERS.len()].copy_from_slice(&CHARACTERS);
+        memory[BIG_CHARACTERS_START..BIG_CHARACTERS_START + BIG_CHARACTERS.len()]
+            .copy_from_slice(&BIG_CHARACTERS);
 
         Chip8 {
             memory,
             registers: [0; 16],
             i: 0,
-            input: 0,
+            keys: 0,
+            state: State::Running,
             delay: 0,
             sound: 0,
-            program_counter: PROGRAM_START as u16,
+            timer_source: TimerSource::External,
+            timer_phase: 0,
+            cycle_overrun: 0,
+            program_counter: start,
+            start_address: start,
             stack: [0; 16],
             stack_pointer: 0,
+            flags: [0; 16],
             display: [0; WIDTH_BYTE * HEIGHT_BYTE],
+            hires: false,
+            two_page: false,
+            shown: Default::default(),
             rom,
-            canvas: Canvas::new(WIDTH_PIX as u32, HEIGHT_PIX as u32),
+            quirks: Quirks::default(),
+            rng: new_rng(),
+            #[cfg(feature = "std")]
+            random: None,
+            breakpoints: BTreeSet::new(),
+            conditions: BTreeMap::new(),
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            self_modified: None,
+            #[cfg(feature = "std")]
+            tracer: None,
+            #[cfg(feature = "std")]
+            hooks: Hooks::default(),
+            #[cfg(feature = "std")]
+            profile: None,
+            coverage: Coverage::default(),
         }
     }
-    fn set_addr(&mut self, a1: u8, a2: u8, a3: u8) {
-        self.program_counter = assemble_addr(a1, a2, a3) - 2;
+
+    /// Start the rom over, as if just loaded: registers, stack, display and
+    /// timers cleared and the rom image back in memory, undoing whatever the
+    /// program wrote over it. The quirks, timing, persistent flags and
+    /// [debugging](Chip8::take_debugging) stay as they are.
+    pub fn reset(&mut self) {
+        let mut chip8 = Chip8::load(self.rom.clone(), self.memory.len(), self.start_address);
+        chip8.quirks = self.quirks;
+        chip8.timer_source = self.timer_source;
+        chip8.flags = self.flags;
+        chip8.rng = self.rng.clone();
+        // what the frontend shows is still the old display, until it looks again
+        chip8.shown = mem::take(&mut self.shown);
+        #[cfg(feature = "std")]
+        {
+            chip8.random = self.random.take();
+        }
+        chip8.take_debugging(self);
+        if self.two_page {
+            chip8.start_two_page();
+        }
+        *self = chip8;
+    }
+    /// Seed the random number generator, making Cxkk reproducible
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+    pub fn with_timer_source(mut self, timer_source: TimerSource) -> Self {
+        self.timer_source = timer_source;
+        self
+    }
+    /// Run the instruction variants picked by `quirks`
+    pub fn with_quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = quirks;
+        self
     }
 
-    #[allow(dead_code)]
+    /// Run as `platform` would, with its quirks and display. Two page hi-res roms
+    /// skip their leading jump to 0x260 and start at 0x2C0.
+    pub fn with_platform(mut self, platform: Platform) -> Self {
+        self.quirks = platform.into();
+        if platform == Platform::HiresChip8 {
+            self.start_two_page();
+        }
+        self
+    }
+
+    /// Switch to the two page display, skipping the rom's leading jump to 0x260
+    fn start_two_page(&mut self) {
+        self.two_page = true;
+        if self.opcode() == 0x1260 {
+            self.program_counter = TWO_PAGE_START;
+        }
+    }
+
+    #[cfg(test)]
     fn set_memory(&mut self, start_location: u16, data: Vec<u8>) {
-        self.memory[start_location as usize..start_location as usize + data.len() as usize]
+        self.memory[start_location as usize..start_location as usize + data.len()]
             .copy_from_slice(&data);
     }
 
-    pub fn step(&mut self) -> &mut Chip8 {
-        let byte_1 = self.memory[self.program_counter as usize];
-        let n1 = (byte_1 & 0xF0) >> 4;
-        let n2 = byte_1 & 0x0F;
-        let byte_2 = self.memory[(self.program_counter + 1) as usize];
-        let n3 = (byte_2 & 0xF0) >> 4;
-        let n4 = byte_2 & 0x0F;
-        //println!("{:#x},{:#x}", byte_1, byte_2);
-        //println!("{:#x}, {:#x}, {:#x}, {:#x}", n1, n2, n3, n4);
-        //println!("{:?}", state);
-        match (n1, n2, n3, n4) {
-            //// CLS
-            (0, 0, 0x0E, 0x00) => self.display.fill(0),
-            //// RET
-            (0, 0, 0x0E, 0x0E) => {
+    /// The instruction at the program counter, bytes past the end of memory read as 0
+    pub fn opcode(&self) -> u16 {
+        let pc = self.program_counter as usize;
+        let byte = |address: usize| self.memory.get(address).copied().unwrap_or(0) as u16;
+        byte(pc) << 8 | byte(pc + 1)
+    }
+
+    /// The instruction at the program counter, decoded
+    pub fn instruction(&self) -> Instruction {
+        Instruction::decode(self.opcode())
+    }
+
+    /// True when the next instruction jumps to itself, the idiomatic way to end a
+    /// program, or is SUPER-CHIP's exit, or the machine already [halted](State::Halted)
+    pub fn is_spinning(&self) -> bool {
+        let opcode = self.opcode();
+        self.is_halted() || opcode == 0x1000 | self.program_counter || opcode == 0x00FD
+    }
+
+    /// True once the program has ended, see [`State::Halted`]
+    pub fn is_halted(&self) -> bool {
+        self.state == State::Halted
+    }
+
+    /// Display width in the current mode
+    pub fn width(&self) -> usize {
+        match self.hires {
+            true => HIRES_WIDTH_PIX,
+            false => WIDTH_PIX,
+        }
+    }
+
+    /// Display height in the current mode
+    pub fn height(&self) -> usize {
+        match self.hires || self.two_page {
+            true => HIRES_HEIGHT_PIX,
+            false => HEIGHT_PIX,
+        }
+    }
+
+    pub fn key_down(&mut self, key: u8) {
+        self.keys |= 1 << (key & 0xF);
+    }
+
+    pub fn key_up(&mut self, key: u8) {
+        self.keys &= !(1 << (key & 0xF));
+    }
+
+    pub fn is_key_down(&self, key: u8) -> bool {
+        self.keys & 1 << (key & 0xF) != 0
+    }
+
+    /// True while Fx0A can't make progress until the keypad changes, stepping
+    /// before then does nothing but keep the timers going
+    pub fn is_waiting_for_key(&self) -> bool {
+        match self.state {
+            State::Running | State::WaitingForVblank | State::Halted => false,
+            State::WaitingForKey { key: None, .. } => self.keys == 0,
+            State::WaitingForKey { key: Some(key), .. } => self.is_key_down(key),
+        }
+    }
+
+    /// Execute one instruction. On error nothing changed, the program counter
+    /// still points at the instruction that failed.
+    pub fn step(&mut self) -> Result<StepOutcome, Chip8Error> {
+        let before = self.before_step();
+        #[cfg(feature = "std")]
+        let hooked = self.before_hooks();
+        self.self_modified = None;
+        let outcome = match self.state {
+            State::Running => self.execute_observed()?,
+            State::WaitingForVblank => StepOutcome::WaitingForVblank,
+            State::Halted => StepOutcome::Halted,
+            State::WaitingForKey {
+                register,
+                key: None,
+            } => {
+                if self.keys != 0 {
+                    let key = Some(self.keys.trailing_zeros() as u8);
+                    self.state = State::WaitingForKey { register, key };
+                }
+                StepOutcome::WaitingForKey
+            }
+            State::WaitingForKey {
+                register,
+                key: Some(key),
+            } => {
+                if self.is_key_down(key) {
+                    StepOutcome::WaitingForKey
+                } else {
+                    self.registers[register as usize] = key;
+                    self.state = State::Running;
+                    StepOutcome::Executed
+                }
+            }
+        };
+        if let TimerSource::Instructions(rate) = self.timer_source {
+            self.timer_phase += 60;
+            while self.timer_phase >= rate.max(1) {
+                self.timer_phase -= rate.max(1);
+                self.tick_timers();
+            }
+        }
+        self.watch_hit = self.fired_watchpoint(before);
+        #[cfg(feature = "std")]
+        self.fire_hooks(hooked);
+        Ok(outcome)
+    }
+
+    /// Execute the instruction at the program counter, profiled, traced and
+    /// hooked as asked
+    #[cfg(feature = "std")]
+    fn execute_observed(&mut self) -> Result<StepOutcome, Chip8Error> {
+        self.execute_profiled()
+            .inspect_err(|error| self.hook_error(error))
+    }
+
+    /// Execute the instruction at the program counter, there's nothing watching
+    /// without std
+    #[cfg(not(feature = "std"))]
+    fn execute_observed(&mut self) -> Result<StepOutcome, Chip8Error> {
+        self.execute()
+    }
+
+    fn execute(&mut self) -> Result<StepOutcome, Chip8Error> {
+        use Instruction::*;
+        let address = self.program_counter;
+        if address as usize + 1 >= self.memory.len() {
+            return Err(Chip8Error::MemoryOutOfBounds { address });
+        }
+        let mut outcome = StepOutcome::Executed;
+        let skip = |condition: bool| if condition { 2 } else { 0 };
+        let mut next = address.wrapping_add(2);
+        let instruction = self.instruction();
+        let fault = |index| Chip8Error::MemoryFault {
+            address,
+            instruction,
+            index,
+        };
+        match instruction {
+            Cls => self.display.fill(0),
+            //// SUPER-CHIP scrolling, by pixels of the current mode
+            Scd(n) => self.scroll_down(n as usize),
+            Scr => self.scroll_sideways(4),
+            Scl => self.scroll_sideways(-4),
+            //// SUPER-CHIP exit, stays put like a jump to self
+            Exit => {
+                next = address;
+                self.state = State::Halted;
+            }
+            //// SUPER-CHIP lo-res and hi-res, switching clears the display
+            Low | High => {
+                self.hires = instruction == High;
+                self.display.fill(0);
+            }
+            Ret => {
                 // pop sp
-                self.program_counter = self.stack[self.stack_pointer as usize];
+                if self.stack_pointer == 0 {
+                    return Err(Chip8Error::StackUnderflow { address });
+                }
+                if self.stack_pointer as usize >= self.stack.len() {
+                    return Err(Chip8Error::StackPointerOutOfRange {
+                        address,
+                        stack_pointer: self.stack_pointer,
+                    });
+                }
+                next = self.stack[self.stack_pointer as usize].wrapping_add(2);
                 self.stack_pointer -= 1;
             }
-            //// JP addr
-            (0x01, a1, a2, a3) => self.set_addr(a1, a2, a3),
-            //// CALL addr
-            (0x02, a1, a2, a3) => {
-                // push sp
-                self.stack_pointer += 1;
-                self.stack[self.stack_pointer as usize] = self.program_counter;
-                self.set_addr(a1, a2, a3);
-            }
-            //// Eq Vx, NN
-            (0x03, x, n1, n2) => {
-                if self.registers[x as usize] == (n1 << 4 | n2) {
-                    self.program_counter += 2;
+            Jp(nnn) => {
+                next = nnn;
+                if nnn == address {
+                    self.state = State::Halted;
                 }
             }
-            //// Neq Vx,NN
-            (0x04, x, n1, n2) => {
-                if self.registers[x as usize] != (n1 << 4 | n2) {
-                    self.program_counter += 2;
+            Call(nnn) => {
+                // push sp, the first entry is never used
+                if self.stack_pointer as usize + 1 >= self.stack.len() {
+                    return Err(Chip8Error::StackOverflow { address });
                 }
+                self.stack_pointer += 1;
+                self.stack[self.stack_pointer as usize] = address;
+                next = nnn;
+            }
+            SeVx(x, kk) => next += skip(self.registers[x as usize] == kk),
+            SneVx(x, kk) => next += skip(self.registers[x as usize] != kk),
+            SeVxVy(x, y) => next += skip(self.registers[x as usize] == self.registers[y as usize]),
+            LdVx(x, kk) => self.registers[x as usize] = kk,
+            AddVx(x, kk) => {
+                self.registers[x as usize] = self.registers[x as usize].wrapping_add(kk)
             }
-            //// Eq Vx, Vy
-            (0x05, x, y, _) => {
-                if self.registers[x as usize] == self.registers[y as usize] {
-                    self.program_counter += 2;
+            LdVxVy(x, y) => self.registers[x as usize] = self.registers[y as usize],
+            Or(x, y) | And(x, y) | Xor(x, y) => {
+                let vy = self.registers[y as usize];
+                let vx = &mut self.registers[x as usize];
+                match instruction {
+                    Or(..) => *vx |= vy,
+                    And(..) => *vx &= vy,
+                    _ => *vx ^= vy,
+                }
+                if self.quirks.vf_reset {
+                    self.registers[15] = 0;
                 }
             }
-            //// Set Vx=NN
-            (0x06, x, n1, n2) => self.registers[x as usize] = n1 << 4 | n2,
-            (0x07, x, n1, n2) => self.registers[x as usize] += n1 << 4 | n2,
-            (0x08, x, y, 0) => self.registers[x as usize] += self.registers[y as usize],
-            (0x08, x, y, 1) => self.registers[x as usize] |= self.registers[y as usize],
-            (0x08, x, y, 2) => self.registers[x as usize] &= self.registers[y as usize],
-            (0x08, x, y, 3) => self.registers[x as usize] ^= self.registers[y as usize],
-            (0x08, x, y, 4) => {
+            AddVxVy(x, y) => {
                 let (value, overflow) =
                     self.registers[x as usize].overflowing_add(self.registers[y as usize]);
                 self.registers[x as usize] = value;
                 self.registers[15] = overflow as u8;
             }
-            (0x08, x, y, 5) => {
+            Sub(x, y) => {
                 let (value, overflow) =
                     self.registers[x as usize].overflowing_sub(self.registers[y as usize]);
                 self.registers[x as usize] = value;
                 self.registers[15] = (!overflow) as u8;
             }
-            (0x08, x, _, 6) => {
-                self.registers[15] = self.registers[x as usize] | 1;
-                self.registers[x as usize] >>= 1
+            Shr(x, y) => {
+                let value = self.registers[self.shift_source(x, y)];
+                self.registers[x as usize] = value >> 1;
+                self.registers[15] = value & 1;
             }
-            (0x08, x, y, 7) => {
+            Subn(x, y) => {
                 let (value, overflow) =
                     self.registers[y as usize].overflowing_sub(self.registers[x as usize]);
                 self.registers[x as usize] = value;
                 self.registers[15] = (!overflow) as u8;
             }
-            (0x08, x, _, 0xE) => {
-                self.registers[15] = self.registers[x as usize] | 0x8;
-                self.registers[x as usize] <<= 1
+            Shl(x, y) => {
+                let value = self.registers[self.shift_source(x, y)];
+                self.registers[x as usize] = value << 1;
+                self.registers[15] = value >> 7;
+            }
+            SneVxVy(x, y) => next += skip(self.registers[x as usize] != self.registers[y as usize]),
+            LdI(nnn) => self.i = nnn,
+            JpV0(nnn) => {
+                let offset = match self.quirks.jump_uses_vx {
+                    true => self.registers[(nnn >> 8) as usize],
+                    false => self.registers[0],
+                };
+                next = offset as u16 + nnn;
             }
-            (0x09, x, y, 0) => {
-                if self.registers[x as usize] != self.registers[y as usize] {
-                    self.program_counter += 2;
+            Rnd(x, kk) => self.registers[x as usize] = self.random_byte() & kk,
+            Drw(x, y, n) => {
+                // all of the sprite has to be in memory, even rows that get clipped
+                let len = if n == 0 { 32 } else { n as usize };
+                self.check_at_i(len).map_err(fault)?;
+                self.coverage.draw(self.i, len, self.memory.len());
+                let collision = self.draw(x, y, n);
+                outcome = StepOutcome::Drew { collision };
+                if self.quirks.display_wait {
+                    self.state = State::WaitingForVblank;
                 }
             }
-            (0x0A, n1, n2, n3) => self.i = assemble_addr(n1, n2, n3),
-            (0x0B, n1, n2, n3) => {
-                self.program_counter = self.registers[0] as u16 + assemble_addr(n1, n2, n3)
+            Skp(x) => next += skip(self.is_key_down(self.registers[x as usize])),
+            Sknp(x) => next += skip(!self.is_key_down(self.registers[x as usize])),
+            LdVxDt(x) => self.registers[x as usize] = self.delay,
+            //// Wait for a key, the lowest pressed goes in Vx once released
+            LdVxK(x) => {
+                self.state = State::WaitingForKey {
+                    register: x,
+                    key: None,
+                };
+                outcome = StepOutcome::WaitingForKey;
             }
-            (0x0C, x, n1, n2) => {
-                self.registers[x as usize] = rand::random::<u8>() & ((n1 << 4) | n2)
+            LdDtVx(x) => self.delay = self.registers[x as usize],
+            LdStVx(x) => self.sound = self.registers[x as usize],
+            AddIVx(x) => self.add_to_i(self.registers[x as usize] as u16),
+            LdFVx(x) => self.i = (self.registers[x as usize] & 0x0F) as u16 * 5,
+            LdHfVx(x) => {
+                let character = (self.registers[x as usize] & 0x0F) as usize;
+                self.i = (BIG_CHARACTERS_START + character * 10) as u16;
             }
-            //// Draw
-            (0x0D, x, y, n) => {
-                let mut changed = false;
-                for i in 0..n {
-                    let px = self.registers[x as usize];
-                    let py = self.registers[y as usize];
-                    let current_screen = &mut self.display[(px / 8 + ((py + i) * 8)) as usize];
-                    let current_sprite = self.memory[self.i as usize + i as usize];
-                    let fin = *current_screen ^ current_sprite;
-                    changed |= (*current_screen & !fin) != 0;
-                    *current_screen = fin;
-                    let pixel_string = format!("{:08b}", fin);
-                    pixel_string.chars().enumerate().for_each(|(j, p)| {
-                        match p {
-                            '0' => self
-                                .canvas
-                                .unset((j + (px) as usize) as u32, (py + i) as u32),
-                            '1' => self.canvas.set((j + (px) as usize) as u32, (py + i) as u32),
-                            e => panic!("unexpected char:{}", e),
-                        };
-                    });
+            LdBVx(x) => {
+                self.check_at_i(3).map_err(fault)?;
+                let val = self.registers[x as usize];
+                for (offset, digit) in [val / 100, (val % 100) / 10, val % 10]
+                    .into_iter()
+                    .enumerate()
+                {
+                    let at = self.at_i(offset);
+                    self.memory[at] = digit;
+                    self.note_write(at);
                 }
-                self.registers[15] = changed as u8;
             }
-            (0x0E, x, 9, 0x0E) => {
-                if self.input == self.registers[x as usize] {
-                    self.program_counter += 2;
+            LdIVx(x) => {
+                self.check_at_i(x as usize + 1).map_err(fault)?;
+                for i in 0..=x as usize {
+                    let at = self.at_i(i);
+                    self.memory[at] = self.registers[i];
+                    self.note_write(at);
                 }
-            }
-            (0x0E, x, 0x0A, 1) => {
-                if self.input != self.registers[x as usize] {
-                    self.program_counter += 2;
+                if self.quirks.load_store_increments_i {
+                    self.add_to_i(x as u16 + 1);
                 }
             }
-            (0x0F, x, 0, 7) => self.registers[x as usize] = self.delay,
-            (0x0F, x, 0, 0x0A) => self.registers[x as usize] = self.input,
-            (0x0F, x, 1, 5) => self.delay = self.registers[x as usize],
-            (0x0F, x, 1, 8) => self.sound = self.registers[x as usize],
-            (0x0F, x, 1, 0x0E) => self.i += self.registers[x as usize] as u16,
-            (0x0F, x, 2, 0x09) => self.i = x as u16 * 5,
-            (0x0F, x, 3, 3) => {
-                let val = self.registers[x as usize];
-                self.memory[self.i as usize] = val / 100;
-                self.memory[self.i as usize + 1] = (val % 100) / 10;
-                self.memory[self.i as usize + 2] = val % 10;
-            }
-            (0x0F, x, 5, 5) => {
-                for i in 0..=x {
-                    self.memory[self.i as usize + i as usize] = self.registers[i as usize]
+            LdVxI(x) => {
+                self.check_at_i(x as usize + 1).map_err(fault)?;
+                for i in 0..=x as usize {
+                    self.registers[i] = self.memory[self.at_i(i)]
                 }
-            }
-            (0x0F, x, 6, 5) => {
-                for i in 0..=x {
-                    self.registers[i as usize] = self.memory[self.i as usize + i as usize]
+                if self.quirks.load_store_increments_i {
+                    self.add_to_i(x as u16 + 1);
                 }
             }
-
-            _ => {
-                println!(
-                    " Unknown command: {:#x}, {:#x}, {:#x}, {:#x}",
-                    n1, n2, n3, n4
-                );
-                todo!("Not all opcodes implemented!")
+            // SUPER-CHIP flags, kept between runs by the frontend
+            LdRVx(x) => {
+                let x = x as usize;
+                self.flags[..=x].copy_from_slice(&self.registers[..=x]);
             }
+            LdVxR(x) => {
+                let x = x as usize;
+                self.registers[..=x].copy_from_slice(&self.flags[..=x]);
+            }
+            // Two page hi-res clear, a machine code call anywhere else
+            Unknown(0x0230) if self.two_page => self.display.fill(0),
+            Unknown(opcode) => return Err(Chip8Error::UnknownOpcode { address, opcode }),
         }
-        //match (n1, n2, n3, n4) {
-        //    (0, 0, 0xE, 0x0) => self.op_00e0(),
-        //    (0, 0, 0xE, 0xE) => self.op_00ee(),
-        //    (0x1, n1, n2, n3) => self.op_1nnn(n1, n2, n3),
-        //    (0x2, n1, n2, n3) => self.op_2nnn(n1, n2, n3),
-        //    (0x3, x, k1, k2) => self.op_3xkk(x, k1, k2),
-        //    (0x4, x, k1, k2) => self.op_4xkk(x, k1, k2),
-        //    (0x5, x, y, _) => self.op_5xy(x, y),
-        //    (0x6, x, k1, k2) => self.op_6xkk(x, k1, k2),
-        //    (0x7, x, k1, k2) => self.op_7xkk(x, k1, k2),
-        //    (0x8, x, y, 0x0) => self.op_8xy0(x, y),
-        //    (0x8, x, y, 0x1) => self.op_8xy1(x, y),
-        //    (0x8, x, y, 0x2) => self.op_8xy2(x, y),
-        //    (0x8, x, y, 0x3) => self.op_8xy3(x, y),
-        //    (0x8, x, y, 0x4) => self.op_8xy4(x, y),
-        //    (0x8, x, y, 0x5) => self.op_8xy5(x, y),
-        //    (0x8, x, _, 0x6) => self.op_8xy6(x),
-        //    (0x8, x, y, 0x7) => self.op_8xy7(x, y),
-        //    (0x8, x, _, 0xE) => self.op_8xyE(x),
-        //    (0x9, x, y, 0x0) => self.op_9xy0(x, y),
-        //    (0xA, n1, n2, n3) => self.op_Annn(n1, n2, n3),
-        //    (0xB, n1, n2, n3) => self.op_Bnnn(n1, n2, n3),
-        //    (0xC, x, n1, n2) => self.op_Cxnn(x, n1, n2),
-        //    (0xD, x, y, n) => self.op_Dxyn(x, y, n),
-        //    (0xE, x, 0x9, 0xE) => self.opEx9E(x),
-        //    (0xE, x, 0xA, 0x1) => self.opExA1(x),
-        //    (0xF, x, 0x0, 0x7) => self.opFx07(x),
-        //    (0xF, x, 0x0, 0xA) => self.opFx0A(x),
-        //    (0xF, x, 0x1, 0x5) => self.opFx15(x),
-        //    (0xF, x, 0x1, 0x8) => self.opFx18(x),
-        //    (0xF, x, 0x1, 0xE) => self.opFx1E(x),
-        //    (0xF, x, 0x2, 0x9) => self.opFx29(x),
-        //    (0xF, x, 0x3, 0x3) => self.opFx33(x),
-        //    (0xF, x, 0x5, 0x5) => self.opFx55(x),
-        //    (0xF, x, 0x6, 0x5) => self.opFx65(x),
-        //    _ => todo!(),
-        //}
-        //each instruction is 2 bytes
-        self.program_counter += 2;
-        if self.delay > 0 {
-            self.delay -= 1;
-        }
-        if self.sound > 0 {
-            self.sound -= 1;
-        }
-        //self.input = 0;
-        self
-    }
-
-    fn op_00e0(&self) {
-        todo!()
-    }
-
-    fn op_00ee(&self) {
-        todo!()
-    }
-
-    fn op_1nnn(&self, n1: u8, n2: u8, n3: u8) {
-        todo!()
-    }
-
-    fn op_2nnn(&self, n1: u8, n2: u8, n3: u8) {
-        todo!()
-    }
-
-    fn op_3xkk(&self, x: u8, k1: u8, k2: u8) {
-        todo!()
-    }
-
-    fn op_4xkk(&self, x: u8, k1: u8, k2: u8) {
-        todo!()
-    }
-
-    fn op_5xy(&self, x: u8, y: u8) {
-        todo!()
-    }
-
-    fn op_6xkk(&self, x: u8, k1: u8, k2: u8) {
-        todo!()
-    }
-
-    fn op_7xkk(&self, x: u8, k1: u8, k2: u8) {
-        todo!()
-    }
-
-    fn op_8xy0(&self, x: u8, y: u8) {
-        todo!()
-    }
-
-    fn op_8xy1(&self, x: u8, y: u8) {
-        todo!()
-    }
-
-    fn op_8xy2(&self, x: u8, y: u8) {
-        todo!()
-    }
-
-    fn op_8xy3(&self, x: u8, y: u8) {
-        todo!()
-    }
-
-    fn op_8xy4(&self, x: u8, y: u8) {
-        todo!()
-    }
-
-    fn op_8xy5(&self, x: u8, y: u8) {
-        todo!()
-    }
-    fn op_8xy6(&self, x: u8) {
-        todo!()
-    }
-
-    fn op_8xy7(&self, x: u8, y: u8) {
-        todo!()
-    }
-
-    fn op_8xyE(&self, x: u8) {
-        todo!()
-    }
-
-    fn op_9xy0(&self, x: u8, y: u8) {
-        todo!()
+        self.coverage.execute(address);
+        self.program_counter = next;
+        Ok(outcome)
     }
 
-    fn op_Annn(&self, n1: u8, n2: u8, n3: u8) {
-        todo!()
+    /// Move I on by `n`, wrapping around the 4K its 12 bits of address reach
+    fn add_to_i(&mut self, n: u16) {
+        self.i = self.i.wrapping_add(n) % MEMORY_SIZE as u16;
     }
 
-    fn op_Bnnn(&self, n1: u8, n2: u8, n3: u8) {
-        todo!()
-    }
-
-    fn op_Cxnn(&self, x: u8, n1: u8, n2: u8) {
-        todo!()
-    }
-
-    fn op_Dxyn(&self, x: u8, y: u8, n2: u8) {
-        todo!()
-    }
-
-    fn opEx9E(&self, x: u8) {
-        todo!()
-    }
-
-    fn opExA1(&self, x: u8) {
-        todo!()
-    }
-
-    fn opFx07(&self, x: u8) {
-        todo!()
-    }
-
-    fn opFx0A(&self, x: u8) {
-        todo!()
-    }
-
-    fn opFx15(&self, x: u8) {
-        todo!()
-    }
-
-    fn opFx18(&self, x: u8) {
-        todo!()
-    }
-
-    fn opFx1E(&self, x: u8) {
-        todo!()
+    /// Fails with the first index past the end of memory, unless the `len` bytes
+    /// from I all fit or the `wrap_memory` quirk wraps them around
+    fn check_at_i(&self, len: usize) -> Result<(), usize> {
+        let end = self.i as usize + len;
+        match self.quirks.wrap_memory || end <= self.memory.len() {
+            true => Ok(()),
+            false => Err((self.i as usize).max(self.memory.len())),
+        }
     }
 
-    fn opFx29(&self, x: u8) {
-        todo!()
+    /// Index of the byte `offset` past I, once [`Chip8::check_at_i`] has passed
+    fn at_i(&self, offset: usize) -> usize {
+        (self.i as usize + offset) % self.memory.len()
+    }
+
+    /// Xor the sprite at I onto the display at Vx, Vy, returning whether any lit
+    /// pixel was erased
+    fn draw(&mut self, x: u8, y: u8, n: u8) -> bool {
+        let (width, height) = (self.width(), self.height());
+        let row_bytes = width / 8;
+        let px = self.registers[x as usize] as usize % width;
+        let py = self.registers[y as usize] as usize % height;
+        // Dxy0 is SUPER-CHIP's 16x16 sprite, two bytes a row
+        let (rows, sprite_bytes) = match n {
+            0 => (16, 2),
+            n => (n as usize, 1),
+        };
+        let mut changed = false;
+        for row in 0..rows {
+            let mut y = py + row;
+            // sprites are clipped at the bottom edge, or wrap to the top
+            if y >= height {
+                if !self.quirks.wrap_sprites {
+                    break;
+                }
+                y -= height;
+            }
+            // the sprite row left aligned in a word, then moved into place
+            let sprite = (0..sprite_bytes)
+                .map(|b| (self.memory[self.at_i(row * sprite_bytes + b)] as u32) << (24 - 8 * b))
+                .fold(0, |row, byte| row | byte)
+                >> (px % 8);
+            // an unaligned sprite row straddles one more display byte
+            for k in 0..=sprite_bytes {
+                let bits = (sprite >> (24 - 8 * k)) as u8;
+                let mut column = px / 8 + k;
+                // and likewise at the right edge
+                if column >= row_bytes && self.quirks.wrap_sprites {
+                    column -= row_bytes;
+                }
+                if column >= row_bytes || bits == 0 {
+                    continue;
+                }
+                let current_screen = &mut self.display[y * WIDTH_BYTE + column];
+                changed |= *current_screen & bits != 0;
+                *current_screen ^= bits;
+            }
+        }
+        self.registers[15] = changed as u8;
+        changed
+    }
+
+    /// Move the display down `n` rows, blanking the rows uncovered at the top
+    fn scroll_down(&mut self, n: usize) {
+        let bytes = self.width() / 8;
+        for y in (0..self.height()).rev() {
+            for column in 0..bytes {
+                self.display[y * WIDTH_BYTE + column] = match y.checked_sub(n) {
+                    Some(from) => self.display[from * WIDTH_BYTE + column],
+                    None => 0,
+                };
+            }
+        }
     }
 
-    fn opFx33(&self, x: u8) {
-        todo!()
+    /// Move the display right by `n` pixels, or left when negative, `n` under 8
+    fn scroll_sideways(&mut self, n: i8) {
+        let (bytes, height) = (self.width() / 8, self.height());
+        let shift = n.unsigned_abs();
+        for row in self.display.chunks_exact_mut(WIDTH_BYTE).take(height) {
+            let row = &mut row[..bytes];
+            // walk away from the edge pixels move towards, so each byte still
+            // holds its old value when its neighbour reads it
+            if n > 0 {
+                for column in (0..bytes).rev() {
+                    let carry = column.checked_sub(1).map_or(0, |c| row[c] << (8 - shift));
+                    row[column] = row[column] >> shift | carry;
+                }
+            } else {
+                for column in 0..bytes {
+                    let carry = row.get(column + 1).map_or(0, |b| b >> (8 - shift));
+                    row[column] = row[column] << shift | carry;
+                }
+            }
+        }
     }
 
-    fn opFx55(&self, x: u8) {
-        todo!()
+    /// The register 8xy6/8xyE shift
+    fn shift_source(&self, x: u8, y: u8) -> usize {
+        match self.quirks.shift_uses_vy {
+            true => y as usize,
+            false => x as usize,
+        }
     }
 
-    fn opFx65(&self, x: u8) {
-        todo!()
+    /// Count the delay and sound timers down once, frontends call this at 60Hz.
+    /// The tick is also the vertical blank a `display_wait` draw waits for.
+    pub fn tick_timers(&mut self) {
+        self.delay = self.delay.saturating_sub(1);
+        self.sound = self.sound.saturating_sub(1);
+        if self.state == State::WaitingForVblank {
+            self.state = State::Running;
+        }
     }
 }
 
-fn assemble_addr(a1: u8, a2: u8, a3: u8) -> u16 {
-    let a_low: u16 = ((a2 << 4) | a3) as u16;
-    ((a1 as u16) << 8) | a_low
-}
-
-#[test]
-fn cls() {
-    let mut state = Chip8::new("");
-    let mut expected_state = state.clone();
-    state.display.fill(1);
-
-    assert_ne!(state, expected_state);
-    state.set_memory(state.program_counter, vec![0x00, 0x00, 0x0E, 0x00]);
-    state.step();
-    expected_state.program_counter += 2;
-
-    assert_eq!(state, expected_state)
-}
-#[test]
-fn ret() {
-    let mut state = Chip8::new("");
-    state.stack_pointer = 3;
-    state.stack[3] = 0xF0;
-    state.stack[2] = 0xF1;
-    state.stack[1] = 0xF2;
-    state.stack[0] = 0xF3;
-    #[rustfmt::skip]
-    state.set_memory(
-        state.program_counter,
-        vec![
-            0x00, 0x00, 0x0E, 0x0E,
-            0x00, 0x00, 0x0E, 0x0E,
-            0x00, 0x00, 0x0E, 0x0E,
-            0x00, 0x00, 0x0E, 0x0E,
-            0x00, 0x00, 0x0E, 0x0E,
-        ],
-    );
-    let mut expected_state = state.clone();
-
-    state.step();
-    expected_state.stack_pointer = 2;
-    expected_state.program_counter = 0xF0 + 2;
-
-    assert_eq!(state, expected_state);
-
-    state.step();
-    expected_state.stack_pointer = 1;
-    expected_state.program_counter = 0xF1 + 2;
-
-    assert_eq!(state, expected_state);
-
-    state.step();
-    expected_state.stack_pointer = 0;
-    expected_state.program_counter = 0xF2 + 2;
-
-    assert_eq!(state, expected_state);
-
-    state.step();
-    expected_state.stack_pointer = 0xFF;
-    expected_state.program_counter = 0xF3 + 2;
-
-    assert_eq!(state, expected_state);
-}
-
-#[test]
-fn jump() {
-    let mut state = Chip8::new("".into());
-    state.set_memory(state.program_counter, vec![0x01, 0x01, 0x02, 0x03]);
-    let mut expected_state = state.clone();
-
-    expected_state.program_counter = 0x0123 + 2;
-    assert_eq!(state, expected_state);
-    expected_state.program_counter = 0x0456 + 2;
-    assert_ne!(state, expected_state);
-}
+#[cfg(test)]
+mod conformance;
 
 // Implement Debug manually
 impl fmt::Debug for Chip8 {
@@ -542,8 +855,8 @@ impl fmt::Debug for Chip8 {
             .collect::<Vec<_>>()
             .join(", ");
 
-        let memory_pointer = &self.memory
-            [self.program_counter as usize..self.program_counter as usize + 8]
+        let pc = (self.program_counter as usize).min(self.memory.len());
+        let memory_pointer = &self.memory[pc..(pc + 8).min(self.memory.len())]
             .iter()
             .map(|r| format!("{:#04x}", r))
             .collect::<Vec<_>>()
@@ -556,29 +869,10 @@ impl fmt::Debug for Chip8 {
             .collect::<Vec<_>>()
             .join(", ");
 
-        //// Display part of the display array, e.g., a small block or first few pixels
-        //let pixel_strings = &self
-        //    .display
-        //    .iter()
-        //    .map(|r| format!("{:08b}", r))
-        //    .collect::<Vec<_>>();
-
-        //let display_preview: String =
-        //    pixel_strings
-        //        .into_iter()
-        //        .enumerate()
-        //        .fold("".to_owned(), |acc, (i, bit)| {
-        //            if i % 16 == 0 {
-        //                acc + "\n" + bit
-        //            } else {
-        //                acc + bit
-        //            }
-        //        });
-
         // Use `{:#?}` for debug formatting of arrays
         write!(
             f,
-            "\x1B[2J\x1B[1;1H State {{
+            "State {{
     Memory (first 8 bytes): {:?}
     Registers: [{}]
     I Register: {:#06x}
@@ -588,7 +882,6 @@ impl fmt::Debug for Chip8 {
     Memory At Program Counter (next 8 bytes): {:?}
     Stack (first 4 entries): {:?}
     Stack Pointer: {:#x}
-{}
 }}",
             memory_preview,
             registers_display,
@@ -599,7 +892,6 @@ impl fmt::Debug for Chip8 {
             memory_pointer,
             stack_preview,
             self.stack_pointer,
-            self.canvas.frame()
         )
     }
 }
@@ -0,0 +1,138 @@
+//! Breakpoints
+//!
+//! Addresses the program counter stops at, once their [`Condition`] holds if they
+//! have one. They belong to the debugging session rather than to the machine, so
+//! they aren't saved with its state.
+use core::mem;
+
+use super::{Chip8, Chip8Error, Condition};
+
+/// Why [`Chip8::run_until_break`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// the program counter reached a breakpoint, the instruction there hasn't run
+    Breakpoint { address: u16 },
+    /// the last step fired the watchpoint at `index` of `watchpoints`
+    Watchpoint { index: usize },
+    /// blocked in Fx0A, running on would only spin until a key changes
+    WaitingForKey,
+    /// the program ended, nothing more will run
+    Halted,
+    /// [`Chip8::step_over`] or [`Chip8::step_out`] got where it was going
+    Stepped,
+    /// ran the most steps it was allowed to
+    StepLimit,
+}
+
+impl Chip8 {
+    /// Set a breakpoint at `address`, or clear the one already there.
+    /// True if there is one now.
+    pub fn toggle_breakpoint(&mut self, address: u16) -> bool {
+        self.conditions.remove(&address);
+        match self.breakpoints.remove(&address) {
+            true => false,
+            false => self.breakpoints.insert(address),
+        }
+    }
+
+    /// Set a breakpoint at `address`, one that only stops once `condition` holds
+    /// if there is one, replacing whatever was there
+    pub fn set_breakpoint(&mut self, address: u16, condition: Option<Condition>) {
+        self.breakpoints.insert(address);
+        match condition {
+            Some(condition) => self.conditions.insert(address, condition),
+            None => self.conditions.remove(&address),
+        };
+    }
+
+    /// What the breakpoint at `address` waits for, None if it always stops
+    pub fn breakpoint_condition(&self, address: u16) -> Option<&Condition> {
+        self.conditions.get(&address)
+    }
+
+    /// Carry the breakpoints, watchpoints, tracer, hooks, profile and coverage of
+    /// `other` over, for when this state replaces it, as after loading or rewinding
+    pub fn take_debugging(&mut self, other: &mut Chip8) {
+        self.breakpoints = mem::take(&mut other.breakpoints);
+        self.conditions = mem::take(&mut other.conditions);
+        self.watchpoints = mem::take(&mut other.watchpoints);
+        #[cfg(feature = "std")]
+        {
+            self.tracer = other.tracer.take();
+            self.hooks = mem::take(&mut other.hooks);
+            self.profile = other.profile.take();
+        }
+        self.coverage = mem::take(&mut other.coverage);
+    }
+
+    /// True when the next instruction has a breakpoint on it, and the breakpoint's
+    /// condition holds if it has one
+    pub fn at_breakpoint(&self) -> bool {
+        let pc = self.program_counter;
+        self.breakpoints.contains(&pc)
+            && self
+                .conditions
+                .get(&pc)
+                .is_none_or(|condition| condition.holds(self))
+    }
+
+    /// Step until the program counter lands on a breakpoint or a watchpoint fires,
+    /// for at most `max_steps`. A breakpoint under the program counter when called
+    /// doesn't stop it, so calling again resumes from one.
+    pub fn run_until_break(&mut self, max_steps: u32) -> Result<RunOutcome, Chip8Error> {
+        for _ in 0..max_steps {
+            if self.is_waiting_for_key() {
+                return Ok(RunOutcome::WaitingForKey);
+            }
+            if self.is_halted() {
+                return Ok(RunOutcome::Halted);
+            }
+            self.step()?;
+            if let Some(index) = self.watch_hit {
+                return Ok(RunOutcome::Watchpoint { index });
+            }
+            if self.at_breakpoint() {
+                let address = self.program_counter;
+                return Ok(RunOutcome::Breakpoint { address });
+            }
+        }
+        Ok(RunOutcome::StepLimit)
+    }
+
+    /// Step one instruction, all of a subroutine if it's a CALL: step until the
+    /// stack is back to the depth it has now, for at most `max_steps`. Breakpoints
+    /// and watchpoints on the way stop it like [`Chip8::run_until_break`].
+    pub fn step_over(&mut self, max_steps: u32) -> Result<RunOutcome, Chip8Error> {
+        self.run_until_depth(self.stack_pointer, max_steps)
+    }
+
+    /// Step until the subroutine running now returns, for at most `max_steps`.
+    /// Outside of any subroutine there is nothing to return from, and it steps once.
+    pub fn step_out(&mut self, max_steps: u32) -> Result<RunOutcome, Chip8Error> {
+        self.run_until_depth(self.stack_pointer.saturating_sub(1), max_steps)
+    }
+
+    /// Step until the stack is at most `depth` calls deep, or something stops it
+    fn run_until_depth(&mut self, depth: u8, max_steps: u32) -> Result<RunOutcome, Chip8Error> {
+        for _ in 0..max_steps {
+            if self.is_waiting_for_key() {
+                return Ok(RunOutcome::WaitingForKey);
+            }
+            if self.is_halted() {
+                return Ok(RunOutcome::Halted);
+            }
+            self.step()?;
+            if let Some(index) = self.watch_hit {
+                return Ok(RunOutcome::Watchpoint { index });
+            }
+            if self.stack_pointer <= depth {
+                return Ok(RunOutcome::Stepped);
+            }
+            if self.at_breakpoint() {
+                let address = self.program_counter;
+                return Ok(RunOutcome::Breakpoint { address });
+            }
+        }
+        Ok(RunOutcome::StepLimit)
+    }
+}
@@ -0,0 +1,111 @@
+//! Building a machine
+//!
+//! Everything fixed before the first instruction runs, in one place, so a frontend
+//! can set what the command line asked for and find out whether the rom fits
+//! before anything runs.
+use super::{
+    is_valid_memory_size, Chip8, Chip8Error, Platform, Quirks, TimerSource, BIG_CHARACTERS,
+    BIG_CHARACTERS_START, MEMORY_SIZE, PROGRAM_START,
+};
+use crate::rom::Rom;
+
+/// Settings for a new [`Chip8`], from [`Chip8::builder`]
+#[derive(Clone)]
+pub struct Chip8Builder {
+    rom: Rom,
+    quirks: Quirks,
+    platform: Option<Platform>,
+    speed: Option<u32>,
+    seed: Option<u64>,
+    memory_size: usize,
+    start_address: u16,
+}
+
+impl Chip8 {
+    /// Start building a machine running `rom`
+    pub fn builder(rom: Rom) -> Chip8Builder {
+        Chip8Builder {
+            rom,
+            quirks: Quirks::default(),
+            platform: None,
+            speed: None,
+            seed: None,
+            memory_size: MEMORY_SIZE,
+            start_address: PROGRAM_START as u16,
+        }
+    }
+}
+
+impl Chip8Builder {
+    /// Run the instruction variants picked by `quirks`
+    pub fn quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    /// Run as `platform` would, see [`Chip8::with_platform`]
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.quirks = platform.into();
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Tick the timers every `speed` / 60 instructions, as if the machine ran
+    /// `speed` instructions a second, instead of leaving them to the frontend
+    pub fn speed(mut self, speed: u32) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Seed the random number generator, making Cxkk reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Bytes of memory, at most the 4K 12 bit addresses reach, e.g. 2K for an
+    /// early VIP
+    pub fn memory_size(mut self, size: usize) -> Self {
+        self.memory_size = size;
+        self
+    }
+
+    /// Where to load the rom and start, see [`Chip8::new_at`]
+    pub fn start_address(mut self, start: u16) -> Self {
+        self.start_address = start;
+        self
+    }
+
+    /// The machine, or why it can't be built, the memory size out of range, the
+    /// start address in the fonts or past the end of memory, or the rom too large
+    /// to fit after it
+    pub fn build(self) -> Result<Chip8, Chip8Error> {
+        let size = self.memory_size;
+        if !is_valid_memory_size(size) {
+            return Err(Chip8Error::InvalidMemorySize { size });
+        }
+        let start = self.start_address as usize;
+        if start > size || start < BIG_CHARACTERS_START + BIG_CHARACTERS.len() {
+            return Err(Chip8Error::InvalidStartAddress {
+                start: self.start_address,
+            });
+        }
+        let room = size.saturating_sub(self.start_address as usize);
+        if self.rom.contents.len() > room {
+            let size = self.rom.contents.len();
+            return Err(Chip8Error::RomTooLarge { size, room });
+        }
+        let mut chip8 = Chip8::load(self.rom, size, self.start_address);
+        if let Some(platform) = self.platform {
+            chip8 = chip8.with_platform(platform);
+        }
+        chip8.quirks = self.quirks;
+        if let Some(speed) = self.speed {
+            chip8.timer_source = TimerSource::Instructions(speed);
+        }
+        if let Some(seed) = self.seed {
+            chip8 = chip8.with_seed(seed);
+        }
+        Ok(chip8)
+    }
+}
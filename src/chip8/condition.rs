@@ -0,0 +1,205 @@
+//! Breakpoint conditions
+//!
+//! A breakpoint can wait for the machine to be in a particular state, like
+//! `v0 == 3` or `[0x300] != 0 && i >= 0x400`, so a loop can run until the
+//! interesting iteration. Numbers are hex, with or without `0x`. `&&` binds
+//! tighter than `||` and there are no parentheses.
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+};
+use core::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::Chip8;
+
+/// A value a condition reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    V(u8),
+    I,
+    Pc,
+    Delay,
+    Sound,
+    /// the byte at an address, `[0x300]`
+    Memory(u16),
+    /// the byte at I, `[i]`
+    AtI,
+    Literal(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Saved as it's written, `v0 == 3`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Condition {
+    Compare(Operand, Comparison, Operand),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl Operand {
+    /// What it reads in `chip8` now
+    pub fn value(self, chip8: &Chip8) -> u16 {
+        let byte = |address: usize| chip8.memory.get(address).copied().unwrap_or(0) as u16;
+        match self {
+            Operand::V(x) => chip8.registers[x as usize & 0xF] as u16,
+            Operand::I => chip8.i,
+            Operand::Pc => chip8.program_counter,
+            Operand::Delay => chip8.delay as u16,
+            Operand::Sound => chip8.sound as u16,
+            Operand::Memory(address) => byte(address as usize),
+            Operand::AtI => byte(chip8.i as usize),
+            Operand::Literal(value) => value,
+        }
+    }
+}
+
+impl Comparison {
+    const ALL: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Eq),
+        ("!=", Comparison::Ne),
+        ("<=", Comparison::Le),
+        (">=", Comparison::Ge),
+        ("<", Comparison::Lt),
+        (">", Comparison::Gt),
+    ];
+
+    fn holds(self, left: u16, right: u16) -> bool {
+        match self {
+            Comparison::Eq => left == right,
+            Comparison::Ne => left != right,
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+impl Condition {
+    /// Whether `chip8` is in the state the condition asks for
+    pub fn holds(&self, chip8: &Chip8) -> bool {
+        match self {
+            Condition::Compare(left, comparison, right) => {
+                comparison.holds(left.value(chip8), right.value(chip8))
+            }
+            Condition::And(left, right) => left.holds(chip8) && right.holds(chip8),
+            Condition::Or(left, right) => left.holds(chip8) || right.holds(chip8),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::V(x) => write!(f, "v{x:x}"),
+            Operand::I => f.write_str("i"),
+            Operand::Pc => f.write_str("pc"),
+            Operand::Delay => f.write_str("dt"),
+            Operand::Sound => f.write_str("st"),
+            Operand::Memory(address) => write!(f, "[{address:#x}]"),
+            Operand::AtI => f.write_str("[i]"),
+            Operand::Literal(value) => write!(f, "{value:#x}"),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Compare(left, comparison, right) => {
+                let (symbol, _) = Comparison::ALL
+                    .iter()
+                    .find(|(_, c)| c == comparison)
+                    .ok_or(fmt::Error)?;
+                write!(f, "{left} {symbol} {right}")
+            }
+            Condition::And(left, right) => write!(f, "{left} && {right}"),
+            Condition::Or(left, right) => write!(f, "{left} || {right}"),
+        }
+    }
+}
+
+fn hex(text: &str) -> Result<u16, String> {
+    u16::from_str_radix(text.trim_start_matches("0x"), 16)
+        .map_err(|_| format!("bad number `{text}`"))
+}
+
+/// A register, `i`, `pc`, `dt`, `st`, a byte of memory like `[0x300]` or `[i]`,
+/// or a number
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(address) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            return match address.trim() {
+                "i" | "I" => Ok(Operand::AtI),
+                address => hex(address).map(Operand::Memory),
+            };
+        }
+        match s {
+            "" => Err("missing operand".into()),
+            "i" | "I" => Ok(Operand::I),
+            "pc" | "PC" => Ok(Operand::Pc),
+            "dt" | "DT" => Ok(Operand::Delay),
+            "st" | "ST" => Ok(Operand::Sound),
+            _ => match s.strip_prefix(['v', 'V']) {
+                Some(x) => match u8::from_str_radix(x, 16) {
+                    Ok(x) if x < 16 => Ok(Operand::V(x)),
+                    _ => Err(format!("no register `{s}`")),
+                },
+                None => hex(s).map(Operand::Literal),
+            },
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((left, right)) = s.split_once("||") {
+            let (left, right) = (left.parse()?, right.parse()?);
+            return Ok(Condition::Or(Box::new(left), Box::new(right)));
+        }
+        if let Some((left, right)) = s.split_once("&&") {
+            let (left, right) = (left.parse()?, right.parse()?);
+            return Ok(Condition::And(Box::new(left), Box::new(right)));
+        }
+        let (at, symbol, comparison) = Comparison::ALL
+            .iter()
+            .filter_map(|&(symbol, comparison)| Some((s.find(symbol)?, symbol, comparison)))
+            .min_by_key(|&(at, ..)| at)
+            .ok_or_else(|| format!("no comparison in `{}`", s.trim()))?;
+        let left = s[..at].parse()?;
+        let right = s[at + symbol.len()..].parse()?;
+        Ok(Condition::Compare(left, comparison, right))
+    }
+}
+
+impl From<Condition> for String {
+    fn from(condition: Condition) -> String {
+        condition.to_string()
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
@@ -0,0 +1,757 @@
+//! Property based opcode conformance tests
+//!
+//! Each property builds a machine with random registers, places a single opcode at
+//! the program start and checks the documented behaviour after one `step()`.
+use proptest::prelude::*;
+
+use super::*;
+
+/// A machine with the given registers and `opcode` loaded at the program start
+fn machine(registers: [u8; 16], opcode: u16) -> Chip8 {
+    let mut chip8 = Chip8::new(Rom::from_bytes(
+        "conformance",
+        opcode.to_be_bytes().to_vec(),
+    ));
+    chip8.registers = registers;
+    chip8
+}
+
+/// Any general purpose register except VF, which doubles as the flag register
+fn reg() -> impl Strategy<Value = u8> {
+    0..15u8
+}
+
+fn op_xy(n1: u16, x: u8, y: u8, n4: u16) -> u16 {
+    n1 << 12 | (x as u16) << 8 | (y as u16) << 4 | n4
+}
+
+fn op_xkk(n1: u16, x: u8, kk: u8) -> u16 {
+    n1 << 12 | (x as u16) << 8 | kk as u16
+}
+
+const START: u16 = PROGRAM_START as u16;
+
+proptest! {
+    #[test]
+    fn cls_clears_display(display in prop::collection::vec(any::<u8>(), WIDTH_BYTE * HEIGHT_BYTE)) {
+        let mut chip8 = machine([0; 16], 0x00E0);
+        chip8.display.copy_from_slice(&display);
+        chip8.step().unwrap();
+        prop_assert!(chip8.display.iter().all(|b| *b == 0));
+        prop_assert_eq!(chip8.program_counter, START + 2);
+    }
+
+    #[test]
+    fn jp_sets_pc(addr in 0x200..0x1000u16) {
+        let mut chip8 = machine([0; 16], 0x1000 | addr);
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.program_counter, addr);
+    }
+
+    #[test]
+    fn jump_to_self_spins(addr in 0x202..0xFFEu16) {
+        let mut chip8 = machine([0; 16], 0x1000 | addr);
+        prop_assert!(!chip8.is_spinning());
+        chip8.set_memory(addr, (0x1000 | addr).to_be_bytes().to_vec());
+        chip8.step().unwrap();
+        prop_assert!(chip8.is_spinning());
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.program_counter, addr);
+    }
+
+    #[test]
+    fn call_then_ret_returns_after_call(addr in 0x202..0xFFEu16) {
+        let mut chip8 = machine([0; 16], 0x2000 | addr);
+        chip8.set_memory(addr, vec![0x00, 0xEE]);
+        let depth = chip8.stack_pointer;
+
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.program_counter, addr);
+        prop_assert_eq!(chip8.stack_pointer, depth + 1);
+
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.program_counter, START + 2);
+        prop_assert_eq!(chip8.stack_pointer, depth);
+    }
+
+    #[test]
+    fn se_vx_kk_skips_when_equal(regs in any::<[u8; 16]>(), x in reg(), kk: u8) {
+        let mut chip8 = machine(regs, op_xkk(0x3, x, kk));
+        chip8.step().unwrap();
+        let expected = if regs[x as usize] == kk { START + 4 } else { START + 2 };
+        prop_assert_eq!(chip8.program_counter, expected);
+    }
+
+    #[test]
+    fn sne_vx_kk_skips_when_different(regs in any::<[u8; 16]>(), x in reg(), kk: u8) {
+        let mut chip8 = machine(regs, op_xkk(0x4, x, kk));
+        chip8.step().unwrap();
+        let expected = if regs[x as usize] != kk { START + 4 } else { START + 2 };
+        prop_assert_eq!(chip8.program_counter, expected);
+    }
+
+    #[test]
+    fn se_vx_vy_skips_when_equal(regs in any::<[u8; 16]>(), x in reg(), y in reg()) {
+        let mut chip8 = machine(regs, op_xy(0x5, x, y, 0));
+        chip8.step().unwrap();
+        let expected = if regs[x as usize] == regs[y as usize] { START + 4 } else { START + 2 };
+        prop_assert_eq!(chip8.program_counter, expected);
+    }
+
+    #[test]
+    fn sne_vx_vy_skips_when_different(regs in any::<[u8; 16]>(), x in reg(), y in reg()) {
+        let mut chip8 = machine(regs, op_xy(0x9, x, y, 0));
+        chip8.step().unwrap();
+        let expected = if regs[x as usize] != regs[y as usize] { START + 4 } else { START + 2 };
+        prop_assert_eq!(chip8.program_counter, expected);
+    }
+
+    #[test]
+    fn ld_vx_kk(regs in any::<[u8; 16]>(), x in 0..16u8, kk: u8) {
+        let mut chip8 = machine(regs, op_xkk(0x6, x, kk));
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[x as usize], kk);
+        prop_assert_eq!(chip8.program_counter, START + 2);
+    }
+
+    #[test]
+    fn add_vx_kk_wraps_without_touching_vf(regs in any::<[u8; 16]>(), x in reg(), kk: u8) {
+        let mut chip8 = machine(regs, op_xkk(0x7, x, kk));
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[x as usize], regs[x as usize].wrapping_add(kk));
+        prop_assert_eq!(chip8.registers[15], regs[15]);
+    }
+
+    #[test]
+    fn alu_ops(regs in any::<[u8; 16]>(), x in reg(), y in reg(), op in 0..4u16) {
+        let mut chip8 = machine(regs, op_xy(0x8, x, y, op));
+        chip8.step().unwrap();
+        let (vx, vy) = (regs[x as usize], regs[y as usize]);
+        let expected = match op {
+            0 => vy,
+            1 => vx | vy,
+            2 => vx & vy,
+            _ => vx ^ vy,
+        };
+        prop_assert_eq!(chip8.registers[x as usize], expected);
+        prop_assert_eq!(chip8.program_counter, START + 2);
+    }
+
+    #[test]
+    fn add_vx_vy_sets_carry(regs in any::<[u8; 16]>(), x in reg(), y in reg()) {
+        let mut chip8 = machine(regs, op_xy(0x8, x, y, 4));
+        chip8.step().unwrap();
+        let sum = regs[x as usize] as u16 + regs[y as usize] as u16;
+        prop_assert_eq!(chip8.registers[x as usize], sum as u8);
+        prop_assert_eq!(chip8.registers[15], (sum > 0xFF) as u8);
+    }
+
+    #[test]
+    fn sub_vx_vy_sets_not_borrow(regs in any::<[u8; 16]>(), x in reg(), y in reg()) {
+        let mut chip8 = machine(regs, op_xy(0x8, x, y, 5));
+        chip8.step().unwrap();
+        let (vx, vy) = (regs[x as usize], regs[y as usize]);
+        prop_assert_eq!(chip8.registers[x as usize], vx.wrapping_sub(vy));
+        prop_assert_eq!(chip8.registers[15], (vx >= vy) as u8);
+    }
+
+    #[test]
+    fn subn_vx_vy_sets_not_borrow(regs in any::<[u8; 16]>(), x in reg(), y in reg()) {
+        let mut chip8 = machine(regs, op_xy(0x8, x, y, 7));
+        chip8.step().unwrap();
+        let (vx, vy) = (regs[x as usize], regs[y as usize]);
+        prop_assert_eq!(chip8.registers[x as usize], vy.wrapping_sub(vx));
+        prop_assert_eq!(chip8.registers[15], (vy >= vx) as u8);
+    }
+
+    #[test]
+    fn shr_moves_lsb_into_vf(regs in any::<[u8; 16]>(), x in reg(), y in reg()) {
+        let mut chip8 = machine(regs, op_xy(0x8, x, y, 6));
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[x as usize], regs[x as usize] >> 1);
+        prop_assert_eq!(chip8.registers[15], regs[x as usize] & 1);
+    }
+
+    #[test]
+    fn shl_moves_msb_into_vf(regs in any::<[u8; 16]>(), x in reg(), y in reg()) {
+        let mut chip8 = machine(regs, op_xy(0x8, x, y, 0xE));
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[x as usize], regs[x as usize] << 1);
+        prop_assert_eq!(chip8.registers[15], regs[x as usize] >> 7);
+    }
+
+    #[test]
+    fn ld_i_addr(addr in 0..0x1000u16) {
+        let mut chip8 = machine([0; 16], 0xA000 | addr);
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.i, addr);
+    }
+
+    #[test]
+    fn jp_v0_addr(regs in any::<[u8; 16]>(), addr in 0x200..0xF00u16) {
+        let mut chip8 = machine(regs, 0xB000 | addr);
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.program_counter, addr + regs[0] as u16);
+    }
+
+    #[test]
+    fn rnd_is_masked(regs in any::<[u8; 16]>(), x in 0..16u8, kk: u8) {
+        let mut chip8 = machine(regs, op_xkk(0xC, x, kk));
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[x as usize] & !kk, 0);
+    }
+
+    #[test]
+    fn drawing_twice_erases_and_reports_collision(
+        regs in any::<[u8; 16]>(),
+        x in reg(),
+        y in reg(),
+        column in 0..(WIDTH_PIX as u8 / 8),
+        row in 0..(HEIGHT_PIX as u8 - 15),
+        n in 1..16u8,
+        sprite in prop::collection::vec(1..=255u8, 15),
+    ) {
+        prop_assume!(x != y);
+        let draw = 0xD000 | (x as u16) << 8 | (y as u16) << 4 | n as u16;
+        let mut chip8 = machine(regs, draw);
+        chip8.set_memory(START + 2, draw.to_be_bytes().to_vec());
+        chip8.set_memory(0x300, sprite);
+        chip8.i = 0x300;
+        chip8.registers[x as usize] = column * 8;
+        chip8.registers[y as usize] = row;
+
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[15], 0);
+        prop_assert!(chip8.display.iter().any(|b| *b != 0));
+
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[15], 1);
+        prop_assert!(chip8.display.iter().all(|b| *b == 0));
+    }
+
+    /// Every sprite pixel lands at its own x and y, whatever the alignment, and
+    /// past the edges is clipped or wraps around as the quirk says
+    #[test]
+    fn draw_places_each_pixel(
+        vx: u8,
+        vy: u8,
+        n in 1..16u8,
+        sprite in prop::collection::vec(any::<u8>(), 15),
+        wrap_sprites: bool,
+    ) {
+        let mut chip8 = machine([0; 16], 0xD01 << 4 | n as u16)
+            .with_quirks(Quirks { wrap_sprites, ..Quirks::default() });
+        chip8.set_memory(0x300, sprite.clone());
+        chip8.i = 0x300;
+        chip8.registers[0] = vx;
+        chip8.registers[1] = vy;
+        chip8.step().unwrap();
+
+        let mut expected = [[false; WIDTH_PIX]; HEIGHT_PIX];
+        for (row, bits) in sprite.iter().take(n as usize).enumerate() {
+            for bit in 0..8 {
+                let x = vx as usize % WIDTH_PIX + bit;
+                let y = vy as usize % HEIGHT_PIX + row;
+                if bits & 0x80 >> bit == 0 || (!wrap_sprites && (x >= WIDTH_PIX || y >= HEIGHT_PIX)) {
+                    continue;
+                }
+                expected[y % HEIGHT_PIX][x % WIDTH_PIX] = true;
+            }
+        }
+        for (y, row) in expected.iter().enumerate() {
+            for (x, lit) in row.iter().enumerate() {
+                prop_assert_eq!(chip8.pixel(x, y), *lit, "pixel {}, {}", x, y);
+            }
+        }
+        prop_assert_eq!(chip8.registers[15], 0);
+    }
+
+    #[test]
+    fn skp_and_sknp_follow_keys(regs in any::<[u8; 16]>(), x in 0..16u8, keys: u16, sknp: bool) {
+        let mut chip8 = machine(regs, op_xkk(0xE, x, if sknp { 0xA1 } else { 0x9E }));
+        chip8.keys = keys;
+        chip8.step().unwrap();
+        let held = keys & 1 << (regs[x as usize] & 0xF) != 0;
+        let skip = held != sknp;
+        prop_assert_eq!(chip8.program_counter, if skip { START + 4 } else { START + 2 });
+    }
+
+    /// Fx0A blocks until a key goes down and back up, then stores the lowest one pressed
+    #[test]
+    fn ld_vx_k_waits_for_press_and_release(
+        regs in any::<[u8; 16]>(),
+        x in 0..16u8,
+        keys in 1..=u16::MAX,
+        idle in 0..10usize,
+    ) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, 0x0A));
+        let waiting = |key| State::WaitingForKey { register: x, key };
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.state, waiting(None));
+        prop_assert_eq!(chip8.program_counter, START + 2);
+        for _ in 0..idle {
+            prop_assert!(chip8.is_waiting_for_key());
+            chip8.step().unwrap();
+        }
+
+        let key = keys.trailing_zeros() as u8;
+        chip8.keys = keys;
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.state, waiting(Some(key)));
+        for _ in 0..idle {
+            prop_assert!(chip8.is_waiting_for_key());
+            chip8.step().unwrap();
+        }
+
+        chip8.key_up(key);
+        prop_assert!(!chip8.is_waiting_for_key());
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.state, State::Running);
+        prop_assert_eq!(chip8.registers[x as usize], key);
+        prop_assert_eq!(chip8.program_counter, START + 2);
+    }
+
+    #[test]
+    fn keys_go_down_and_up(keys: u16, key in 0..16u8) {
+        let mut chip8 = machine([0; 16], 0x1200);
+        chip8.keys = keys;
+        chip8.key_down(key);
+        prop_assert!(chip8.is_key_down(key));
+        prop_assert_eq!(chip8.keys, keys | 1 << key);
+        chip8.key_up(key);
+        prop_assert!(!chip8.is_key_down(key));
+        prop_assert_eq!(chip8.keys, keys & !(1 << key));
+    }
+
+    #[test]
+    fn ld_vx_dt_reads_delay(regs in any::<[u8; 16]>(), x in 0..16u8, delay: u8) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, 0x07));
+        chip8.delay = delay;
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[x as usize], delay);
+    }
+
+    #[test]
+    fn ld_timers_from_vx(regs in any::<[u8; 16]>(), x in 0..16u8, sound: bool) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, if sound { 0x18 } else { 0x15 }));
+        chip8.step().unwrap();
+        let timer = if sound { chip8.sound } else { chip8.delay };
+        prop_assert_eq!(timer, regs[x as usize]);
+    }
+
+    /// Counted in instructions, timers tick 60 times per `rate` steps
+    #[test]
+    fn timers_follow_instruction_count(rate in 60..2000u32, steps in 0..4000u32) {
+        let mut chip8 = machine([0; 16], 0x1200).with_timer_source(TimerSource::Instructions(rate));
+        chip8.delay = 0xFF;
+        for _ in 0..steps {
+            chip8.step().unwrap();
+        }
+        let ticks = (steps as u64 * 60 / rate as u64).min(0xFF) as u8;
+        prop_assert_eq!(chip8.delay, 0xFF - ticks);
+    }
+
+    /// Timers only move when ticked, never by stepping
+    #[test]
+    fn timers_count_down_to_zero(delay: u8, sound: u8) {
+        let mut chip8 = machine([0; 16], 0x6000);
+        chip8.delay = delay;
+        chip8.sound = sound;
+        chip8.step().unwrap();
+        prop_assert_eq!((chip8.delay, chip8.sound), (delay, sound));
+        chip8.tick_timers();
+        prop_assert_eq!(
+            (chip8.delay, chip8.sound),
+            (delay.saturating_sub(1), sound.saturating_sub(1))
+        );
+    }
+
+    #[test]
+    fn add_i_vx(regs in any::<[u8; 16]>(), x in 0..16u8, i: u16) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, 0x1E));
+        chip8.i = i;
+        chip8.step().unwrap();
+        let sum = i as usize + regs[x as usize] as usize;
+        prop_assert_eq!(chip8.i as usize, sum % MEMORY_SIZE);
+    }
+
+    #[test]
+    fn ld_f_vx_points_at_font(regs in any::<[u8; 16]>(), x in 0..16u8) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, 0x29));
+        chip8.step().unwrap();
+        let digit = (regs[x as usize] & 0x0F) as usize;
+        prop_assert_eq!(chip8.i as usize, digit * 5);
+        prop_assert_eq!(&chip8.memory[digit * 5..digit * 5 + 5], &CHARACTERS[digit * 5..digit * 5 + 5]);
+    }
+
+    #[test]
+    fn ld_b_vx_stores_bcd(regs in any::<[u8; 16]>(), x in 0..16u8, i in 0x300..0xF00u16) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, 0x33));
+        chip8.i = i;
+        chip8.step().unwrap();
+        let digits = &chip8.memory[i as usize..i as usize + 3];
+        let vx = regs[x as usize];
+        prop_assert_eq!(digits, &[vx / 100, vx / 10 % 10, vx % 10]);
+    }
+
+    #[test]
+    fn store_then_load_registers_round_trips(
+        regs in any::<[u8; 16]>(),
+        x in 0..16u8,
+        i in 0x300..0xF00u16,
+    ) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, 0x55));
+        chip8.set_memory(START + 2, op_xkk(0xF, x, 0x65).to_be_bytes().to_vec());
+        chip8.i = i;
+
+        chip8.step().unwrap();
+        prop_assert_eq!(&chip8.memory[i as usize..=i as usize + x as usize], &regs[..=x as usize]);
+
+        chip8.registers = [0; 16];
+        chip8.step().unwrap();
+        prop_assert_eq!(&chip8.registers[..=x as usize], &regs[..=x as usize]);
+        prop_assert_eq!(chip8.i, i);
+    }
+}
+
+fn quirky(registers: [u8; 16], opcode: u16, quirks: Quirks) -> Chip8 {
+    machine(registers, opcode).with_quirks(quirks)
+}
+
+proptest! {
+    #[test]
+    fn shift_quirk_shifts_vy(regs in any::<[u8; 16]>(), x in reg(), y in reg(), left: bool) {
+        let quirks = Quirks { shift_uses_vy: true, ..Quirks::default() };
+        let mut chip8 = quirky(regs, op_xy(0x8, x, y, if left { 0xE } else { 6 }), quirks);
+        chip8.step().unwrap();
+        let vy = regs[y as usize];
+        let (shifted, flag) = if left { (vy << 1, vy >> 7) } else { (vy >> 1, vy & 1) };
+        prop_assert_eq!(chip8.registers[x as usize], shifted);
+        prop_assert_eq!(chip8.registers[15], flag);
+    }
+
+    #[test]
+    fn load_store_quirk_increments_i(
+        regs in any::<[u8; 16]>(),
+        x in 0..16u8,
+        i in 0x300..0xF00u16,
+        load: bool,
+    ) {
+        let quirks = Quirks { load_store_increments_i: true, ..Quirks::default() };
+        let mut chip8 = quirky(regs, op_xkk(0xF, x, if load { 0x65 } else { 0x55 }), quirks);
+        chip8.i = i;
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.i, i + x as u16 + 1);
+    }
+
+    #[test]
+    fn jump_quirk_adds_vx(regs in any::<[u8; 16]>(), addr in 0x200..0xF00u16) {
+        let quirks = Quirks { jump_uses_vx: true, ..Quirks::default() };
+        let mut chip8 = quirky(regs, 0xB000 | addr, quirks);
+        chip8.step().unwrap();
+        let x = (addr >> 8) as usize;
+        prop_assert_eq!(chip8.program_counter, addr + regs[x] as u16);
+    }
+
+    #[test]
+    fn vf_reset_quirk_clears_vf(regs in any::<[u8; 16]>(), x in reg(), y in reg(), op in 1..4u16) {
+        let quirks = Quirks { vf_reset: true, ..Quirks::default() };
+        let mut chip8 = quirky(regs, op_xy(0x8, x, y, op), quirks);
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.registers[15], 0);
+    }
+
+    /// A sprite hanging off the bottom right corner is clipped, or wraps to the
+    /// other three corners
+    #[test]
+    fn sprites_clip_or_wrap_at_the_edges(
+        x in (WIDTH_PIX - 7) as u8..WIDTH_PIX as u8,
+        y in (HEIGHT_PIX - 14) as u8..HEIGHT_PIX as u8,
+        wrap: bool,
+    ) {
+        let quirks = Quirks { wrap_sprites: wrap, ..Quirks::default() };
+        let mut chip8 = quirky([0; 16], 0xD01F, quirks);
+        chip8.set_memory(0x300, vec![0xFF; 15]);
+        chip8.i = 0x300;
+        chip8.registers[0] = x;
+        chip8.registers[1] = y;
+        chip8.step().unwrap();
+
+        let (x, y) = (x as usize, y as usize);
+        prop_assert!(chip8.pixel(x, y));
+        prop_assert_eq!(chip8.pixel(0, 0), wrap);
+        prop_assert_eq!(chip8.pixel(0, y), wrap);
+        prop_assert_eq!(chip8.pixel(x, 0), wrap);
+        let lit = chip8.display.iter().map(|b| b.count_ones() as usize).sum::<usize>();
+        let visible = (WIDTH_PIX - x) * (HEIGHT_PIX - y);
+        prop_assert_eq!(lit, if wrap { 8 * 15 } else { visible });
+    }
+}
+
+/// A machine running `program` with the display already in hi-res
+fn hires(program: &[u16]) -> Chip8 {
+    let mut chip8 = machine([0; 16], 0x00FF);
+    let bytes = program.iter().flat_map(|op| op.to_be_bytes()).collect();
+    chip8.set_memory(START + 2, bytes);
+    chip8.step().unwrap();
+    chip8
+}
+
+fn lit(chip8: &Chip8) -> Vec<(usize, usize)> {
+    (0..chip8.height())
+        .flat_map(|y| (0..chip8.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| chip8.pixel(x, y))
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn resolution_switch_clears_display(
+        display in prop::collection::vec(any::<u8>(), WIDTH_BYTE * HEIGHT_BYTE),
+        high: bool,
+    ) {
+        let mut chip8 = machine([0; 16], if high { 0x00FF } else { 0x00FE });
+        chip8.hires = !high;
+        chip8.display.copy_from_slice(&display);
+        chip8.step().unwrap();
+        prop_assert_eq!(chip8.hires, high);
+        prop_assert_eq!(chip8.width(), if high { HIRES_WIDTH_PIX } else { WIDTH_PIX });
+        prop_assert!(chip8.display.iter().all(|b| *b == 0));
+    }
+
+    /// Dxy0 in hi-res draws a 16x16 sprite from 32 bytes
+    #[test]
+    fn large_sprite_is_16_by_16(
+        x in 0..(HIRES_WIDTH_PIX as u8 - 16),
+        y in 0..(HIRES_HEIGHT_PIX as u8 - 16),
+    ) {
+        let mut chip8 = hires(&[0xD010]);
+        chip8.set_memory(0x300, vec![0xFF; 32]);
+        chip8.i = 0x300;
+        chip8.registers[0] = x;
+        chip8.registers[1] = y;
+        chip8.step().unwrap();
+
+        let (x, y) = (x as usize, y as usize);
+        let expected: Vec<_> = (y..y + 16)
+            .flat_map(|py| (x..x + 16).map(move |px| (px, py)))
+            .collect();
+        prop_assert_eq!(lit(&chip8), expected);
+        prop_assert_eq!(chip8.registers[15], 0);
+    }
+
+    #[test]
+    fn scrolling_moves_pixels(
+        x in 0..HIRES_WIDTH_PIX as u8,
+        y in 0..HIRES_HEIGHT_PIX as u8,
+        op in prop::sample::select(vec![0x00C0u16, 0x00C5, 0x00CF, 0x00FB, 0x00FC]),
+    ) {
+        let mut chip8 = hires(&[0xD011, op]);
+        chip8.set_memory(0x300, vec![0x80]);
+        chip8.i = 0x300;
+        chip8.registers[0] = x;
+        chip8.registers[1] = y;
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+
+        let (x, y) = (x as usize, y as usize);
+        let moved = match op {
+            0x00FB => Some((x + 4, y)),
+            0x00FC => x.checked_sub(4).map(|x| (x, y)),
+            _ => Some((x, y + (op & 0xF) as usize)),
+        };
+        let expected: Vec<_> = moved
+            .filter(|&(x, y)| x < HIRES_WIDTH_PIX && y < HIRES_HEIGHT_PIX)
+            .into_iter()
+            .collect();
+        prop_assert_eq!(lit(&chip8), expected);
+    }
+
+    #[test]
+    fn ld_hf_vx_points_at_large_font(regs in any::<[u8; 16]>(), x in 0..16u8) {
+        let mut chip8 = machine(regs, op_xkk(0xF, x, 0x30));
+        chip8.step().unwrap();
+        let character = (regs[x as usize] & 0xF) as usize;
+        let i = chip8.i as usize;
+        prop_assert_eq!(&chip8.memory[i..i + 10], &BIG_CHARACTERS[character * 10..][..10]);
+    }
+}
+
+#[test]
+fn exit_stays_put() {
+    let mut chip8 = machine([0; 16], 0x00FD);
+    assert!(chip8.is_spinning());
+    chip8.step().unwrap();
+    assert_eq!(chip8.program_counter, START);
+}
+
+/// A failing instruction is reported and leaves the machine untouched
+fn fails_with(mut chip8: Chip8, error: Chip8Error) {
+    let before = chip8.clone();
+    assert_eq!(chip8.step(), Err(error));
+    assert!(chip8 == before, "state changed by a failing step");
+}
+
+#[test]
+fn unknown_opcode_is_an_error() {
+    let address = START;
+    fails_with(
+        machine([0; 16], 0xFFFF),
+        Chip8Error::UnknownOpcode {
+            address,
+            opcode: 0xFFFF,
+        },
+    );
+}
+
+#[test]
+fn call_with_full_stack_overflows() {
+    // every call jumps to itself, nesting one deeper each step
+    let mut chip8 = machine([0; 16], 0x2000 | START);
+    while chip8.stack_pointer < 15 {
+        chip8.step().unwrap();
+    }
+    fails_with(chip8, Chip8Error::StackOverflow { address: START });
+}
+
+#[test]
+fn ret_with_empty_stack_underflows() {
+    fails_with(
+        machine([0; 16], 0x00EE),
+        Chip8Error::StackUnderflow { address: START },
+    );
+}
+
+#[test]
+fn running_off_the_end_of_memory_is_an_error() {
+    let mut chip8 = machine([0; 16], 0x1FFF);
+    chip8.step().unwrap();
+    fails_with(chip8, Chip8Error::MemoryOutOfBounds { address: 0xFFF });
+}
+
+#[test]
+fn memory_past_the_end_at_i_faults() {
+    for (opcode, instruction) in [
+        (0xF555, Instruction::LdIVx(5)),
+        (0xF565, Instruction::LdVxI(5)),
+        (0xF033, Instruction::LdBVx(0)),
+        (0xD016, Instruction::Drw(0, 1, 6)),
+    ] {
+        let mut chip8 = machine([0; 16], opcode);
+        chip8.i = 0xFFE;
+        let error = Chip8Error::MemoryFault {
+            address: START,
+            instruction,
+            index: 0x1000,
+        };
+        fails_with(chip8, error);
+    }
+}
+
+#[test]
+fn memory_at_i_wraps_with_the_quirk() {
+    let mut chip8 =
+        machine([1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 0xF355).with_quirks(Quirks {
+            wrap_memory: true,
+            ..Quirks::default()
+        });
+    chip8.i = 0xFFE;
+    chip8.step().unwrap();
+    assert_eq!(chip8.memory[0xFFE..], [1, 2]);
+    assert_eq!(chip8.memory[..2], [3, 4]);
+}
+
+#[test]
+fn display_wait_idles_until_the_timer_tick() {
+    let mut chip8 = machine([0; 16], 0xD001).with_quirks(Quirks {
+        display_wait: true,
+        ..Quirks::default()
+    });
+    chip8.set_memory(START + 2, vec![0x60, 0x07]);
+    assert_eq!(chip8.step(), Ok(StepOutcome::Drew { collision: false }));
+    for _ in 0..3 {
+        assert_eq!(chip8.step(), Ok(StepOutcome::WaitingForVblank));
+    }
+    assert_eq!(chip8.program_counter, START + 2);
+    chip8.tick_timers();
+    chip8.step().unwrap();
+    assert_eq!(chip8.registers[0], 7);
+}
+
+#[test]
+fn draw_outcome_reports_collision() {
+    let mut chip8 = machine([0; 16], 0xD001);
+    chip8.set_memory(START + 2, vec![0xD0, 0x01]);
+    assert_eq!(chip8.step(), Ok(StepOutcome::Drew { collision: false }));
+    assert_eq!(chip8.step(), Ok(StepOutcome::Drew { collision: true }));
+}
+
+proptest! {
+    /// decode and step agree on which words are instructions
+    #[test]
+    fn step_rejects_exactly_the_unknown_instructions(opcode: u16) {
+        let mut chip8 = machine([0; 16], opcode);
+        // room on the stack for both CALL and RET
+        chip8.stack_pointer = 1;
+        let unknown = matches!(Instruction::decode(opcode), Instruction::Unknown(_));
+        let rejected = matches!(chip8.step(), Err(Chip8Error::UnknownOpcode { .. }));
+        prop_assert_eq!(unknown, rejected);
+    }
+
+    /// The fuzz target's state, one step at a time: whatever a frontend or a save
+    /// state leaves in the registers, an instruction runs or fails. The ones using
+    /// the stack pointer and I run every time besides the random one
+    #[test]
+    fn any_state_steps_without_panicking(
+        opcode: u16,
+        x in 0..16u16,
+        regs in any::<[u8; 16]>(),
+        // often at the top, where adding to I wraps
+        i in prop_oneof![any::<u16>(), 0xFF00..=0xFFFFu16],
+        stack in any::<[u16; 16]>(),
+        stack_pointer: u8,
+        quirks in any::<[bool; 7]>(),
+        hires: bool,
+        two_page: bool,
+    ) {
+        let quirks = Quirks {
+            shift_uses_vy: quirks[0],
+            load_store_increments_i: quirks[1],
+            jump_uses_vx: quirks[2],
+            vf_reset: quirks[3],
+            wrap_sprites: quirks[4],
+            wrap_memory: quirks[5],
+            display_wait: quirks[6],
+        };
+        let x = x << 8;
+        for opcode in [opcode, 0x00EE, 0x2400, 0xD00F | x, 0xF01E | x, 0xF033 | x, 0xF055 | x, 0xF065 | x] {
+            let mut chip8 = machine(regs, opcode).with_quirks(quirks);
+            chip8.i = i;
+            chip8.stack = stack;
+            chip8.stack_pointer = stack_pointer;
+            chip8.hires = hires;
+            chip8.two_page = two_page;
+            let _ = chip8.step();
+        }
+    }
+}
+
+#[test]
+fn instructions_print_as_mnemonics() {
+    for (opcode, mnemonic) in [
+        (0x00E0, "CLS"),
+        (0x00C4, "SCD 4"),
+        (0x1234, "JP 0x234"),
+        (0x3A0F, "SE VA, 0x0f"),
+        (0x8126, "SHR V1, V2"),
+        (0xB300, "JP V0, 0x300"),
+        (0xD12F, "DRW V1, V2, 15"),
+        (0xE5A1, "SKNP V5"),
+        (0xF20A, "LD V2, K"),
+        (0xFF55, "LD [I], VF"),
+        (0xF365, "LD V3, [I]"),
+        (0xFFFF, "DW 0xffff"),
+    ] {
+        assert_eq!(Instruction::decode(opcode).to_string(), mnemonic);
+    }
+}
@@ -0,0 +1,164 @@
+//! Memory coverage
+//!
+//! Which addresses have run as instructions and which were drawn as sprites, a bit
+//! each. A rom mixes code and data freely, so this is the one reliable way to tell
+//! them apart, and whatever neither touched is data for something else or dead.
+//! How many times each instruction ran is counted too, for a heatmap of where
+//! the program spends its time.
+use alloc::{vec, vec::Vec};
+use core::{fmt, ops::Range};
+
+use super::{Chip8, MEMORY_SIZE};
+
+const WORDS: usize = MEMORY_SIZE / 64;
+
+/// One bit per address of memory
+#[derive(Clone, PartialEq, Eq)]
+struct Bits([u64; WORDS]);
+
+impl Bits {
+    fn set(&mut self, address: usize) {
+        let address = address % MEMORY_SIZE;
+        self.0[address / 64] |= 1 << (address % 64);
+    }
+
+    fn get(&self, address: usize) -> bool {
+        address < MEMORY_SIZE && self.0[address / 64] & 1 << (address % 64) != 0
+    }
+}
+
+/// How an address has been used so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Usage {
+    Unreached,
+    Code,
+    Sprite,
+    /// executed and drawn, self-modifying tricks or a sprite made of code
+    CodeAndSprite,
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Usage::Unreached => "unreached",
+            Usage::Code => "code",
+            Usage::Sprite => "sprite",
+            Usage::CodeAndSprite => "code and sprite",
+        })
+    }
+}
+
+/// Addresses executed and read as sprite data
+#[derive(Clone)]
+pub struct Coverage {
+    executed: Bits,
+    sprites: Bits,
+    /// times each address has run as part of an instruction, empty until one has
+    runs: Vec<u32>,
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self {
+            executed: Bits([0; WORDS]),
+            sprites: Bits([0; WORDS]),
+            runs: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Coverage")
+    }
+}
+
+/// Coverage is what has been seen of the program, not part of the machine's state,
+/// a loaded state has none yet is the same machine
+impl PartialEq for Coverage {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Coverage {
+    pub fn executed(&self, address: u16) -> bool {
+        self.executed.get(address as usize)
+    }
+
+    pub fn sprite(&self, address: u16) -> bool {
+        self.sprites.get(address as usize)
+    }
+
+    /// Times the instruction `address` is part of has run
+    pub fn runs(&self, address: u16) -> u32 {
+        self.runs.get(address as usize).copied().unwrap_or(0)
+    }
+
+    pub fn usage(&self, address: u16) -> Usage {
+        match (self.executed(address), self.sprite(address)) {
+            (false, false) => Usage::Unreached,
+            (true, false) => Usage::Code,
+            (false, true) => Usage::Sprite,
+            (true, true) => Usage::CodeAndSprite,
+        }
+    }
+
+    /// `addresses` split into runs of the same usage
+    pub fn regions(&self, addresses: Range<u16>) -> Vec<(Range<u16>, Usage)> {
+        let mut regions: Vec<(Range<u16>, Usage)> = Vec::new();
+        for address in addresses {
+            let usage = self.usage(address);
+            match regions.last_mut() {
+                Some((range, last)) if *last == usage => range.end = address + 1,
+                _ => regions.push((address..address + 1, usage)),
+            }
+        }
+        regions
+    }
+
+    /// Both bytes of the instruction at `address`
+    pub(super) fn execute(&mut self, address: u16) {
+        if self.runs.is_empty() {
+            self.runs = vec![0; MEMORY_SIZE];
+        }
+        for at in [address as usize, address as usize + 1] {
+            self.executed.set(at);
+            let runs = &mut self.runs[at % MEMORY_SIZE];
+            *runs = runs.saturating_add(1);
+        }
+    }
+
+    /// `len` bytes of sprite from `address`, wrapping around the end of the
+    /// `size` bytes of memory
+    pub(super) fn draw(&mut self, address: u16, len: usize, size: usize) {
+        for offset in 0..len {
+            self.sprites.set((address as usize + offset) % size);
+        }
+    }
+}
+
+impl Chip8 {
+    /// What has been executed and drawn since the rom was loaded
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+
+    /// A copy of the machine without the coverage, which a copy kept to go back to
+    /// has no use for, it stays with the present, see [`Chip8::take_debugging`]
+    #[cfg(feature = "std")]
+    pub(crate) fn clone_without_coverage(&mut self) -> Chip8 {
+        let coverage = core::mem::take(&mut self.coverage);
+        let copy = self.clone();
+        self.coverage = coverage;
+        copy
+    }
+
+    /// Flag a write to `index` if it has run as code, the program is rewriting
+    /// itself
+    pub(super) fn note_write(&mut self, index: usize) {
+        if self.self_modified.is_none() && self.coverage.executed(index as u16) {
+            self.self_modified = Some(index as u16);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! COSMAC VIP instruction timing
+//!
+//! The original interpreter took very different times for different instructions,
+//! a register load a few dozen machine cycles and a BCD conversion hundreds. Roms
+//! that time themselves by counting instructions only run at the right speed when
+//! each costs what it did on the VIP. The counts are approximations from published
+//! measurements of the interpreter, including its fetch and decode, rather than a
+//! simulation of the 1802 underneath.
+use super::{Chip8, FrameSummary, Instruction};
+
+/// Machine cycles a frame leaves for the interpreter, after the display's DMA and
+/// the timer interrupt take their share
+pub const VIP_CYCLES_PER_FRAME: u32 = 1760;
+
+/// The rate of VIP timing, in cycles per second
+pub const VIP_CYCLES_PER_SECOND: u32 = VIP_CYCLES_PER_FRAME * 60;
+
+impl Instruction {
+    /// Machine cycles the VIP interpreter takes for this instruction. Sprites cost
+    /// by the row and Fx55/Fx65 by the register, skips the same whether they skip.
+    pub fn vip_cycles(self) -> u32 {
+        use Instruction::*;
+        match self {
+            LdVx(..) => 26,
+            AddVx(..) | LdVxDt(_) | LdVxK(_) | LdDtVx(_) | LdStVx(_) => 30,
+            SeVx(..) | SneVx(..) | LdI(_) => 32,
+            SeVxVy(..) | SneVxVy(..) | Skp(_) | Sknp(_) => 36,
+            AddIVx(_) => 39,
+            LdFVx(_) | LdHfVx(_) => 40,
+            Jp(_) | Call(_) | Ret | JpV0(_) => 43,
+            Cls | Scd(_) | Scr | Scl | Low | High | Exit => 44,
+            Or(..) | And(..) | Xor(..) | AddVxVy(..) | Sub(..) | Shr(..) | Subn(..) | Shl(..)
+            | LdVxVy(..) => 64,
+            Rnd(..) => 56,
+            Drw(_, _, n) => 60 + 26 * if n == 0 { 16 } else { n as u32 },
+            LdBVx(_) => 224,
+            LdIVx(x) | LdVxI(x) => 34 + 14 * (x as u32 + 1),
+            // not on the VIP, costed like the loads and stores they resemble
+            LdRVx(x) | LdVxR(x) => 34 + 14 * (x as u32 + 1),
+            Unknown(_) => 20,
+        }
+    }
+}
+
+impl Chip8 {
+    /// VIP machine cycles the next instruction will take
+    pub fn vip_cycles(&self) -> u32 {
+        self.instruction().vip_cycles()
+    }
+
+    /// Run a frame's worth of VIP machine cycles and tick the timers once, like
+    /// [`Chip8::step_frame`] but with each instruction costing what it did on the
+    /// VIP. An instruction running over the end of the frame takes its overrun from
+    /// the next.
+    pub fn step_vip_frame(&mut self) -> FrameSummary {
+        let mut spent = self.cycle_overrun;
+        let summary = self.run_frame(|chip8, _| {
+            let more = spent < VIP_CYCLES_PER_FRAME;
+            if more {
+                spent += chip8.vip_cycles();
+            }
+            more
+        });
+        // a frame cut short by a halt, a wait or an error owes nothing
+        self.cycle_overrun = match summary.halted || summary.error.is_some() {
+            true => 0,
+            false => spent.saturating_sub(VIP_CYCLES_PER_FRAME),
+        };
+        summary
+    }
+}
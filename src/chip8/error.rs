@@ -0,0 +1,125 @@
+//! Ways a program can make the machine stop, or not start at all
+//!
+//! A rom that does something the hardware has no answer for is a bug in the rom,
+//! not in the emulator, so `step` reports it and leaves the machine as it was
+//! before the instruction, for the frontend to show and the user to inspect.
+//! [`Chip8Builder`](super::Chip8Builder) reports a rom that can't be loaded.
+use core::{error::Error, fmt};
+
+use super::Instruction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chip8Error {
+    /// no instruction decodes to `opcode`
+    UnknownOpcode { address: u16, opcode: u16 },
+    /// a CALL with all 16 stack entries in use
+    StackOverflow { address: u16 },
+    /// a RET with nothing on the stack
+    StackUnderflow { address: u16 },
+    /// a RET with the stack pointer at `stack_pointer`, past the top of the stack
+    StackPointerOutOfRange { address: u16, stack_pointer: u8 },
+    /// the instruction at `address` needs memory past the end
+    MemoryOutOfBounds { address: u16 },
+    /// `instruction` reads or writes memory at I up to `index`, past the end, and
+    /// the `wrap_memory` quirk is off
+    MemoryFault {
+        address: u16,
+        instruction: Instruction,
+        index: usize,
+    },
+    /// memory of `size` bytes, more than 4K or too little for the fonts
+    InvalidMemorySize { size: usize },
+    /// a start address past the end of memory, or where the fonts would
+    /// overwrite the start of the rom
+    InvalidStartAddress { start: u16 },
+    /// a rom of `size` bytes with only `room` from its start address to the end of
+    /// memory
+    RomTooLarge { size: usize, room: usize },
+    /// a saved state with its `field` at `value`, which no machine gets to
+    InvalidState { field: &'static str, value: usize },
+}
+
+impl Chip8Error {
+    /// Where the instruction that failed is, None when the machine never ran
+    pub fn address(&self) -> Option<u16> {
+        match *self {
+            Chip8Error::UnknownOpcode { address, .. }
+            | Chip8Error::StackOverflow { address }
+            | Chip8Error::StackUnderflow { address }
+            | Chip8Error::StackPointerOutOfRange { address, .. }
+            | Chip8Error::MemoryOutOfBounds { address }
+            | Chip8Error::MemoryFault { address, .. } => Some(address),
+            Chip8Error::InvalidMemorySize { .. }
+            | Chip8Error::InvalidStartAddress { .. }
+            | Chip8Error::RomTooLarge { .. }
+            | Chip8Error::InvalidState { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chip8Error::UnknownOpcode { address, opcode } => {
+                write!(f, "unknown opcode {opcode:#06x} at {address:#05x}")
+            }
+            Chip8Error::StackOverflow { address } => {
+                write!(
+                    f,
+                    "stack overflow, call at {address:#05x} is nested too deep"
+                )
+            }
+            Chip8Error::StackUnderflow { address } => {
+                write!(
+                    f,
+                    "stack underflow, return at {address:#05x} with an empty stack"
+                )
+            }
+            Chip8Error::StackPointerOutOfRange {
+                address,
+                stack_pointer,
+            } => {
+                write!(
+                    f,
+                    "return at {address:#05x} with the stack pointer at {stack_pointer}, past the stack"
+                )
+            }
+            Chip8Error::MemoryOutOfBounds { address } => {
+                write!(f, "memory access out of bounds at {address:#05x}")
+            }
+            Chip8Error::MemoryFault {
+                address,
+                instruction,
+                index,
+            } => {
+                write!(
+                    f,
+                    "memory fault, {instruction} at {address:#05x} reaches {index:#x}"
+                )
+            }
+            Chip8Error::InvalidMemorySize { size } => {
+                write!(
+                    f,
+                    "memory of {size:#x} bytes, it has to be at most 4K and hold the fonts"
+                )
+            }
+            Chip8Error::InvalidStartAddress { start } => {
+                write!(
+                    f,
+                    "start address {start:#05x} is in the fonts or past the end of memory"
+                )
+            }
+            Chip8Error::RomTooLarge { size, room } => {
+                write!(
+                    f,
+                    "rom of {size} bytes doesn't fit in the {room} bytes after its start"
+                )
+            }
+            Chip8Error::InvalidState { field, value } => {
+                write!(f, "saved state with {field} at {value:#x}, out of range")
+            }
+        }
+    }
+}
+
+impl Error for Chip8Error {}
@@ -0,0 +1,47 @@
+//! SUPER-CHIP's persistent flags
+//!
+//! Fx75 and Fx85 save and load registers to flags that outlive the program, on the
+//! HP-48 they were the calculator's user flags. Games keep high scores there, so
+//! the flags of each rom are kept in a small file between runs, named by a hash of
+//! the rom so renaming it doesn't lose them.
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use super::Chip8;
+
+impl Chip8 {
+    /// Where the flags of this rom are kept, e.g.
+    /// `~/.local/share/chipy8/flags/0123456789abcdef.bin`
+    pub fn default_flags_path(&self) -> Option<PathBuf> {
+        let file = format!("{:016x}.bin", self.rom.hash());
+        dirs::data_dir().map(|dir| dir.join("chipy8").join("flags").join(file))
+    }
+
+    /// Read the flags saved at `path`, leaving them cleared if there are none yet
+    pub fn load_flags(&mut self, path: &Path) -> Result<(), Error> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        self.flags = bytes
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "flags aren't 16 bytes"))?;
+        Ok(())
+    }
+
+    /// Write the flags to `path`, creating its directory if needed. Flags a rom
+    /// never set aren't worth a file, so all zero and no file yet writes nothing.
+    pub fn save_flags(&self, path: &Path) -> Result<(), Error> {
+        if self.flags == [0; 16] && !path.exists() {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.flags)
+    }
+}
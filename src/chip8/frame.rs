@@ -0,0 +1,76 @@
+//! Whole frames at a time
+//!
+//! The usual way to pace an interpreter: a fixed number of instructions, then one
+//! tick of the 60Hz timers. Frontends that draw at 60Hz can call
+//! [`Chip8::step_frame`] once per frame instead of keeping their own counts, and
+//! [`Chip8::run_until_draw`] runs to the next change worth redrawing.
+use super::{Chip8, Chip8Error, TimerSource};
+
+/// What happened during a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameSummary {
+    /// instructions executed, fewer than asked for if the frame stopped early
+    pub executed: u32,
+    /// the display changed, by drawing, clearing or scrolling
+    pub drew: bool,
+    /// the sound timer went from zero to running
+    pub sound_started: bool,
+    /// the program ended, jumping to itself or exiting
+    pub halted: bool,
+    /// the instruction that failed, the frame stopped there
+    pub error: Option<Chip8Error>,
+}
+
+impl Chip8 {
+    /// Run up to `instructions_per_frame` instructions and tick the timers once.
+    /// The frame ends early when the program halts, waits for a key or fails.
+    /// With [`TimerSource::Instructions`] the steps tick the timers themselves.
+    pub fn step_frame(&mut self, instructions_per_frame: u32) -> FrameSummary {
+        self.run_frame(|_, executed| executed < instructions_per_frame)
+    }
+
+    /// Step until an instruction changes the display, for at most `max_steps`,
+    /// returning the steps run. Stops early without a change when the program
+    /// halts or waits for a key, since nothing would draw until it's given one.
+    pub fn run_until_draw(&mut self, max_steps: u32) -> Result<u32, Chip8Error> {
+        for steps in 0..max_steps {
+            if self.is_spinning() || self.is_waiting_for_key() {
+                return Ok(steps);
+            }
+            let (display, hires) = (self.display, self.hires);
+            self.step()?;
+            if self.display != display || self.hires != hires {
+                return Ok(steps + 1);
+            }
+        }
+        Ok(max_steps)
+    }
+
+    /// Step while `more`, given the machine and the instructions executed so far,
+    /// says the frame has room for the next instruction, then tick the timers
+    pub(super) fn run_frame(&mut self, mut more: impl FnMut(&Chip8, u32) -> bool) -> FrameSummary {
+        let mut summary = FrameSummary::default();
+        let display = self.display;
+        let mut sounding = self.sound > 0;
+        loop {
+            if self.is_spinning() || self.is_waiting_for_key() || !more(self, summary.executed) {
+                break;
+            }
+            match self.step() {
+                Ok(_) => summary.executed += 1,
+                Err(error) => {
+                    summary.error = Some(error);
+                    break;
+                }
+            }
+            summary.sound_started |= !sounding && self.sound > 0;
+            sounding = self.sound > 0;
+        }
+        summary.drew = self.display != display;
+        summary.halted = self.is_spinning();
+        if self.timer_source == TimerSource::External {
+            self.tick_timers();
+        }
+        summary
+    }
+}
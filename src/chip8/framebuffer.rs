@@ -0,0 +1,55 @@
+//! Pixel level access to the display
+//!
+//! The display is a packed bit buffer, these read and write it a pixel at a time
+//! and tell frontends which pixels changed, so they can skip or limit redrawing.
+use alloc::vec::Vec;
+
+use super::{Chip8, WIDTH_BYTE};
+
+/// The display as the frontend last saw it, through [`Chip8::take_dirty`].
+/// Empty until then, which stands for a blank display.
+#[derive(Clone, Default)]
+pub(super) struct Shown(Vec<u8>);
+
+/// What a frontend has seen isn't part of the machine, so it never makes two states differ
+impl PartialEq for Shown {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Chip8 {
+    /// Whether the pixel at `x`, `y` is lit
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        self.display[y * WIDTH_BYTE + x / 8] & (0x80 >> (x % 8)) != 0
+    }
+
+    /// Light or clear the pixel at `x`, `y`
+    pub fn set_pixel(&mut self, x: usize, y: usize, lit: bool) {
+        let byte = &mut self.display[y * WIDTH_BYTE + x / 8];
+        match lit {
+            true => *byte |= 0x80 >> (x % 8),
+            false => *byte &= !(0x80 >> (x % 8)),
+        }
+    }
+
+    /// The pixels that changed since the last call, within the current mode's
+    /// width and height, in row order
+    pub fn take_dirty(&mut self) -> Vec<(usize, usize)> {
+        let shown = &self.shown.0;
+        let mut dirty = Vec::new();
+        for y in 0..self.height() {
+            for column in 0..self.width() / 8 {
+                let i = y * WIDTH_BYTE + column;
+                let changed = self.display[i] ^ shown.get(i).copied().unwrap_or(0);
+                for bit in 0..8 {
+                    if changed & 0x80 >> bit != 0 {
+                        dirty.push((column * 8 + bit, y));
+                    }
+                }
+            }
+        }
+        self.shown.0 = self.display.to_vec();
+        dirty
+    }
+}
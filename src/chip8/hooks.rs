@@ -0,0 +1,120 @@
+//! Event hooks
+//!
+//! Callbacks run as things happen inside a step, so a frontend or script can react
+//! to the display changing or a beep starting without comparing whole states after
+//! every instruction. Like the tracer, copies of the machine share the same hooks.
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use super::{Chip8, Chip8Error, State, HEIGHT_BYTE, WIDTH_BYTE};
+
+type Hook<F> = Option<Arc<Mutex<F>>>;
+
+/// The callbacks registered on a machine
+#[derive(Clone, Default)]
+pub struct Hooks {
+    display_update: Hook<dyn FnMut(&Chip8) + Send>,
+    sound_start: Hook<dyn FnMut() + Send>,
+    key_wait: Hook<dyn FnMut(u8) + Send>,
+    unknown_opcode: Hook<dyn FnMut(u16, u16) + Send>,
+    self_modify: Hook<dyn FnMut(u16) + Send>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Hooks")
+    }
+}
+
+/// Hooks observe the machine, they are no part of its state
+impl PartialEq for Hooks {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// What the hooks compare against once a step is done
+pub(super) struct Before {
+    display: Option<[u8; WIDTH_BYTE * HEIGHT_BYTE]>,
+    hires: bool,
+    sound: u8,
+    state: State,
+}
+
+fn call<F: ?Sized>(hook: &Hook<F>, f: impl FnOnce(&mut F)) {
+    if let Some(Ok(mut hook)) = hook.as_ref().map(|hook| hook.lock()) {
+        f(&mut hook)
+    }
+}
+
+impl Chip8 {
+    /// Call `hook` with the machine after each step that changed the display
+    pub fn on_display_update(&mut self, hook: impl FnMut(&Chip8) + Send + 'static) {
+        self.hooks.display_update = Some(Arc::new(Mutex::new(hook)));
+    }
+
+    /// Call `hook` when the sound timer is set while the sound is off
+    pub fn on_sound_start(&mut self, hook: impl FnMut() + Send + 'static) {
+        self.hooks.sound_start = Some(Arc::new(Mutex::new(hook)));
+    }
+
+    /// Call `hook` with the register Fx0A loads when it starts waiting for a key
+    pub fn on_key_wait(&mut self, hook: impl FnMut(u8) + Send + 'static) {
+        self.hooks.key_wait = Some(Arc::new(Mutex::new(hook)));
+    }
+
+    /// Call `hook` with the address and opcode of an instruction that isn't one
+    pub fn on_unknown_opcode(&mut self, hook: impl FnMut(u16, u16) + Send + 'static) {
+        self.hooks.unknown_opcode = Some(Arc::new(Mutex::new(hook)));
+    }
+
+    /// Call `hook` with the address when the program writes over code it already ran
+    pub fn on_self_modify(&mut self, hook: impl FnMut(u16) + Send + 'static) {
+        self.hooks.self_modify = Some(Arc::new(Mutex::new(hook)));
+    }
+
+    /// Drop every hook
+    pub fn clear_hooks(&mut self) {
+        self.hooks = Hooks::default();
+    }
+
+    /// What the hooks need to know from before a step, the display only copied
+    /// when something listens for it
+    pub(super) fn before_hooks(&self) -> Before {
+        Before {
+            display: self.hooks.display_update.as_ref().map(|_| self.display),
+            hires: self.hires,
+            sound: self.sound,
+            state: self.state,
+        }
+    }
+
+    /// Call the hooks for a step that failed with `error`
+    pub(super) fn hook_error(&self, error: &Chip8Error) {
+        if let Chip8Error::UnknownOpcode { address, opcode } = *error {
+            call(&self.hooks.unknown_opcode, |hook| hook(address, opcode));
+        }
+    }
+
+    /// Call the hooks for whatever the step since `before` did
+    pub(super) fn fire_hooks(&self, before: Before) {
+        if before
+            .display
+            .is_some_and(|display| display != self.display || before.hires != self.hires)
+        {
+            call(&self.hooks.display_update, |hook| hook(self));
+        }
+        if before.sound == 0 && self.sound > 0 {
+            call(&self.hooks.sound_start, |hook| hook());
+        }
+        if let (State::Running, State::WaitingForKey { register, .. }) = (before.state, self.state)
+        {
+            call(&self.hooks.key_wait, |hook| hook(register));
+        }
+        if let Some(address) = self.self_modified {
+            call(&self.hooks.self_modify, |hook| hook(address));
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! Opcodes decoded into instructions
+//!
+//! Every 16 bit word decodes to exactly one [`Instruction`], words that aren't an
+//! instruction become [`Instruction::Unknown`]. `Display` writes the usual
+//! mnemonics, as in Cowgod's technical reference, with hex operands.
+use core::fmt;
+
+/// One instruction, `x` and `y` name registers, addresses are 12 bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// 00E0 clear the display
+    Cls,
+    /// 00EE return from a subroutine
+    Ret,
+    /// 00Cn SUPER-CHIP scroll down n rows
+    Scd(u8),
+    /// 00FB SUPER-CHIP scroll right 4 pixels
+    Scr,
+    /// 00FC SUPER-CHIP scroll left 4 pixels
+    Scl,
+    /// 00FD SUPER-CHIP exit the interpreter
+    Exit,
+    /// 00FE SUPER-CHIP lo-res
+    Low,
+    /// 00FF SUPER-CHIP hi-res
+    High,
+    /// 1nnn
+    Jp(u16),
+    /// 2nnn
+    Call(u16),
+    /// 3xkk skip if Vx == kk
+    SeVx(u8, u8),
+    /// 4xkk skip if Vx != kk
+    SneVx(u8, u8),
+    /// 5xy0 skip if Vx == Vy, the low nibble is ignored
+    SeVxVy(u8, u8),
+    /// 6xkk
+    LdVx(u8, u8),
+    /// 7xkk, without carry
+    AddVx(u8, u8),
+    /// 8xy0
+    LdVxVy(u8, u8),
+    /// 8xy1
+    Or(u8, u8),
+    /// 8xy2
+    And(u8, u8),
+    /// 8xy3
+    Xor(u8, u8),
+    /// 8xy4, carry in VF
+    AddVxVy(u8, u8),
+    /// 8xy5 Vx -= Vy, not borrow in VF
+    Sub(u8, u8),
+    /// 8xy6
+    Shr(u8, u8),
+    /// 8xy7 Vx = Vy - Vx, not borrow in VF
+    Subn(u8, u8),
+    /// 8xyE
+    Shl(u8, u8),
+    /// 9xy0 skip if Vx != Vy
+    SneVxVy(u8, u8),
+    /// Annn
+    LdI(u16),
+    /// Bnnn jump to nnn plus V0
+    JpV0(u16),
+    /// Cxkk random byte masked with kk
+    Rnd(u8, u8),
+    /// Dxyn draw n rows at Vx, Vy, Dxy0 is a 16x16 sprite
+    Drw(u8, u8, u8),
+    /// Ex9E skip if key Vx is down
+    Skp(u8),
+    /// ExA1 skip if key Vx is up
+    Sknp(u8),
+    /// Fx07
+    LdVxDt(u8),
+    /// Fx0A wait for a key
+    LdVxK(u8),
+    /// Fx15
+    LdDtVx(u8),
+    /// Fx18
+    LdStVx(u8),
+    /// Fx1E
+    AddIVx(u8),
+    /// Fx29 point I at the small character Vx
+    LdFVx(u8),
+    /// Fx30 SUPER-CHIP point I at the large character Vx
+    LdHfVx(u8),
+    /// Fx33 binary coded decimal of Vx at I
+    LdBVx(u8),
+    /// Fx55 store V0..=Vx at I
+    LdIVx(u8),
+    /// Fx65 load V0..=Vx from I
+    LdVxI(u8),
+    /// Fx75 SUPER-CHIP save V0..=Vx to the persistent flags
+    LdRVx(u8),
+    /// Fx85 SUPER-CHIP load V0..=Vx from the persistent flags
+    LdVxR(u8),
+    /// any other word
+    Unknown(u16),
+}
+
+impl Instruction {
+    pub fn decode(opcode: u16) -> Instruction {
+        use Instruction::*;
+        let x = (opcode >> 8 & 0xF) as u8;
+        let y = (opcode >> 4 & 0xF) as u8;
+        let n = (opcode & 0xF) as u8;
+        let kk = opcode as u8;
+        let nnn = opcode & 0xFFF;
+        match (opcode >> 12, x, y, n) {
+            (0, 0, 0xE, 0) => Cls,
+            (0, 0, 0xE, 0xE) => Ret,
+            (0, 0, 0xC, n) => Scd(n),
+            (0, 0, 0xF, 0xB) => Scr,
+            (0, 0, 0xF, 0xC) => Scl,
+            (0, 0, 0xF, 0xD) => Exit,
+            (0, 0, 0xF, 0xE) => Low,
+            (0, 0, 0xF, 0xF) => High,
+            (1, ..) => Jp(nnn),
+            (2, ..) => Call(nnn),
+            (3, ..) => SeVx(x, kk),
+            (4, ..) => SneVx(x, kk),
+            (5, ..) => SeVxVy(x, y),
+            (6, ..) => LdVx(x, kk),
+            (7, ..) => AddVx(x, kk),
+            (8, _, _, 0) => LdVxVy(x, y),
+            (8, _, _, 1) => Or(x, y),
+            (8, _, _, 2) => And(x, y),
+            (8, _, _, 3) => Xor(x, y),
+            (8, _, _, 4) => AddVxVy(x, y),
+            (8, _, _, 5) => Sub(x, y),
+            (8, _, _, 6) => Shr(x, y),
+            (8, _, _, 7) => Subn(x, y),
+            (8, _, _, 0xE) => Shl(x, y),
+            (9, _, _, 0) => SneVxVy(x, y),
+            (0xA, ..) => LdI(nnn),
+            (0xB, ..) => JpV0(nnn),
+            (0xC, ..) => Rnd(x, kk),
+            (0xD, ..) => Drw(x, y, n),
+            (0xE, _, 9, 0xE) => Skp(x),
+            (0xE, _, 0xA, 1) => Sknp(x),
+            (0xF, _, 0, 7) => LdVxDt(x),
+            (0xF, _, 0, 0xA) => LdVxK(x),
+            (0xF, _, 1, 5) => LdDtVx(x),
+            (0xF, _, 1, 8) => LdStVx(x),
+            (0xF, _, 1, 0xE) => AddIVx(x),
+            (0xF, _, 2, 9) => LdFVx(x),
+            (0xF, _, 3, 0) => LdHfVx(x),
+            (0xF, _, 3, 3) => LdBVx(x),
+            (0xF, _, 5, 5) => LdIVx(x),
+            (0xF, _, 6, 5) => LdVxI(x),
+            (0xF, _, 7, 5) => LdRVx(x),
+            (0xF, _, 8, 5) => LdVxR(x),
+            _ => Unknown(opcode),
+        }
+    }
+
+    /// The opcode pattern this instruction is one of, e.g. `8xy4` or `Dxyn`
+    pub fn family(self) -> &'static str {
+        use Instruction::*;
+        match self {
+            Cls => "00E0",
+            Ret => "00EE",
+            Scd(_) => "00Cn",
+            Scr => "00FB",
+            Scl => "00FC",
+            Exit => "00FD",
+            Low => "00FE",
+            High => "00FF",
+            Jp(_) => "1nnn",
+            Call(_) => "2nnn",
+            SeVx(..) => "3xkk",
+            SneVx(..) => "4xkk",
+            SeVxVy(..) => "5xy0",
+            LdVx(..) => "6xkk",
+            AddVx(..) => "7xkk",
+            LdVxVy(..) => "8xy0",
+            Or(..) => "8xy1",
+            And(..) => "8xy2",
+            Xor(..) => "8xy3",
+            AddVxVy(..) => "8xy4",
+            Sub(..) => "8xy5",
+            Shr(..) => "8xy6",
+            Subn(..) => "8xy7",
+            Shl(..) => "8xyE",
+            SneVxVy(..) => "9xy0",
+            LdI(_) => "Annn",
+            JpV0(_) => "Bnnn",
+            Rnd(..) => "Cxkk",
+            Drw(..) => "Dxyn",
+            Skp(_) => "Ex9E",
+            Sknp(_) => "ExA1",
+            LdVxDt(_) => "Fx07",
+            LdVxK(_) => "Fx0A",
+            LdDtVx(_) => "Fx15",
+            LdStVx(_) => "Fx18",
+            AddIVx(_) => "Fx1E",
+            LdFVx(_) => "Fx29",
+            LdHfVx(_) => "Fx30",
+            LdBVx(_) => "Fx33",
+            LdIVx(_) => "Fx55",
+            LdVxI(_) => "Fx65",
+            LdRVx(_) => "Fx75",
+            LdVxR(_) => "Fx85",
+            Unknown(_) => "????",
+        }
+    }
+
+    /// The opcode that decodes to this instruction. Operands are masked to the bits
+    /// the encoding has room for.
+    pub fn encode(self) -> u16 {
+        use Instruction::*;
+        let xy = |n1: u16, x: u8, y: u8, n4: u16| {
+            n1 << 12 | (x as u16 & 0xF) << 8 | (y as u16 & 0xF) << 4 | n4
+        };
+        let xkk = |n1: u16, x: u8, kk: u8| n1 << 12 | (x as u16 & 0xF) << 8 | kk as u16;
+        let nnn = |n1: u16, nnn: u16| n1 << 12 | nnn & 0xFFF;
+        match self {
+            Cls => 0x00E0,
+            Ret => 0x00EE,
+            Scd(n) => 0x00C0 | n as u16 & 0xF,
+            Scr => 0x00FB,
+            Scl => 0x00FC,
+            Exit => 0x00FD,
+            Low => 0x00FE,
+            High => 0x00FF,
+            Jp(addr) => nnn(1, addr),
+            Call(addr) => nnn(2, addr),
+            SeVx(x, kk) => xkk(3, x, kk),
+            SneVx(x, kk) => xkk(4, x, kk),
+            SeVxVy(x, y) => xy(5, x, y, 0),
+            LdVx(x, kk) => xkk(6, x, kk),
+            AddVx(x, kk) => xkk(7, x, kk),
+            LdVxVy(x, y) => xy(8, x, y, 0),
+            Or(x, y) => xy(8, x, y, 1),
+            And(x, y) => xy(8, x, y, 2),
+            Xor(x, y) => xy(8, x, y, 3),
+            AddVxVy(x, y) => xy(8, x, y, 4),
+            Sub(x, y) => xy(8, x, y, 5),
+            Shr(x, y) => xy(8, x, y, 6),
+            Subn(x, y) => xy(8, x, y, 7),
+            Shl(x, y) => xy(8, x, y, 0xE),
+            SneVxVy(x, y) => xy(9, x, y, 0),
+            LdI(addr) => nnn(0xA, addr),
+            JpV0(addr) => nnn(0xB, addr),
+            Rnd(x, kk) => xkk(0xC, x, kk),
+            Drw(x, y, n) => xy(0xD, x, y, n as u16 & 0xF),
+            Skp(x) => xkk(0xE, x, 0x9E),
+            Sknp(x) => xkk(0xE, x, 0xA1),
+            LdVxDt(x) => xkk(0xF, x, 0x07),
+            LdVxK(x) => xkk(0xF, x, 0x0A),
+            LdDtVx(x) => xkk(0xF, x, 0x15),
+            LdStVx(x) => xkk(0xF, x, 0x18),
+            AddIVx(x) => xkk(0xF, x, 0x1E),
+            LdFVx(x) => xkk(0xF, x, 0x29),
+            LdHfVx(x) => xkk(0xF, x, 0x30),
+            LdBVx(x) => xkk(0xF, x, 0x33),
+            LdIVx(x) => xkk(0xF, x, 0x55),
+            LdVxI(x) => xkk(0xF, x, 0x65),
+            LdRVx(x) => xkk(0xF, x, 0x75),
+            LdVxR(x) => xkk(0xF, x, 0x85),
+            Unknown(opcode) => opcode,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Instruction::*;
+        match *self {
+            Cls => write!(f, "CLS"),
+            Ret => write!(f, "RET"),
+            Scd(n) => write!(f, "SCD {n}"),
+            Scr => write!(f, "SCR"),
+            Scl => write!(f, "SCL"),
+            Exit => write!(f, "EXIT"),
+            Low => write!(f, "LOW"),
+            High => write!(f, "HIGH"),
+            Jp(nnn) => write!(f, "JP {nnn:#05x}"),
+            Call(nnn) => write!(f, "CALL {nnn:#05x}"),
+            SeVx(x, kk) => write!(f, "SE V{x:X}, {kk:#04x}"),
+            SneVx(x, kk) => write!(f, "SNE V{x:X}, {kk:#04x}"),
+            SeVxVy(x, y) => write!(f, "SE V{x:X}, V{y:X}"),
+            LdVx(x, kk) => write!(f, "LD V{x:X}, {kk:#04x}"),
+            AddVx(x, kk) => write!(f, "ADD V{x:X}, {kk:#04x}"),
+            LdVxVy(x, y) => write!(f, "LD V{x:X}, V{y:X}"),
+            Or(x, y) => write!(f, "OR V{x:X}, V{y:X}"),
+            And(x, y) => write!(f, "AND V{x:X}, V{y:X}"),
+            Xor(x, y) => write!(f, "XOR V{x:X}, V{y:X}"),
+            AddVxVy(x, y) => write!(f, "ADD V{x:X}, V{y:X}"),
+            Sub(x, y) => write!(f, "SUB V{x:X}, V{y:X}"),
+            Shr(x, y) => write!(f, "SHR V{x:X}, V{y:X}"),
+            Subn(x, y) => write!(f, "SUBN V{x:X}, V{y:X}"),
+            Shl(x, y) => write!(f, "SHL V{x:X}, V{y:X}"),
+            SneVxVy(x, y) => write!(f, "SNE V{x:X}, V{y:X}"),
+            LdI(nnn) => write!(f, "LD I, {nnn:#05x}"),
+            JpV0(nnn) => write!(f, "JP V0, {nnn:#05x}"),
+            Rnd(x, kk) => write!(f, "RND V{x:X}, {kk:#04x}"),
+            Drw(x, y, n) => write!(f, "DRW V{x:X}, V{y:X}, {n}"),
+            Skp(x) => write!(f, "SKP V{x:X}"),
+            Sknp(x) => write!(f, "SKNP V{x:X}"),
+            LdVxDt(x) => write!(f, "LD V{x:X}, DT"),
+            LdVxK(x) => write!(f, "LD V{x:X}, K"),
+            LdDtVx(x) => write!(f, "LD DT, V{x:X}"),
+            LdStVx(x) => write!(f, "LD ST, V{x:X}"),
+            AddIVx(x) => write!(f, "ADD I, V{x:X}"),
+            LdFVx(x) => write!(f, "LD F, V{x:X}"),
+            LdHfVx(x) => write!(f, "LD HF, V{x:X}"),
+            LdBVx(x) => write!(f, "LD B, V{x:X}"),
+            LdIVx(x) => write!(f, "LD [I], V{x:X}"),
+            LdVxI(x) => write!(f, "LD V{x:X}, [I]"),
+            LdRVx(x) => write!(f, "LD R, V{x:X}"),
+            LdVxR(x) => write!(f, "LD V{x:X}, R"),
+            Unknown(opcode) => write!(f, "DW {opcode:#06x}"),
+        }
+    }
+}
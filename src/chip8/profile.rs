@@ -0,0 +1,103 @@
+//! Execution profile
+//!
+//! How often each opcode family ran and how long it took, for rom authors looking
+//! for the hot spots in their loops. Off unless asked for, timing every
+//! instruction costs a clock read either side of it.
+use std::{
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use super::{Chip8, Chip8Error, StepOutcome};
+
+/// Count and cumulative time of one opcode family
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FamilyStats {
+    pub count: u64,
+    pub time: Duration,
+}
+
+/// Per opcode family totals, keyed by [`Instruction::family`](super::Instruction::family)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    families: BTreeMap<&'static str, FamilyStats>,
+}
+
+impl Profile {
+    pub fn record(&mut self, family: &'static str, time: Duration) {
+        let entry = self.families.entry(family).or_default();
+        entry.count += 1;
+        entry.time += time;
+    }
+
+    pub fn get(&self, family: &str) -> Option<FamilyStats> {
+        self.families.get(family).copied()
+    }
+
+    /// Families that ran, the most time first
+    pub fn hottest(&self) -> Vec<(&'static str, FamilyStats)> {
+        let mut families: Vec<_> = self.families.iter().map(|(&f, &t)| (f, t)).collect();
+        families.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(b.1.count.cmp(&a.1.count)));
+        families
+    }
+
+    /// Instructions executed
+    pub fn count(&self) -> u64 {
+        self.families.values().map(|family| family.count).sum()
+    }
+
+    /// Time spent executing
+    pub fn time(&self) -> Duration {
+        self.families.values().map(|family| family.time).sum()
+    }
+}
+
+/// A table, one family per line
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.time().as_secs_f64().max(f64::MIN_POSITIVE);
+        writeln!(f, "family      count       time      %")?;
+        for (family, FamilyStats { count, time }) in self.hottest() {
+            writeln!(
+                f,
+                "{family}   {count:>10} {:>8.2}ms {:>6.1}",
+                time.as_secs_f64() * 1000.0,
+                time.as_secs_f64() / total * 100.0
+            )?;
+        }
+        write!(
+            f,
+            "total  {:>10} {:>8.2}ms",
+            self.count(),
+            self.time().as_secs_f64() * 1000.0
+        )
+    }
+}
+
+impl Chip8 {
+    /// Start or stop profiling, starting again clears the last profile
+    pub fn set_profiling(&mut self, on: bool) {
+        self.profile = on.then(Profile::default);
+    }
+
+    /// What has run since profiling started, None when it's off
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Execute the instruction at the program counter, timing it if profiling
+    pub(super) fn execute_profiled(&mut self) -> Result<StepOutcome, Chip8Error> {
+        if self.profile.is_none() {
+            return self.execute_traced();
+        }
+        let family = self.instruction().family();
+        let start = Instant::now();
+        let outcome = self.execute_traced()?;
+        let time = start.elapsed();
+        if let Some(profile) = &mut self.profile {
+            profile.record(family, time);
+        }
+        Ok(outcome)
+    }
+}
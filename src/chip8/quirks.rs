@@ -0,0 +1,108 @@
+//! Behaviors that differ between CHIP-8 interpreters
+//!
+//! The original COSMAC VIP interpreter and its later descendants disagree on a
+//! handful of instructions, and roms tend to rely on the one they were written for.
+//! The default keeps the behavior this emulator always had, a [`Platform`] picks
+//! the set a particular interpreter had.
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+/// Which variant of each disputed instruction to run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quirks {
+    /// 8xy6/8xyE shift Vy into Vx, rather than shifting Vx in place
+    pub shift_uses_vy: bool,
+    /// Fx55/Fx65 leave I pointing past the last register stored or loaded
+    pub load_store_increments_i: bool,
+    /// Bnnn jumps to nnn plus Vx, with x the top nibble of nnn, instead of V0
+    pub jump_uses_vx: bool,
+    /// 8xy1/8xy2/8xy3 clear VF
+    pub vf_reset: bool,
+    /// sprites wrap around to the opposite edge instead of being clipped
+    pub wrap_sprites: bool,
+    /// Fx33/Fx55/Fx65 and sprites reaching past the end of memory wrap around to
+    /// its start, rather than stopping the program with a memory fault
+    pub wrap_memory: bool,
+    /// Dxyn waits for the vertical blank, so a program draws at most one sprite a
+    /// frame, as on the COSMAC VIP
+    pub display_wait: bool,
+}
+
+impl Quirks {
+    /// The names of the quirks set, as the fields are called
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        [
+            ("shift_uses_vy", self.shift_uses_vy),
+            ("load_store_increments_i", self.load_store_increments_i),
+            ("jump_uses_vx", self.jump_uses_vx),
+            ("vf_reset", self.vf_reset),
+            ("wrap_sprites", self.wrap_sprites),
+            ("wrap_memory", self.wrap_memory),
+            ("display_wait", self.display_wait),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| name)
+    }
+}
+
+/// Interpreters with a known set of quirks, named as on the command line
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, EnumIter, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    /// the original COSMAC VIP interpreter
+    Chip8,
+    /// CHIP-48 on the HP-48 calculators, whose Fx55/Fx65 still moved I
+    Chip48,
+    /// SUPER-CHIP 1.1, which kept CHIP-48's changes but left I where it was
+    Schip,
+    /// Octo's XO-CHIP, back to the VIP's arithmetic but wrapping sprites
+    Xochip,
+    /// the VIP's two page 64x64 hi-res variant, as used by Hires Maze
+    #[strum(serialize = "hires")]
+    #[serde(rename = "hires")]
+    HiresChip8,
+}
+
+impl Platform {
+    /// The platform with exactly these quirks, the first listed where two share
+    /// them
+    pub fn of(quirks: Quirks) -> Option<Platform> {
+        Platform::iter().find(|&platform| Quirks::from(platform) == quirks)
+    }
+}
+
+impl From<Platform> for Quirks {
+    fn from(platform: Platform) -> Self {
+        match platform {
+            Platform::Chip8 | Platform::HiresChip8 => Quirks {
+                shift_uses_vy: true,
+                load_store_increments_i: true,
+                vf_reset: true,
+                display_wait: true,
+                ..Quirks::default()
+            },
+            // CHIP-48's Fx55/Fx65 still moved I, run as the VIP's I += x + 1 though the
+            // calculator stopped one short at I += x
+            Platform::Chip48 => Quirks {
+                load_store_increments_i: true,
+                jump_uses_vx: true,
+                ..Quirks::default()
+            },
+            Platform::Schip => Quirks {
+                jump_uses_vx: true,
+                ..Quirks::default()
+            },
+            Platform::Xochip => Quirks {
+                shift_uses_vy: true,
+                load_store_increments_i: true,
+                wrap_sprites: true,
+                ..Quirks::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Where Cxkk's random bytes come from
+//!
+//! A seeded ChaCha rng by default, which save states carry along. Tests and tools
+//! that need particular bytes can hand the machine their own source instead.
+#[cfg(feature = "std")]
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use rand::Rng;
+
+use super::Chip8;
+
+/// A shared handle on a function giving random bytes
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct RandomSource(Arc<Mutex<dyn FnMut() -> u8 + Send>>);
+
+#[cfg(feature = "std")]
+impl RandomSource {
+    pub fn new(source: impl FnMut() -> u8 + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(source)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for RandomSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RandomSource")
+    }
+}
+
+/// Like the tracer, an injected source is outside the machine's state
+#[cfg(feature = "std")]
+impl PartialEq for RandomSource {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Chip8 {
+    /// Take Cxkk's bytes from `source` rather than the seeded rng, e.g.
+    /// `with_random(|| 0xFF)` to make every mask come through whole
+    #[cfg(feature = "std")]
+    pub fn with_random(mut self, source: impl FnMut() -> u8 + Send + 'static) -> Self {
+        self.random = Some(RandomSource::new(source));
+        self
+    }
+
+    /// The next byte for Cxkk
+    pub(super) fn random_byte(&mut self) -> u8 {
+        #[cfg(feature = "std")]
+        if let Some(RandomSource(source)) = &self.random {
+            return source.lock().map_or(0, |mut source| source());
+        }
+        self.rng.gen()
+    }
+}
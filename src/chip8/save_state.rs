@@ -0,0 +1,155 @@
+//! Save states
+//!
+//! The whole machine, written out as TOML so a state can be read and even patched
+//! by hand. Memory and the display are rows of hex rather than thousands of
+//! numbers, and the rng keeps its position so Cxkk carries on where it left off.
+#[cfg(feature = "std")]
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "std")]
+use super::{is_valid_memory_size, Chip8, Chip8Error, MEMORY_SIZE};
+
+#[cfg(feature = "std")]
+impl Chip8 {
+    /// Where the state of this rom goes by default, e.g.
+    /// `~/.local/share/chipy8/states/PONG.toml`
+    pub fn default_state_path(&self) -> Option<PathBuf> {
+        self.state_slot_path(1)
+    }
+
+    /// Where the state in `slot` of this rom goes, the first slot being the
+    /// default path and the others numbered, e.g. `PONG.3.toml`
+    pub fn state_slot_path(&self, slot: u8) -> Option<PathBuf> {
+        let file = match slot {
+            1 => format!("{}.toml", self.rom.name()),
+            slot => format!("{}.{slot}.toml", self.rom.name()),
+        };
+        dirs::data_dir().map(|dir| dir.join("chipy8").join("states").join(file))
+    }
+
+    /// Write the machine state to `path`, creating its directory if needed
+    pub fn save_state(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// Read a machine state written by [`Chip8::save_state`], refusing one edited
+    /// into a state the machine can't run from
+    pub fn load_state(path: &Path) -> Result<Chip8, Error> {
+        let chip8: Chip8 = toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        chip8
+            .check_state()
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        Ok(chip8)
+    }
+
+    /// Whether memory is a size the builder allows and the registers of a loaded
+    /// state are in range
+    fn check_state(&self) -> Result<(), Chip8Error> {
+        let size = self.memory.len();
+        if !is_valid_memory_size(size) {
+            return Err(Chip8Error::InvalidMemorySize { size });
+        }
+        if self.program_counter as usize >= size {
+            return Err(Chip8Error::InvalidState {
+                field: "program_counter",
+                value: self.program_counter as usize,
+            });
+        }
+        // I reaches all 12 bits of address whatever the memory, see Fx1E
+        if self.i as usize >= MEMORY_SIZE {
+            return Err(Chip8Error::InvalidState {
+                field: "i",
+                value: self.i as usize,
+            });
+        }
+        // a CALL pushes to the entry past the pointer, the first is never used
+        if self.stack_pointer as usize >= self.stack.len() {
+            return Err(Chip8Error::InvalidState {
+                field: "stack_pointer",
+                value: self.stack_pointer as usize,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Bytes as rows of hex, for `#[serde(with = "hex")]`
+pub(crate) mod hex {
+    use alloc::{format, string::String, vec::Vec};
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    const ROW: usize = 32;
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(bytes: &T, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(bytes.as_ref().chunks(ROW).map(|row| {
+            row.iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>()
+        }))
+    }
+
+    pub fn deserialize<'de, D, T>(d: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<Vec<u8>>,
+    {
+        let rows = Vec::<String>::deserialize(d)?;
+        let hex = rows.concat();
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|b| u8::from_str_radix(b, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| D::Error::custom("bad hex"))?;
+        let len = bytes.len();
+        T::try_from(bytes).map_err(|_| D::Error::custom(format!("wrong length {len}")))
+    }
+}
+
+/// The rng's seed, stream and position, for `#[serde(with = "rng")]`. The position
+/// is 128 bits, more than TOML's integers hold, so the numbers go in hex strings.
+pub(super) mod rng {
+    use alloc::{format, string::String};
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Rng {
+        #[serde(with = "super::hex")]
+        seed: [u8; 32],
+        stream: String,
+        word_pos: String,
+    }
+
+    pub fn serialize<S: Serializer>(rng: &ChaCha8Rng, s: S) -> Result<S::Ok, S::Error> {
+        Rng {
+            seed: rng.get_seed(),
+            stream: format!("{:x}", rng.get_stream()),
+            word_pos: format!("{:x}", rng.get_word_pos()),
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<ChaCha8Rng, D::Error> {
+        let state = Rng::deserialize(d)?;
+        let number = |hex: &str| u128::from_str_radix(hex, 16).map_err(D::Error::custom);
+        let mut rng = ChaCha8Rng::from_seed(state.seed);
+        rng.set_stream(number(&state.stream)? as u64);
+        rng.set_word_pos(number(&state.word_pos)?);
+        Ok(rng)
+    }
+}
@@ -0,0 +1,138 @@
+//! Instruction traces
+//!
+//! Every instruction executed, with what it changed, handed to a sink as it runs.
+//! Written to a file, one line each, traces of two emulators running the same rom
+//! can be diffed to find the first instruction they disagree on.
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use super::{Chip8, Chip8Error, Instruction, StepOutcome};
+
+/// One executed instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub address: u16,
+    pub opcode: u16,
+    pub instruction: Instruction,
+    /// registers that changed, with their new values
+    pub registers: Vec<(u8, u8)>,
+    /// the new I, if it changed
+    pub i: Option<u16>,
+    /// the first address of code that already ran the instruction wrote over
+    pub self_modified: Option<u16>,
+}
+
+/// `0x200  a21e  LD I, 0x21e          I=0x21e`, a write over code that already ran
+/// is flagged `SMC=0x2a4`
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut changes: Vec<String> = self
+            .registers
+            .iter()
+            .map(|(x, value)| format!("V{x:X}={value:#04x}"))
+            .collect();
+        changes.extend(self.i.map(|i| format!("I={i:#05x}")));
+        changes.extend(self.self_modified.map(|a| format!("SMC={a:#05x}")));
+        write!(f, "{:#05x}  {:04x}  ", self.address, self.opcode)?;
+        match changes.is_empty() {
+            true => write!(f, "{}", self.instruction),
+            false => write!(
+                f,
+                "{:<20} {}",
+                self.instruction.to_string(),
+                changes.join(" ")
+            ),
+        }
+    }
+}
+
+/// Where trace entries go
+pub trait TraceSink: Send {
+    fn record(&mut self, entry: &TraceEntry);
+}
+
+impl<F: FnMut(&TraceEntry) + Send> TraceSink for F {
+    fn record(&mut self, entry: &TraceEntry) {
+        self(entry)
+    }
+}
+
+/// Writes each entry as a line
+struct Lines<W>(W);
+
+impl<W: Write + Send> TraceSink for Lines<W> {
+    fn record(&mut self, entry: &TraceEntry) {
+        // a trace that can't be written isn't worth stopping the program for
+        writeln!(self.0, "{entry}").ok();
+    }
+}
+
+/// A shared handle on a sink. Copies of the machine, as kept for rewinding, trace
+/// into the same one.
+#[derive(Clone)]
+pub struct Tracer(Arc<Mutex<dyn TraceSink>>);
+
+impl Tracer {
+    pub fn new(sink: impl TraceSink + 'static) -> Self {
+        Self(Arc::new(Mutex::new(sink)))
+    }
+
+    /// Trace a line per instruction into `writer`
+    pub fn to_writer(writer: impl Write + Send + 'static) -> Self {
+        Self::new(Lines(writer))
+    }
+
+    /// Trace a line per instruction into a new file at `path`
+    pub fn create(path: &Path) -> Result<Self, io::Error> {
+        Ok(Self::to_writer(BufWriter::new(File::create(path)?)))
+    }
+
+    pub(super) fn record(&self, entry: &TraceEntry) {
+        if let Ok(mut sink) = self.0.lock() {
+            sink.record(entry);
+        }
+    }
+}
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Tracer")
+    }
+}
+
+/// Tracing is an observer, not part of the machine, so it never makes two states differ
+impl PartialEq for Tracer {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Chip8 {
+    /// Execute the instruction at the program counter, handing it to the tracer if
+    /// there is one
+    pub(super) fn execute_traced(&mut self) -> Result<StepOutcome, Chip8Error> {
+        let Some(tracer) = self.tracer.clone() else {
+            return self.execute();
+        };
+        let (address, opcode, registers, i) =
+            (self.program_counter, self.opcode(), self.registers, self.i);
+        let outcome = self.execute()?;
+        tracer.record(&TraceEntry {
+            address,
+            opcode,
+            instruction: Instruction::decode(opcode),
+            registers: (0..16)
+                .filter(|&x| registers[x] != self.registers[x])
+                .map(|x| (x as u8, self.registers[x]))
+                .collect(),
+            i: (i != self.i).then_some(self.i),
+            self_modified: self.self_modified,
+        });
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,108 @@
+//! Watchpoints
+//!
+//! Stop when a register changes or a stretch of memory is written, for finding out
+//! who clobbers a variable. Like breakpoints they belong to the debugging session
+//! and aren't saved with the machine.
+use alloc::{format, string::String};
+use core::{fmt, ops::Range, str::FromStr};
+
+use super::{Chip8, Instruction, State};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Watchpoint {
+    /// any write into the range, even of the value already there
+    Memory(Range<u16>),
+    /// Vx changing value
+    Register(u8),
+}
+
+impl Watchpoint {
+    /// Whether a step that started with `registers` and wrote `written` fired this,
+    /// `current` being the registers after it
+    fn fired(&self, registers: &[u8; 16], current: &[u8; 16], written: &Range<u16>) -> bool {
+        match self {
+            Watchpoint::Memory(range) => range.start < written.end && written.start < range.end,
+            Watchpoint::Register(x) => registers[*x as usize & 0xF] != current[*x as usize & 0xF],
+        }
+    }
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Watchpoint::Memory(range) if range.len() == 1 => {
+                write!(f, "write to {:#05x}", range.start)
+            }
+            Watchpoint::Memory(range) => {
+                write!(f, "write to {:#05x}..{:#05x}", range.start, range.end)
+            }
+            Watchpoint::Register(x) => write!(f, "V{x:X} changes"),
+        }
+    }
+}
+
+/// `v5` for a register, `0x300` or `0x300..0x310` for memory
+impl FromStr for Watchpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = |text: &str| {
+            let hex = text.trim().trim_start_matches("0x");
+            u16::from_str_radix(hex, 16).map_err(|_| format!("bad address `{text}`"))
+        };
+        if let Some(x) = s.strip_prefix(['v', 'V']) {
+            return match u8::from_str_radix(x, 16) {
+                Ok(x) if x < 16 => Ok(Watchpoint::Register(x)),
+                _ => Err(format!("no register `{s}`")),
+            };
+        }
+        let range = match s.split_once("..") {
+            Some((start, end)) => address(start)?..address(end)?,
+            None => address(s).map(|start| start..start + 1)?,
+        };
+        match range.is_empty() {
+            true => Err(format!("empty range `{s}`")),
+            false => Ok(Watchpoint::Memory(range)),
+        }
+    }
+}
+
+/// What a step has to remember beforehand to tell which watchpoints it fired
+pub(super) struct Before {
+    registers: [u8; 16],
+    written: Range<u16>,
+}
+
+impl Chip8 {
+    /// Stop when `watchpoint` fires, returns its index in `watchpoints`
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        self.watchpoints.push(watchpoint);
+        self.watchpoints.len() - 1
+    }
+
+    /// Memory the next instruction will write, empty for most
+    fn written(&self) -> Range<u16> {
+        let i = self.i;
+        match (self.state, self.instruction()) {
+            (State::Running, Instruction::LdBVx(_)) => i..i.saturating_add(3),
+            (State::Running, Instruction::LdIVx(x)) => i..i.saturating_add(x as u16 + 1),
+            _ => i..i,
+        }
+    }
+
+    /// Note what the next step could fire, nothing when no watchpoints are set
+    pub(super) fn before_step(&self) -> Option<Before> {
+        (!self.watchpoints.is_empty()).then(|| Before {
+            registers: self.registers,
+            written: self.written(),
+        })
+    }
+
+    /// The first watchpoint the step since `before` fired
+    pub(super) fn fired_watchpoint(&self, before: Option<Before>) -> Option<usize> {
+        let before = before?;
+        self.watchpoints.iter().position(|watchpoint| {
+            watchpoint.fired(&before.registers, &self.registers, &before.written)
+        })
+    }
+}
@@ -1,12 +1,266 @@
 use std::path::PathBuf;
 
-use clap::{command, Parser};
+use clap::{Parser, Subcommand};
+
+use crate::{
+    chip8::{Chip8, Chip8Builder, Platform, Quirks, Watchpoint},
+    config::Config,
+    determinism,
+    graphics::Graphics,
+    keymap::Keymap,
+    library::RomSettings,
+    rom::Rom,
+    theme::Theme,
+    widget::PixelMarker,
+};
+
+/// Instructions per second when neither --speed nor the config file gives one
+pub const DEFAULT_SPEED: u32 = 700;
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 pub struct Cli {
-    pub rom_path: PathBuf,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// Roms to load, the terminal frontend opens a tab for each.
+    /// Without any the last session is offered for restoring.
+    pub rom_paths: Vec<PathBuf>,
 
     #[arg(short, long)]
     pub paused: bool,
+
+    /// Keep the buzzer quiet, as does `mute = true` in the config file
+    #[arg(long)]
+    pub mute: bool,
+
+    /// Interpreter whose quirks to run the rom with: chip8, chip48, schip, xochip
+    /// or hires
+    #[arg(long)]
+    pub platform: Option<Platform>,
+
+    /// Where to load the rom and start, 0x600 for ETI 660 roms
+    #[arg(long, value_name = "ADDRESS", default_value = "0x200", value_parser = address)]
+    pub start_addr: u16,
+
+    /// Instructions per second, the config file's or 700 without
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub speed: Option<u32>,
+
+    /// Pace instructions by what each cost on the COSMAC VIP, instead of --speed
+    /// of them a second
+    #[arg(long)]
+    pub vip_timing: bool,
+
+    /// Config file to use instead of ~/.config/chipy8/config.toml
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Directory of roms for the library, instead of the config file's
+    #[arg(long, value_name = "DIR")]
+    pub library: Option<PathBuf>,
+
+    /// Keys for the keypad: qwerty, azerty, dvorak, or the 16 characters for keys
+    /// 0 to F. Overrides the config file's, for every rom.
+    #[arg(long)]
+    pub keymap: Option<Keymap>,
+
+    /// How long a key stays held after the terminal last reported it, in
+    /// milliseconds, where the terminal doesn't report key releases
+    #[arg(long, value_name = "MS", default_value_t = 250)]
+    pub key_hold: u64,
+
+    /// Colors of the terminal frontend: default, green, amber or high-contrast.
+    /// Overrides the config file's, its [colors] still apply.
+    #[arg(long)]
+    pub theme: Option<Theme>,
+
+    /// How the terminal frontend draws pixels: auto, half-block, braille or
+    /// block. g cycles through them while running.
+    #[arg(long)]
+    pub marker: Option<PixelMarker>,
+
+    /// How the terminal frontend shows the display: auto, kitty, sixel or
+    /// cells. Kitty and sixel draw it as an image, on terminals that can.
+    #[arg(long)]
+    pub graphics: Option<Graphics>,
+
+    /// Let pixels fade out over this many frames rather than go dark at once,
+    /// which hides the flicker of sprites redrawn every frame. F toggles it.
+    #[arg(long, value_name = "FRAMES", value_parser = clap::value_parser!(u8).range(1..))]
+    pub phosphor: Option<u8>,
+
+    /// Pause when the display hasn't changed, nor a key been pressed, for this
+    /// many 60Hz frames
+    #[arg(long, value_name = "FRAMES")]
+    pub idle_pause: Option<u32>,
+
+    /// Run reproducibly: fixed rng seed and keypad input only from --input-script
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// Seed for the random numbers of Cxkk, the same seed gives the same run
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Keys to replay in deterministic mode, one `<step> <key>` pair per line
+    #[arg(long, requires = "deterministic")]
+    pub input_script: Option<PathBuf>,
+
+    /// Pause when a register changes or memory is written, e.g. `v5`, `0x300` or
+    /// `0x300..0x310` (end exclusive). Can be given more than once.
+    #[arg(long = "watch", value_name = "WATCHPOINT")]
+    pub watchpoints: Vec<Watchpoint>,
+
+    /// Write every instruction executed, with the registers it changed, to this file,
+    /// or with `-` to the terminal frontend's log panel
+    #[arg(long, value_name = "FILE")]
+    pub trace: Option<PathBuf>,
+
+    /// Count and time the instructions by opcode family, printing the totals on exit
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Names for addresses in the debugger, one `<name> <address>` per line,
+    /// instead of the .sym file next to each rom
+    #[arg(long, value_name = "FILE")]
+    pub symbols: Option<PathBuf>,
+}
+
+// Tools that work on a rom without running it
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Print an annotated listing of a rom
+    Disasm { rom: PathBuf },
+    /// Assemble mnemonics into a rom
+    Asm {
+        source: PathBuf,
+        /// Where to write the rom, next to the source with a .ch8 extension by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run a rom without input and report which of it ran as code, was drawn as
+    /// sprites, or was never reached
+    Coverage {
+        rom: PathBuf,
+        /// Instructions to run, fewer if the program ends or waits for a key
+        #[arg(long, default_value_t = 1_000_000)]
+        steps: u64,
+        /// Where to load the rom and start
+        #[arg(long, value_name = "ADDRESS", default_value = "0x200", value_parser = address)]
+        start_addr: u16,
+    },
+    /// Run a rom without input and save a picture of the display, as a PNG or a
+    /// PBM by the output's extension
+    Screenshot {
+        rom: PathBuf,
+        /// Instructions to run first, fewer if the program ends or waits for a key
+        #[arg(long, default_value_t = 10_000)]
+        steps: u64,
+        /// Interpreter whose quirks to run the rom with
+        #[arg(long)]
+        platform: Option<Platform>,
+        /// Pixels to the side of each of the display's
+        #[arg(long, default_value_t = 8)]
+        scale: usize,
+        /// Where to write the picture, named after the rom and the time by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Where to load the rom and start
+        #[arg(long, value_name = "ADDRESS", default_value = "0x200", value_parser = address)]
+        start_addr: u16,
+    },
+    /// Run a rom under two platforms in lockstep and report the first instruction
+    /// after which their registers, memory or display differ
+    Compare {
+        rom: PathBuf,
+        /// Platform of the first machine, the emulator's own quirks without one
+        #[arg(long)]
+        platform: Option<Platform>,
+        /// Platform of the second machine
+        #[arg(long, required_unless_present = "reference")]
+        against: Option<Platform>,
+        /// Compare the first machine with the reference interpreter instead
+        #[arg(long, conflicts_with = "against")]
+        reference: bool,
+        /// Instructions to run, fewer if the program fails
+        #[arg(long, default_value_t = 100_000)]
+        steps: usize,
+        /// Keys to hold, as for --deterministic, none without a script
+        #[arg(long)]
+        input_script: Option<PathBuf>,
+        /// Where to load the rom and start
+        #[arg(long, value_name = "ADDRESS", default_value = "0x200", value_parser = address)]
+        start_addr: u16,
+    },
+}
+
+impl Cli {
+    /// The quirks of the platform asked for, the emulator's own without one
+    pub fn quirks(&self) -> Quirks {
+        self.platform.map(Quirks::from).unwrap_or_default()
+    }
+
+    /// Instructions per second asked for on the command line, or the default
+    pub fn speed(&self) -> u32 {
+        self.speed.unwrap_or(DEFAULT_SPEED)
+    }
+
+    /// The rng seed asked for, deterministic runs get a fixed one if none is given
+    pub fn seed(&self) -> Option<u64> {
+        match (self.seed, self.deterministic) {
+            (None, true) => Some(determinism::DEFAULT_SEED),
+            (seed, _) => seed,
+        }
+    }
+
+    /// A machine for `rom` set up as the options ask
+    pub fn builder(&self, rom: Rom) -> Chip8Builder {
+        let mut builder = Chip8::builder(rom).start_address(self.start_addr);
+        if let Some(platform) = self.platform {
+            builder = builder.platform(platform);
+        }
+        if let Some(seed) = self.seed() {
+            builder = builder.seed(seed);
+        }
+        builder
+    }
+
+    /// Instructions per second: --speed, else the rom's remembered speed unless
+    /// the run is deterministic, else the config's
+    pub fn speed_with(&self, settings: &RomSettings, config: &Config) -> u32 {
+        let speed = match self.deterministic {
+            true => self.speed,
+            false => self.speed.or(settings.speed),
+        };
+        speed.or(config.speed).unwrap_or(DEFAULT_SPEED)
+    }
+
+    /// The keymap for the rom called `name`: --keymap, else the one remembered in
+    /// `settings`, else the config's
+    pub fn keymap_with(&self, name: &str, settings: &RomSettings, config: &Config) -> Keymap {
+        self.keymap
+            .or(settings.keymap)
+            .unwrap_or_else(|| config.keymap(name))
+    }
+
+    /// A machine for `rom` as the options ask, on the platform remembered in
+    /// `settings` or else the config's without --platform, and with the config's
+    /// quirks over that platform's
+    pub fn builder_with(&self, rom: Rom, settings: &RomSettings, config: &Config) -> Chip8Builder {
+        let platform = self.platform.or(settings.platform).or(config.platform);
+        let mut builder = self.builder(rom);
+        if let Some(platform) = platform {
+            builder = builder.platform(platform);
+        }
+        builder.quirks(config.quirks(platform))
+    }
+}
+
+/// An address in memory, in hex with or without `0x`
+fn address(s: &str) -> Result<u16, String> {
+    u16::from_str_radix(s.trim_start_matches("0x"), 16)
+        .ok()
+        .filter(|&address| address < 0x1000)
+        .ok_or_else(|| format!("bad address `{s}`"))
 }
@@ -0,0 +1,75 @@
+//! Two machines side by side
+//!
+//! The same rom run on two machines in lockstep, usually under two platforms'
+//! quirks, stopping at the first instruction after which they disagree. That is
+//! where the rom starts depending on the quirk, often long before anything looks
+//! wrong on screen. [`reference::lockstep`] does the same against the reference
+//! interpreter.
+use std::fmt;
+
+use crate::{chip8::Chip8, reference};
+
+/// The first point where the two machines disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// how many instructions had already executed in agreement
+    pub step: usize,
+    /// address and opcode of the first machine's instruction that diverged
+    pub address: u16,
+    pub opcode: u16,
+    pub field: &'static str,
+    pub first: String,
+    pub second: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "step {}: {:#06x} at {:#05x} diverged on {}, first {} but second {}",
+            self.step, self.opcode, self.address, self.field, self.first, self.second
+        )
+    }
+}
+
+/// Step `first` and `second` side by side for up to `steps` instructions, `input`
+/// picks the keys held before each step as a bitmask. Seed both the same, or
+/// Cxkk alone tells them apart. Stops early once both agree the program failed.
+pub fn lockstep(
+    first: &mut Chip8,
+    second: &mut Chip8,
+    steps: usize,
+    mut input: impl FnMut(usize) -> u16,
+) -> Result<(), Divergence> {
+    for step in 0..steps {
+        let keys = input(step);
+        first.keys = keys;
+        second.keys = keys;
+        let (address, opcode) = (first.program_counter, first.opcode());
+
+        // one timer tick per instruction, like the other headless runs
+        let results = (first.step().map(|_| ()), second.step().map(|_| ()));
+        first.tick_timers();
+        second.tick_timers();
+
+        let diverged = |field, first: String, second: String| Divergence {
+            step,
+            address,
+            opcode,
+            field,
+            first,
+            second,
+        };
+        if results.0 != results.1 {
+            let (first, second) = (format!("{:?}", results.0), format!("{:?}", results.1));
+            return Err(diverged("result", first, second));
+        }
+        if let Some((field, first, second)) = reference::compare(first, second) {
+            return Err(diverged(field, first, second));
+        }
+        if results.0.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! User configuration, read from `~/.config/chipy8/config.toml`
+//!
+//! Everything can be left out, and what's given on the command line goes
+//! before what's here.
+//!
+//! ```toml
+//! # instructions per second
+//! speed = 1000
+//! # the interpreter whose quirks to run roms with
+//! platform = "schip"
+//! # no buzzer
+//! mute = true
+//! # a preset, or the keys for 0 to F
+//! keymap = "azerty"
+//! # roms to list in the library, L
+//! library = "/home/me/roms"
+//! # default, green, amber or high-contrast
+//! theme = "amber"
+//! # how pixels are drawn: auto, half-block, braille or block
+//! marker = "braille"
+//! # the display as an image: auto, kitty, sixel, or cells for never
+//! graphics = "sixel"
+//! # pixels fade out over 4 frames, hiding flicker
+//! phosphor = 4
+//!
+//! # single colors of the theme, by name or as #rrggbb
+//! [colors]
+//! background = "#000000"
+//!
+//! # quirks turned on or off whatever the platform, named as in the rom info
+//! [quirks]
+//! wrap_sprites = true
+//! display_wait = false
+//!
+//! # panels shown around the display, until toggled
+//! [layout]
+//! registers = false
+//! zen = false
+//!
+//! [macros]
+//! # tap 5 three times, then hold 6 for 10 frames
+//! m = "tap 5 x3, hold 6 10"
+//!
+//! # settings for one rom, by its file name without the extension
+//! [roms.pong]
+//! keymap = "1234qwerasdfzxcv"
+//! ```
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chip8::{Platform, Quirks},
+    graphics::Graphics,
+    input_macro::InputMacro,
+    keymap::Keymap,
+    session::Panels,
+    theme::{Colors, Theme},
+    widget::PixelMarker,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// instructions per second
+    pub speed: Option<u32>,
+    /// the interpreter whose quirks roms run with
+    pub platform: Option<Platform>,
+    /// quirks set in place of the platform's
+    pub quirks: QuirkOverrides,
+    /// keep the buzzer quiet
+    pub mute: bool,
+    /// keys for the keypad, QWERTY's without one
+    pub keymap: Option<Keymap>,
+    /// the directory of roms the library lists
+    pub library: Option<PathBuf>,
+    /// the colors of the terminal frontend
+    pub theme: Option<Theme>,
+    /// colors changed in the theme
+    pub colors: Colors,
+    /// how the terminal frontend draws pixels
+    pub marker: Option<PixelMarker>,
+    /// whether the terminal frontend shows the display as an image
+    pub graphics: Option<Graphics>,
+    /// frames pixels fade out over, from the start
+    pub phosphor: Option<u8>,
+    /// panels shown around the display, a restored session's take their place
+    pub layout: Option<Panels>,
+    /// terminal keys bound to keypad sequences
+    pub macros: BTreeMap<char, InputMacro>,
+    /// overrides for roms by name
+    pub roms: BTreeMap<String, RomConfig>,
+}
+
+/// Settings for one rom, in place of the general ones
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RomConfig {
+    pub keymap: Option<Keymap>,
+}
+
+/// Quirks turned on or off, the rest left as the platform has them
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuirkOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shift_uses_vy: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load_store_increments_i: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jump_uses_vx: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vf_reset: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrap_sprites: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrap_memory: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_wait: Option<bool>,
+}
+
+impl QuirkOverrides {
+    /// `quirks` with the ones given here changed
+    pub fn apply(self, quirks: Quirks) -> Quirks {
+        Quirks {
+            shift_uses_vy: self.shift_uses_vy.unwrap_or(quirks.shift_uses_vy),
+            load_store_increments_i: self
+                .load_store_increments_i
+                .unwrap_or(quirks.load_store_increments_i),
+            jump_uses_vx: self.jump_uses_vx.unwrap_or(quirks.jump_uses_vx),
+            vf_reset: self.vf_reset.unwrap_or(quirks.vf_reset),
+            wrap_sprites: self.wrap_sprites.unwrap_or(quirks.wrap_sprites),
+            wrap_memory: self.wrap_memory.unwrap_or(quirks.wrap_memory),
+            display_wait: self.display_wait.unwrap_or(quirks.display_wait),
+        }
+    }
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("chipy8").join("config.toml"))
+    }
+
+    /// Load `path`, or the default location when none is given.
+    /// A missing file at the default location is an empty config.
+    pub fn load(path: Option<&Path>) -> Result<Self, Error> {
+        match path {
+            Some(path) => Self::parse(&fs::read_to_string(path)?),
+            None => match Self::default_path().map(fs::read_to_string) {
+                Some(Ok(text)) => Self::parse(&text),
+                Some(Err(e)) if e.kind() != ErrorKind::NotFound => Err(e),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// The keymap for the rom called `name`, its own if it has one
+    pub fn keymap(&self, name: &str) -> Keymap {
+        self.roms
+            .get(name)
+            .and_then(|rom| rom.keymap)
+            .or(self.keymap)
+            .unwrap_or_default()
+    }
+
+    /// The quirks of `platform`, the emulator's own without one, with `[quirks]`
+    pub fn quirks(&self, platform: Option<Platform>) -> Quirks {
+        self.quirks
+            .apply(platform.map(Quirks::from).unwrap_or_default())
+    }
+
+    /// The theme, `theme` if given instead of the config's, with `[colors]`
+    pub fn theme(&self, theme: Option<Theme>) -> Theme {
+        theme.or(self.theme).unwrap_or_default().with(&self.colors)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        toml::from_str(text).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+}
@@ -0,0 +1,588 @@
+//! The debugger's command line
+//!
+//! What can be typed at the TUI's `:` prompt, parsed apart from the terminal so it
+//! can be tested. Addresses and values are hex, with or without `0x`, like
+//! everywhere else, while step counts are decimal. Addresses can also be given by
+//! name when the rom has [`Symbols`].
+use std::{
+    fmt::{self, Write},
+    ops::Range,
+    str::FromStr,
+};
+
+use crate::{
+    chip8::{Chip8, Condition, Instruction, Operand, Watchpoint},
+    symbols::Symbols,
+};
+
+/// Bytes `mem` shows
+pub const DUMP_LEN: usize = 16;
+
+/// Something `set` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    V(u8),
+    I,
+    Pc,
+    Delay,
+    Sound,
+}
+
+impl Target {
+    /// In the order the registers panel shows them
+    pub const ALL: [Target; 20] = [
+        Target::V(0x0),
+        Target::V(0x1),
+        Target::V(0x2),
+        Target::V(0x3),
+        Target::V(0x4),
+        Target::V(0x5),
+        Target::V(0x6),
+        Target::V(0x7),
+        Target::V(0x8),
+        Target::V(0x9),
+        Target::V(0xA),
+        Target::V(0xB),
+        Target::V(0xC),
+        Target::V(0xD),
+        Target::V(0xE),
+        Target::V(0xF),
+        Target::Delay,
+        Target::Sound,
+        Target::I,
+        Target::Pc,
+    ];
+
+    /// The widest value it holds
+    fn max(self) -> u16 {
+        match self {
+            Target::V(_) | Target::Delay | Target::Sound => u8::MAX.into(),
+            Target::I | Target::Pc => u16::MAX,
+        }
+    }
+
+    pub fn read(self, chip8: &Chip8) -> u16 {
+        match self {
+            Target::V(x) => chip8.registers[x as usize].into(),
+            Target::I => chip8.i,
+            Target::Pc => chip8.program_counter,
+            Target::Delay => chip8.delay.into(),
+            Target::Sound => chip8.sound.into(),
+        }
+    }
+
+    pub fn write(self, chip8: &mut Chip8, value: u16) {
+        match self {
+            Target::V(x) => chip8.registers[x as usize] = value as u8,
+            Target::I => chip8.i = value,
+            Target::Pc => chip8.program_counter = value,
+            Target::Delay => chip8.delay = value as u8,
+            Target::Sound => chip8.sound = value as u8,
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::V(x) => write!(f, "V{x:X}"),
+            Target::I => f.write_str("I"),
+            Target::Pc => f.write_str("PC"),
+            Target::Delay => f.write_str("DT"),
+            Target::Sound => f.write_str("ST"),
+        }
+    }
+}
+
+/// `v0` to `vf`, `i`, `pc`, `dt` and `st`
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "i" => Ok(Target::I),
+            "pc" => Ok(Target::Pc),
+            "dt" => Ok(Target::Delay),
+            "st" => Ok(Target::Sound),
+            name => name
+                .strip_prefix('v')
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+                .filter(|&x| x < 16)
+                .map(Target::V)
+                .ok_or_else(|| format!("no register `{s}`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// `break 0x230`, stop before the instruction there, or with a [`Condition`]
+    /// like `break 0x230 if v0 == 3` only once it holds
+    Break(u16, Option<Condition>),
+    /// `watch v3` or `watch 0x300..0x310`, see [`Watchpoint`]
+    Watch(Watchpoint),
+    /// `mem 0x300`, show the memory from there
+    Mem(u16),
+    /// `set v1=0xFF`
+    Set(Target, u16),
+    /// `continue`, run on from a pause
+    Continue,
+    /// `step 10`, or just `step` for one
+    Step(u32),
+    /// `display v0 + v1`, show an expression in the watch panel
+    Display(WatchExpression),
+    /// `undisplay 2`, take the third expression out of the watch panel
+    Undisplay(usize),
+    /// `find de ad be ef` or `find "SCORE"`, show the next place memory holds
+    /// those bytes, or with just `find` the next match of the last search
+    Find(Option<Vec<u8>>),
+}
+
+/// Something the watch panel shows, worked out again every tick
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchExpression {
+    /// operands added and subtracted in turn, `v0 + v1` or `[0x300] - 1`, the
+    /// bool set for those subtracted
+    Sum(Vec<(bool, Operand)>),
+    /// a [`Condition`] like `v0 == 3`, 1 while it holds and 0 otherwise
+    Condition(Condition),
+}
+
+impl WatchExpression {
+    pub fn evaluate(&self, chip8: &Chip8) -> u16 {
+        match self {
+            WatchExpression::Sum(terms) => {
+                terms
+                    .iter()
+                    .fold(0, |sum, &(negated, operand)| match negated {
+                        true => sum.wrapping_sub(operand.value(chip8)),
+                        false => sum.wrapping_add(operand.value(chip8)),
+                    })
+            }
+            WatchExpression::Condition(condition) => condition.holds(chip8).into(),
+        }
+    }
+}
+
+impl fmt::Display for WatchExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchExpression::Sum(terms) => {
+                for (n, (negated, operand)) in terms.iter().enumerate() {
+                    match (n, negated) {
+                        (0, true) => write!(f, "-{operand}")?,
+                        (0, false) => write!(f, "{operand}")?,
+                        (_, true) => write!(f, " - {operand}")?,
+                        (_, false) => write!(f, " + {operand}")?,
+                    }
+                }
+                Ok(())
+            }
+            WatchExpression::Condition(condition) => write!(f, "{condition}"),
+        }
+    }
+}
+
+/// A [`Condition`] if it compares, otherwise operands joined by `+` and `-`
+impl FromStr for WatchExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(['=', '<', '>']) {
+            return s.parse().map(WatchExpression::Condition);
+        }
+        let mut terms = Vec::new();
+        let mut negated = false;
+        let mut rest = s;
+        while let Some(at) = rest.find(['+', '-']) {
+            // a sign before the first operand
+            if !(terms.is_empty() && rest[..at].trim().is_empty()) {
+                terms.push((negated, rest[..at].parse()?));
+            }
+            negated = rest[at..].starts_with('-');
+            rest = &rest[at + 1..];
+        }
+        terms.push((negated, rest.parse()?));
+        Ok(WatchExpression::Sum(terms))
+    }
+}
+
+fn hex(text: &str) -> Result<u16, String> {
+    u16::from_str_radix(text.trim().trim_start_matches("0x"), 16)
+        .map_err(|_| format!("bad number `{text}`"))
+}
+
+/// The bytes of `"text"` in quotes, or of hex like `de ad be ef` or `dead beef`
+fn pattern(text: &str) -> Result<Vec<u8>, String> {
+    if let Some(quoted) = text.strip_prefix('"') {
+        return match quoted.strip_suffix('"') {
+            Some(text) if !text.is_empty() => Ok(text.as_bytes().to_vec()),
+            _ => Err(format!("bad text `{text}`")),
+        };
+    }
+    let digits: String = text
+        .split_whitespace()
+        .map(|word| word.trim_start_matches("0x"))
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("`{text}` isn't whole bytes"));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|at| {
+            let byte = digits.get(at..at + 2).unwrap_or_default();
+            u8::from_str_radix(byte, 16).map_err(|_| format!("bad byte `{byte}`"))
+        })
+        .collect()
+}
+
+/// An address by name, or in hex
+fn address(text: &str, symbols: &Symbols) -> Result<u16, String> {
+    symbols.address(text.trim()).map_or_else(|| hex(text), Ok)
+}
+
+/// A command and its argument, most with a one letter short form
+impl FromStr for DebugCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, &Symbols::default())
+    }
+}
+
+impl DebugCommand {
+    /// Parse a command whose addresses can name `symbols`, `break draw_ball`
+    pub fn parse(s: &str, symbols: &Symbols) -> Result<Self, String> {
+        let (name, argument) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+        let argument = argument.trim();
+        let required = || match argument {
+            "" => Err(format!("`{name}` needs an argument")),
+            argument => Ok(argument),
+        };
+        match name {
+            "break" | "b" => {
+                let (address, condition) = match required()?.split_once(" if ") {
+                    Some((address, condition)) => (address, Some(condition.parse()?)),
+                    None => (argument, None),
+                };
+                Ok(DebugCommand::Break(
+                    self::address(address, symbols)?,
+                    condition,
+                ))
+            }
+            "watch" | "w" => required()?.parse().map(DebugCommand::Watch),
+            "mem" | "m" => self::address(required()?, symbols).map(DebugCommand::Mem),
+            "set" => {
+                let (target, value) = required()?
+                    .split_once('=')
+                    .ok_or_else(|| format!("`set` takes register=value, not `{argument}`"))?;
+                let target: Target = target.trim().parse()?;
+                let value = hex(value)?;
+                match value > target.max() {
+                    true => Err(format!("{value:#x} doesn't fit in {target}")),
+                    false => Ok(DebugCommand::Set(target, value)),
+                }
+            }
+            "display" | "d" => required()?.parse().map(DebugCommand::Display),
+            "undisplay" => required()?
+                .parse()
+                .map(DebugCommand::Undisplay)
+                .map_err(|_| format!("bad watch number `{argument}`")),
+            "find" | "f" => match argument {
+                "" => Ok(DebugCommand::Find(None)),
+                pattern => self::pattern(pattern).map(|bytes| DebugCommand::Find(Some(bytes))),
+            },
+            "continue" | "c" => Ok(DebugCommand::Continue),
+            "step" | "s" => match argument {
+                "" => Ok(DebugCommand::Step(1)),
+                count => count
+                    .parse()
+                    .map(DebugCommand::Step)
+                    .map_err(|_| format!("bad step count `{count}`")),
+            },
+            "" => Err("no command".to_string()),
+            _ => Err(format!("no command `{name}`")),
+        }
+    }
+}
+
+/// `de ad be ef`
+pub fn bytes(bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    hex.join(" ")
+}
+
+/// Every address `pattern` starts at in memory, in order
+pub fn find(chip8: &Chip8, pattern: &[u8]) -> Vec<u16> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    chip8
+        .memory
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| *window == pattern)
+        .map(|(address, _)| address as u16)
+        .collect()
+}
+
+/// What changed between a marked state and now
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    /// each register that changed, with its value then and now
+    pub registers: Vec<(String, u16, u16)>,
+    /// runs of memory that changed
+    pub memory: Vec<Range<u16>>,
+    /// pixels that changed, and the columns and rows of the box around them
+    pub pixels: usize,
+    pub display: Option<(Range<usize>, Range<usize>)>,
+}
+
+impl StateDiff {
+    pub fn between(marked: &Chip8, now: &Chip8) -> Self {
+        let mut diff = StateDiff::default();
+        let registers = Target::ALL
+            .iter()
+            .map(|target| (target.to_string(), target.read(marked), target.read(now)))
+            .chain([(
+                "SP".to_string(),
+                marked.stack_pointer.into(),
+                now.stack_pointer.into(),
+            )]);
+        diff.registers = registers.filter(|(_, then, now)| then != now).collect();
+
+        for (address, (then, now)) in marked.memory.iter().zip(&now.memory).enumerate() {
+            if then == now {
+                continue;
+            }
+            let address = address as u16;
+            match diff.memory.last_mut() {
+                Some(run) if run.end == address => run.end += 1,
+                _ => diff.memory.push(address..address + 1),
+            }
+        }
+
+        let (width, height) = (now.width(), now.height());
+        for y in 0..height {
+            for x in 0..width {
+                if marked.pixel(x, y) == now.pixel(x, y) {
+                    continue;
+                }
+                diff.pixels += 1;
+                let (columns, rows) = diff.display.get_or_insert((x..x + 1, y..y + 1));
+                columns.start = columns.start.min(x);
+                columns.end = columns.end.max(x + 1);
+                rows.end = y + 1;
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registers.is_empty() && self.memory.is_empty() && self.pixels == 0
+    }
+}
+
+/// A line per change, `V3 0x0 -> 0x5`, `0x300..0x303` or `12 pixels in 8..16, 4..9`
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "nothing changed");
+        }
+        for (name, then, now) in &self.registers {
+            writeln!(f, "{name} {then:#x} -> {now:#x}")?;
+        }
+        for run in &self.memory {
+            match run.len() {
+                1 => writeln!(f, "memory {:#05x}", run.start)?,
+                len => writeln!(
+                    f,
+                    "memory {:#05x}..{:#05x}, {len} bytes",
+                    run.start, run.end
+                )?,
+            }
+        }
+        if let Some((columns, rows)) = &self.display {
+            writeln!(f, "{} pixels in x {columns:?}, y {rows:?}", self.pixels)?;
+        }
+        Ok(())
+    }
+}
+
+/// `0x300  a2 1e 60 00 ...`, [`DUMP_LEN`] bytes or up to the end of memory
+pub fn dump(chip8: &Chip8, address: u16) -> String {
+    let start = (address as usize).min(chip8.memory.len());
+    let end = (start + DUMP_LEN).min(chip8.memory.len());
+    let mut line = format!("{address:#05x} ");
+    for byte in &chip8.memory[start..end] {
+        write!(line, " {byte:02x}").ok();
+    }
+    line
+}
+
+/// A subroutine call still waiting for its RET
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackFrame {
+    /// where the CALL is, RET comes back to the instruction after it
+    pub return_address: u16,
+    /// the subroutine it called, unless the CALL has since been overwritten
+    pub subroutine: Option<u16>,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#05x}", self.return_address)?;
+        match self.subroutine {
+            Some(subroutine) => write!(f, " sub_{subroutine:03x}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl StackFrame {
+    /// As it's displayed, with the subroutine's name from `symbols` if it has one
+    pub fn describe(&self, symbols: &Symbols) -> String {
+        match self.subroutine.and_then(|address| symbols.name(address)) {
+            Some(name) => format!("{:#05x} {name}", self.return_address),
+            None => self.to_string(),
+        }
+    }
+}
+
+/// The first address of a disassembly `rows` instructions long, of `memory`
+/// bytes, with `focus` `above` instructions down. Near either end of memory the
+/// window stops there and the focus moves off the middle instead, and it goes
+/// in whole instructions from the focus, even an odd one.
+pub fn program_window(focus: usize, above: usize, rows: usize, memory: usize) -> usize {
+    let focus = focus.min(memory.saturating_sub(2));
+    // the last instruction that fits in memory, in step with the focus
+    let last = focus + (memory.saturating_sub(2) - focus) / 2 * 2;
+    let back = |from: usize, instructions: usize| from - (instructions * 2).min(from & !1);
+    back(focus, above).min(back(last, rows.saturating_sub(1)))
+}
+
+/// `instruction` as it's displayed, with the address it names given by name
+/// where `symbols` have one, `CALL draw_ball`
+pub fn mnemonic(instruction: Instruction, symbols: &Symbols) -> String {
+    use Instruction::*;
+    let (operation, nnn) = match instruction {
+        Jp(nnn) => ("JP", nnn),
+        Call(nnn) => ("CALL", nnn),
+        LdI(nnn) => ("LD I,", nnn),
+        JpV0(nnn) => ("JP V0,", nnn),
+        _ => return instruction.to_string(),
+    };
+    match symbols.name(nnn) {
+        Some(name) => format!("{operation} {name}"),
+        None => instruction.to_string(),
+    }
+}
+
+/// The calls in progress, innermost first
+pub fn call_stack(chip8: &Chip8) -> Vec<StackFrame> {
+    let depth = (chip8.stack_pointer as usize).min(chip8.stack.len() - 1);
+    chip8.stack[1..=depth]
+        .iter()
+        .rev()
+        .map(|&return_address| {
+            let at = return_address as usize;
+            let opcode = match chip8.memory.get(at..at + 2) {
+                Some(&[high, low]) => u16::from_be_bytes([high, low]),
+                _ => 0,
+            };
+            let subroutine = match Instruction::decode(opcode) {
+                Instruction::Call(nnn) => Some(nnn),
+                _ => None,
+            };
+            StackFrame {
+                return_address,
+                subroutine,
+            }
+        })
+        .collect()
+}
+
+/// Where in-place editing points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditCursor {
+    Memory(u16),
+    Register(Target),
+}
+
+/// Editing memory and registers in place while paused
+///
+/// Hex digits typed at the cursor are written once there are as many as the value
+/// has, two for a byte, four for I and the PC, or earlier with [`Editor::commit`].
+/// Writing a byte moves on to the next, like typing into a hex editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Editor {
+    pub cursor: EditCursor,
+    /// the digits typed so far, not yet written
+    pending: String,
+}
+
+impl Editor {
+    pub fn new(cursor: EditCursor) -> Self {
+        Editor {
+            cursor,
+            pending: String::new(),
+        }
+    }
+
+    pub fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    /// Move by `delta` bytes or registers, dropping any digits typed
+    pub fn move_by(&mut self, delta: i32, chip8: &Chip8) {
+        self.pending.clear();
+        self.cursor = match self.cursor {
+            EditCursor::Memory(address) => {
+                let last = chip8.memory.len() as i32 - 1;
+                EditCursor::Memory((address as i32 + delta).clamp(0, last) as u16)
+            }
+            EditCursor::Register(target) => {
+                let at = Target::ALL.iter().position(|&t| t == target).unwrap_or(0);
+                let at = (at as i32 + delta).clamp(0, Target::ALL.len() as i32 - 1);
+                EditCursor::Register(Target::ALL[at as usize])
+            }
+        };
+    }
+
+    /// Type a hex digit, returning what was written once the value is complete
+    pub fn type_digit(&mut self, digit: char, chip8: &mut Chip8) -> Option<String> {
+        if !digit.is_ascii_hexdigit() {
+            return None;
+        }
+        self.pending.push(digit);
+        let width = match self.cursor {
+            EditCursor::Register(target) if target.max() > u8::MAX.into() => 4,
+            _ => 2,
+        };
+        match self.pending.len() >= width {
+            true => self.commit(chip8),
+            false => None,
+        }
+    }
+
+    /// Write the digits typed so far, returning what was written
+    pub fn commit(&mut self, chip8: &mut Chip8) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let value = u16::from_str_radix(&self.pending, 16).ok()?;
+        self.pending.clear();
+        match self.cursor {
+            EditCursor::Memory(address) => {
+                let byte = chip8.memory.get_mut(address as usize)?;
+                *byte = value as u8;
+                self.move_by(1, chip8);
+                Some(format!("{address:#05x} set to {value:#04x}"))
+            }
+            EditCursor::Register(target) => {
+                target.write(chip8, value);
+                Some(format!("{target} set to {value:#x}"))
+            }
+        }
+    }
+}
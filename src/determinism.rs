@@ -0,0 +1,114 @@
+//! Strict determinism
+//!
+//! A deterministic run depends on nothing but the rom, the rng seed and an input
+//! script. The emulator core never reads the wall clock, and a deterministic run
+//! has it count timer ticks in instructions ([`TimerSource::Instructions`]), so the
+//! step count serves as the virtual clock the script is keyed on.
+//!
+//! [`TimerSource::Instructions`]: crate::chip8::TimerSource::Instructions
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use crate::chip8::Chip8;
+
+/// Seed used when determinism is asked for without a specific seed
+pub const DEFAULT_SEED: u64 = 0xC8;
+
+/// Keys to hold, each taking effect at a given step
+///
+/// The text format is one `<step> <key>` pair per line, with the key in hex, or
+/// `-` to let go of the keypad. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputScript {
+    events: Vec<(u64, Option<u8>)>,
+}
+
+impl InputScript {
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let invalid = |line: usize, reason: &str| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("input script line {}: {reason}", line + 1),
+            )
+        };
+        let mut events = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let (Some(step), Some(key), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid(n, "expected `<step> <key>`"));
+            };
+            let step: u64 = step.parse().map_err(|_| invalid(n, "bad step"))?;
+            let key = match key {
+                "-" => None,
+                key => Some(
+                    u8::from_str_radix(key, 16)
+                        .ok()
+                        .filter(|k| *k < 16)
+                        .ok_or_else(|| invalid(n, "key must be 0..F or -"))?,
+                ),
+            };
+            events.push((step, key));
+        }
+        // later lines win for the same step
+        events.sort_by_key(|(step, _)| *step);
+        Ok(Self { events })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The key held at `step`, none until the first event
+    pub fn key_at(&self, step: u64) -> Option<u8> {
+        let taken = self.events.partition_point(|(s, _)| *s <= step);
+        taken.checked_sub(1).and_then(|i| self.events[i].1)
+    }
+
+    /// [`InputScript::key_at`] as a keypad bitmask
+    pub fn keys_at(&self, step: u64) -> u16 {
+        self.key_at(step).map_or(0, |key| 1 << key)
+    }
+}
+
+/// One executed instruction and the machine state it left behind
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceEntry {
+    pub step: u64,
+    pub address: u16,
+    pub opcode: u16,
+    pub registers: [u8; 16],
+    pub i: u16,
+    pub delay: u8,
+    pub sound: u8,
+}
+
+/// Run `steps` instructions with input taken only from `script`.
+/// Timers only move if `chip8` counts them in instructions.
+/// The trace stops short at an instruction that fails.
+pub fn run(chip8: &mut Chip8, script: &InputScript, steps: u64) -> Vec<TraceEntry> {
+    (0..steps)
+        .map_while(|step| {
+            chip8.keys = script.keys_at(step);
+            let address = chip8.program_counter;
+            let opcode = chip8.opcode();
+            chip8.step().ok()?;
+            Some(TraceEntry {
+                step,
+                address,
+                opcode,
+                registers: chip8.registers,
+                i: chip8.i,
+                delay: chip8.delay,
+                sound: chip8.sound,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,170 @@
+//! Disassembler
+//!
+//! Turns memory into a listing of addresses, bytes and mnemonics. Code is found by
+//! following jumps, calls and skips from the entry point. Whatever `LD I` points at
+//! is taken to be data, up to the next address that is reached as code, and listed
+//! a byte per line with its bits drawn out, since it is usually a sprite. Bytes
+//! that are neither are decoded as instructions anyway.
+use std::{collections::BTreeSet, fmt, ops::Range};
+
+use crate::chip8::{Instruction, PROGRAM_START};
+use crate::rom::Rom;
+
+/// What a line of the listing holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Code(Instruction),
+    Data(u8),
+}
+
+/// Why an address is worth a label
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Label {
+    /// target of a CALL
+    Subroutine,
+    /// target of a JP
+    Jump,
+    /// target of an LD I
+    Data,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub address: u16,
+    /// the two bytes of an instruction or the one of data
+    pub bytes: Vec<u8>,
+    pub kind: Kind,
+    pub label: Option<Label>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Listing {
+    pub lines: Vec<Line>,
+}
+
+/// Disassemble `range` of `memory`, tracing code from `entry`
+pub fn disassemble(memory: &[u8], range: Range<u16>, entry: u16) -> Listing {
+    let end = range.end.min(memory.len() as u16);
+    let range = range.start..end;
+    let opcode = |address: u16| {
+        (address + 1 < end)
+            .then(|| u16::from_be_bytes([memory[address as usize], memory[address as usize + 1]]))
+    };
+
+    // addresses reached as code, and the labels found on the way
+    let mut code = BTreeSet::new();
+    let mut labels = Vec::new();
+    let mut pending = vec![entry];
+    while let Some(address) = pending.pop() {
+        if !range.contains(&address) || code.contains(&address) {
+            continue;
+        }
+        let Some(opcode) = opcode(address) else {
+            continue;
+        };
+        code.insert(address);
+        let next = address + 2;
+        use Instruction::*;
+        match Instruction::decode(opcode) {
+            Ret | Exit | JpV0(_) | Unknown(_) => {}
+            Jp(target) => {
+                labels.push((target, Label::Jump));
+                pending.push(target);
+            }
+            Call(target) => {
+                labels.push((target, Label::Subroutine));
+                pending.extend([target, next]);
+            }
+            SeVx(..) | SneVx(..) | SeVxVy(..) | SneVxVy(..) | Skp(_) | Sknp(_) => {
+                pending.extend([next, next + 2])
+            }
+            LdI(target) => {
+                labels.push((target, Label::Data));
+                pending.push(next);
+            }
+            _ => pending.push(next),
+        }
+    }
+    // a label earlier in `Label`'s order wins when an address has several
+    labels.sort();
+    labels.dedup_by_key(|(address, _)| *address);
+    let label_at = |address: u16| {
+        labels
+            .binary_search_by_key(&address, |(a, _)| *a)
+            .ok()
+            .map(|i| labels[i].1)
+    };
+
+    // data runs from each LD I target up to the next code
+    let mut data = BTreeSet::new();
+    for (target, label) in &labels {
+        if *label != Label::Data || !range.contains(target) {
+            continue;
+        }
+        let stop = code.range(target..).next().copied().unwrap_or(end);
+        data.extend(*target..stop);
+    }
+
+    let mut lines = Vec::new();
+    let mut address = range.start;
+    while address < end {
+        let label = label_at(address);
+        let line = match opcode(address) {
+            Some(opcode) if !data.contains(&address) => Line {
+                address,
+                bytes: opcode.to_be_bytes().to_vec(),
+                kind: Kind::Code(Instruction::decode(opcode)),
+                label,
+            },
+            _ => Line {
+                address,
+                bytes: vec![memory[address as usize]],
+                kind: Kind::Data(memory[address as usize]),
+                label,
+            },
+        };
+        address += line.bytes.len() as u16;
+        lines.push(line);
+    }
+    Listing { lines }
+}
+
+/// Disassemble a rom as it is loaded, starting at the program start
+pub fn disassemble_rom(rom: &Rom) -> Listing {
+    let mut memory = vec![0; PROGRAM_START];
+    memory.extend_from_slice(&rom.contents);
+    let start = PROGRAM_START as u16;
+    disassemble(&memory, start..memory.len() as u16, start)
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let address = self.address;
+        match self.label {
+            Some(Label::Subroutine) => writeln!(f, "sub_{address:03x}:")?,
+            Some(Label::Jump) => writeln!(f, "label_{address:03x}:")?,
+            Some(Label::Data) => writeln!(f, "data_{address:03x}:")?,
+            None => {}
+        }
+        let bytes: Vec<String> = self.bytes.iter().map(|b| format!("{b:02x}")).collect();
+        let bytes = bytes.join(" ");
+        match self.kind {
+            Kind::Code(instruction) => write!(f, "{address:#05x}  {bytes:<5}  {instruction}"),
+            Kind::Data(byte) => {
+                let bits: String = (0..8)
+                    .map(|bit| if byte & 0x80 >> bit != 0 { '#' } else { '.' })
+                    .collect();
+                write!(f, "{address:#05x}  {bytes:<5}  DB {byte:#04x}  ; {bits}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,847 @@
+//! Drawing the terminal frontend
+//!
+//! The screens from the bottom of the stack up: the display alone or with the
+//! debugger's panels around it, then whatever is open over them, and the status
+//! bar and the console under everything.
+use super::*;
+
+impl App {
+    pub(super) fn draw(&self, frame: &mut Frame) {
+        *self.hitboxes.borrow_mut() = Hitboxes::default();
+        let status_height = match self.screens.base() {
+            Screen::Emulator => 0,
+            _ => 1,
+        };
+        let [mut area, status_bar, console] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(status_height),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        self.render_status_bar(status_bar, frame);
+        self.render_console(console, frame);
+        if self.tabs.len() > 1 {
+            let [tab_bar, rest] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).areas(area);
+            let names = self.tabs.iter().map(|tab| tab.chip8.rom.name());
+            frame.render_widget(
+                Tabs::new(names)
+                    .select(self.active)
+                    .highlight_style(Style::new().fg(self.theme.highlight)),
+                tab_bar,
+            );
+            area = rest;
+        }
+        for screen in self.screens.iter() {
+            match screen {
+                Screen::Emulator => self.render_emulator(area, frame),
+                Screen::Debugger => {
+                    let display = self.render_panels(area, frame);
+                    self.render_over_display(display, frame);
+                }
+                Screen::RomBrowser { selected } => self.render_library(selected, frame),
+                Screen::Settings { selected } => self.render_settings(selected, frame),
+                Screen::Help => frame.render_widget(
+                    screen::Help::new(HELP).highlight(self.theme.highlight),
+                    frame.area(),
+                ),
+            }
+        }
+    }
+
+    /// The display alone, as large as it fits in `area`
+    fn render_emulator(&self, area: Rect, frame: &mut Frame) {
+        match self.shows_image() {
+            true => {
+                frame.render_widget(Block::new().bg(self.theme.background), area);
+                self.image_area.set(Some(area));
+            }
+            false => {
+                let chip8 = &self.tab().chip8;
+                frame.render_widget(
+                    ScaledDisplay::new(chip8)
+                        .colors(self.theme.foreground, self.theme.background)
+                        .phosphor(self.tab().glow()),
+                    area,
+                );
+            }
+        }
+        self.render_over_display(area, frame);
+    }
+
+    /// Whether the display goes out as an image this frame, which it can while
+    /// nothing is drawn over it
+    fn shows_image(&self) -> bool {
+        let covered = self.show_timing
+            || self.tab().scrub.is_some()
+            || self.sprite_preview.is_some()
+            || self.tab().error.is_some()
+            || !self.screens.top().is_base();
+        match self.graphics {
+            Graphics::Kitty | Graphics::Sixel => self.cell_size.is_some() && !covered,
+            Graphics::Auto | Graphics::Cells => false,
+        }
+    }
+
+    /// The size of a cell in pixels, if the terminal tells
+    pub(super) fn cell_size(&self) -> Option<(u16, u16)> {
+        if let Graphics::Auto | Graphics::Cells = self.graphics {
+            return None;
+        }
+        let size = crossterm::terminal::window_size().ok()?;
+        match (size.columns, size.rows, size.width, size.height) {
+            (0, _, _, _) | (_, 0, _, _) | (_, _, 0, _) | (_, _, _, 0) => None,
+            (columns, rows, width, height) => Some((width / columns, height / rows)),
+        }
+    }
+
+    /// Send the display's image to where the last frame left room for it,
+    /// unless it's there already, returning whether the screen needs drawing
+    /// afresh now that one went away
+    pub(super) fn show_image(&mut self) -> io::Result<bool> {
+        let (Some(area), Some(cell)) = (self.image_area.take(), self.cell_size) else {
+            if self.shown.take().is_none() {
+                return Ok(false);
+            }
+            // kitty's lies over the text and is taken away, sixel's is in the
+            // cells and only goes once they're all drawn again
+            return match self.graphics {
+                Graphics::Kitty => {
+                    io::stdout().write_all(graphics::KITTY_DELETE.as_bytes())?;
+                    Ok(false)
+                }
+                _ => Ok(true),
+            };
+        };
+        let tab = self.tab();
+        let raster = Raster::new(
+            &tab.chip8,
+            tab.glow(),
+            self.theme.foreground,
+            self.theme.background,
+        );
+        if let Some((shown_area, shown)) = &self.shown {
+            if *shown_area == area && *shown == raster {
+                return Ok(false);
+            }
+        }
+        let (width, height) = (raster.width(), raster.height());
+        let scale = graphics::scale(width, height, area.width, area.height, cell);
+        // centred, to the nearest cell
+        let columns = (width * scale).div_ceil(cell.0.into()) as u16;
+        let rows = (height * scale).div_ceil(cell.1.into()) as u16;
+        let x = area.x + area.width.saturating_sub(columns) / 2;
+        let y = area.y + area.height.saturating_sub(rows) / 2;
+        let image = match self.graphics {
+            Graphics::Kitty => raster.kitty(scale)?,
+            _ => raster.sixel(scale),
+        };
+        let mut stdout = io::stdout().lock();
+        crossterm::queue!(stdout, crossterm::cursor::MoveTo(x, y))?;
+        stdout.write_all(image.as_bytes())?;
+        stdout.flush()?;
+        self.shown = Some((area, raster));
+        Ok(false)
+    }
+
+    /// What shows over the display where it was drawn: the timing, the rewind
+    /// timeline, the sprite at I and the error that stopped the program
+    fn render_over_display(&self, display: Rect, frame: &mut Frame) {
+        if self.show_timing {
+            self.render_timing(display, frame);
+        }
+        if let Some(scrub) = &self.tab().scrub {
+            let [_, timeline] =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(display);
+            let len = self.tab().history.len();
+            let title = format!("Rewind, {} steps back", len - scrub.index);
+            frame.render_widget(Clear, timeline);
+            frame.render_widget(
+                Timeline::new(len, scrub.index)
+                    .color(self.theme.highlight)
+                    .block(Block::bordered().title(title)),
+                timeline,
+            );
+        }
+        if let Some(height) = self.sprite_preview {
+            self.render_sprite_preview(height, display, frame);
+        }
+        if let Some(error) = self.tab().error {
+            self.render_error(error, display, frame);
+        }
+    }
+
+    /// The display and the panels shown around it, returning where the display
+    /// went
+    fn render_panels(&self, area: Rect, frame: &mut Frame) -> Rect {
+        // room for the display as drawn, no less than the 64x16 cells of lo-res
+        // in half blocks
+        let chip8 = &self.tab().chip8;
+        let (columns, rows) = self.marker.cells(chip8.width(), chip8.height());
+        let horizontal =
+            Layout::horizontal([Constraint::Length(columns.max(64) + 2), Constraint::Min(1)]);
+        let [left, right] = horizontal.areas(area);
+
+        let left_vertical = Layout::vertical([
+            Constraint::Length(rows.max(16) + 2),
+            Constraint::Min(match self.panels.registers {
+                true => 6,
+                false => 0,
+            }),
+        ]);
+        let [display, bottom_left] = left_vertical.areas(left);
+        match self.shows_image() {
+            true => {
+                let block = self.display_block();
+                self.image_area.set(Some(block.inner(display)));
+                frame.render_widget(block.bg(self.theme.background), display);
+            }
+            false => frame.render_widget(self.display(), display),
+        }
+        let [registers, call_stack] =
+            Layout::horizontal([Constraint::Min(1), Constraint::Length(22)]).areas(bottom_left);
+
+        // the watch and diff panels only while there is something to show
+        let watches = self.tab().watches.len() as u16;
+        let watch_height = if watches > 0 { watches + 2 } else { 0 };
+        let diff = self
+            .tab()
+            .marked
+            .as_ref()
+            .map(|marked| StateDiff::between(marked, &self.tab().chip8).to_string());
+        let diff_height = diff
+            .as_ref()
+            .map_or(0, |diff| (diff.lines().count() as u16).min(DIFF_LINES) + 2);
+        let log_height = if self.show_log { LOG_LINES + 2 } else { 0 };
+        let input_height = if self.panels.input { 7 } else { 0 };
+        let info_height = if self.show_rom_info { 8 } else { 0 };
+        let right_vertical = Layout::vertical([
+            Constraint::Length(info_height),
+            Constraint::Min(match self.panels.program {
+                true => 1,
+                false => 0,
+            }),
+            Constraint::Length(watch_height),
+            Constraint::Length(diff_height),
+            Constraint::Length(log_height),
+            Constraint::Length(input_height),
+        ]);
+        let [info, n1, watch, diff_area, log_area, n2] = right_vertical.areas(right);
+        if self.show_rom_info {
+            self.render_rom_info(info, frame);
+        }
+        self.render_watches(watch, frame);
+        self.render_log(log_area, frame);
+        if let Some(diff) = diff {
+            frame.render_widget(
+                Paragraph::new(diff)
+                    .block(Block::bordered().title("Changed since the mark, K to clear")),
+                diff_area,
+            );
+        }
+
+        if self.panels.registers {
+            self.render_registers(registers, frame);
+            self.render_call_stack(call_stack, frame);
+        }
+        match (self.memory_view, self.tab().chip8.profile()) {
+            _ if !self.panels.program => {}
+            (Some(start), _) => self.render_memory(start, n1, frame),
+            (None, Some(profile)) if self.show_profile => self.render_profile(profile, n1, frame),
+            _ => self.render_program(n1, frame),
+        }
+        if self.panels.input {
+            self.hitboxes.borrow_mut().input = n2.inner(Margin::new(1, 1));
+            let title = match self.show_key_heatmap {
+                true => "Input, by presses",
+                false => "Input",
+            };
+            frame.render_widget(
+                HexInput::new(self.tab().chip8.keys)
+                    .colors(self.theme.highlight, self.theme.timers)
+                    .activity(&self.tab().keypad)
+                    .heatmap(self.show_key_heatmap)
+                    .block(Block::bordered().title(title)),
+                n2,
+            );
+        }
+        display
+    }
+    /// What the active tab is doing and how fast it really goes
+    fn render_status_bar(&self, area: Rect, frame: &mut Frame) {
+        let tab = self.tab();
+        let activity = format!(" {} ", tab.activity());
+        let activity = match (tab.mode, tab.chip8.state) {
+            (_, State::Halted) => Span::from(activity).black().on_red(),
+            (Mode::Running, State::WaitingForKey { .. }) => Span::from(activity).black().on_cyan(),
+            (Mode::Running, _) => Span::from(activity).black().bg(self.theme.highlight),
+            (Mode::Paused, _) => Span::from(activity).black().on_yellow(),
+        };
+        let quirks = match Platform::of(tab.chip8.quirks) {
+            Some(platform) => platform.to_string(),
+            None if tab.chip8.quirks == Quirks::default() => "default quirks".to_string(),
+            None => "custom quirks".to_string(),
+        };
+        let now = Instant::now();
+        // for terminals that can't be heard
+        let sound = match matches!(tab.mode, Mode::Running) && tab.chip8.sound > 0 {
+            true => Span::from(" ♪ SOUND ").black().on_yellow(),
+            false => Span::from(""),
+        };
+        let line = Line::from(vec![
+            activity,
+            sound,
+            Span::from(format!(
+                " {:.0} fps  {:.0} ips  {quirks}  x{:.2}",
+                self.fps.per_second(now),
+                self.ips.per_second(now),
+                tab.multiplier(),
+            )),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+    }
+    /// The `:` prompt while typing a command, otherwise what the last one said
+    fn render_console(&self, area: Rect, frame: &mut Frame) {
+        if let Some(editor) = &self.editor {
+            let chip8 = &self.tab().chip8;
+            let (name, value) = match editor.cursor {
+                EditCursor::Memory(address) => {
+                    let byte = chip8.memory[address as usize];
+                    (format!("{address:#05x}"), format!("{byte:#04x}"))
+                }
+                EditCursor::Register(target) => {
+                    (target.to_string(), format!("{:#x}", target.read(chip8)))
+                }
+            };
+            let line = Line::from(vec![
+                Span::from(format!("{name} = {value} ")),
+                Span::from(format!("{}_", editor.pending())).yellow(),
+                Span::from("  hex to write, tab for memory/registers, esc to stop").dim(),
+            ]);
+            frame.render_widget(Paragraph::new(line), area);
+            return;
+        }
+        let line = match (&self.console, &self.console_output) {
+            (Some(line), _) => {
+                let x = area.x + 1 + line.chars().count() as u16;
+                frame.set_cursor_position((x.min(area.right().saturating_sub(1)), area.y));
+                Line::from(format!(":{line}"))
+            }
+            (None, Some(output)) => Line::from(output.as_str()).dim(),
+            (None, None) => Line::from(": for debugger commands").dim(),
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+    /// The error that stopped the program, over the display so the panels
+    /// around it can still be looked through
+    fn render_error(&self, error: Chip8Error, area: Rect, frame: &mut Frame) {
+        let lines = vec![
+            Line::from(error.to_string()),
+            Line::from(""),
+            Line::from("space to retry, J to skip it, Enter to edit, esc to quit").dim(),
+        ];
+        let [area] = Layout::horizontal([Constraint::Length(60)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .centered()
+                .block(Block::bordered().title("Error").red()),
+            area,
+        );
+    }
+    /// The sprite at I in the bottom right corner of `area`, as tall as the next
+    /// instruction draws if it is a DRW, otherwise `height` rows
+    fn render_sprite_preview(&self, height: u8, area: Rect, frame: &mut Frame) {
+        let chip8 = &self.tab().chip8;
+        let (height, next) = match Instruction::decode(chip8.opcode()) {
+            Instruction::Drw(_, _, n) => (n, " DRW"),
+            _ => (height, ""),
+        };
+        let (rows, width) = SpritePreview::size(height);
+        let [_, area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(width * 2 + 2)]).areas(area);
+        let [_, area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(rows + 2)]).areas(area);
+        let title = format!("I {:#05x}{next}", chip8.i);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            SpritePreview::new(&chip8.memory, chip8.i, height)
+                .color(self.theme.foreground)
+                .block(Block::bordered().title(title)),
+            area,
+        );
+    }
+    /// Frame timing overlay in the top right corner of `area`
+    fn render_timing(&self, area: Rect, frame: &mut Frame) {
+        let summary = self.timing.summary();
+        let ms = |d: Duration| format!("{:6.2}ms", d.as_secs_f64() * 1000.0);
+        let missed = Span::from(format!("{:>8}", summary.missed_deadlines));
+        let missed = if summary.missed_deadlines > 0 {
+            missed.red()
+        } else {
+            missed.green()
+        };
+        let lines = vec![
+            Line::from(format!("frames      {:>8}", summary.frames)),
+            Line::from(format!(
+                "emulate {} max {}",
+                ms(summary.mean_emulation),
+                ms(summary.max_emulation)
+            )),
+            Line::from(format!(
+                "render  {} max {}",
+                ms(summary.mean_render),
+                ms(summary.max_render)
+            )),
+            Line::from(vec![Span::from("missed 60Hz "), missed]),
+            Line::from(format!("jitter  {}", ms(summary.jitter))),
+        ];
+        let [_, area] =
+            Layout::horizontal([Constraint::Min(0), Constraint::Length(34)]).areas(area);
+        let [_, area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(lines.len() as u16 + 2),
+        ])
+        .areas(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Timing, last 3s")),
+            area,
+        );
+    }
+    /// The debugger's options of the active tab, Enter toggles the selected one
+    fn render_settings(&self, selected: usize, frame: &mut Frame) {
+        let items: Vec<Line> = Command::iter()
+            .map(|command| {
+                let check = if self.tab().enabled(command) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                Line::from(format!("{check}{command}"))
+            })
+            .collect();
+        frame.render_widget(
+            screen::Menu::new("Settings", items, selected)
+                .width(36)
+                .highlight(self.theme.highlight),
+            frame.area(),
+        );
+    }
+
+    /// The library's roms with their size, platform and when they were last
+    /// played
+    fn render_library(&self, selected: usize, frame: &mut Frame) {
+        let now = library::now();
+        let items: Vec<Line> = self
+            .library_roms
+            .iter()
+            .map(|rom| {
+                let settings = self.library.settings(rom.hash);
+                let platform = settings
+                    .platform
+                    .or(rom.platform())
+                    .map_or("-".to_string(), |platform| platform.to_string());
+                let open = self.tabs.iter().any(|tab| tab.chip8.rom.hash() == rom.hash);
+                let played = match (open, settings.last_played) {
+                    (true, _) => "open".to_string(),
+                    (false, Some(then)) => library::ago(then, now),
+                    (false, None) => "never".to_string(),
+                };
+                Line::from(format!(
+                    "{:<24} {:>6} {platform:<8} {played}",
+                    rom.name(),
+                    rom.size
+                ))
+            })
+            .collect();
+        let title = match &self.library_dir {
+            Some(dir) => format!("Library {}", dir.display()),
+            None => "Library".to_string(),
+        };
+        frame.render_widget(
+            screen::Menu::new(title, items, selected)
+                .width(60)
+                .highlight(self.theme.highlight)
+                .empty("no roms"),
+            frame.area(),
+        );
+    }
+
+    /// Opcode families by the time they took, with --profile
+    fn render_profile(&self, profile: &Profile, area: Rect, frame: &mut Frame) {
+        frame.render_widget(
+            Paragraph::new(profile.to_string())
+                .block(Block::bordered().title("Profile, h for program")),
+            area,
+        );
+    }
+    /// All of memory, PgUp and PgDn to scroll
+    fn render_memory(&self, start: u16, area: Rect, frame: &mut Frame) {
+        let chip8 = &self.tab().chip8;
+        let view = MemoryView::new(&chip8.memory)
+            .scroll(start)
+            .pc(chip8.program_counter)
+            .i(chip8.i)
+            .highlight(self.theme.highlight)
+            .block(Block::bordered().title("Memory, m for program, M for heatmap"));
+        let view = match self.editor.as_ref().map(|editor| editor.cursor) {
+            Some(EditCursor::Memory(address)) => view.cursor(address),
+            _ => view,
+        };
+        let colors = match self.show_heatmap {
+            true => self.tab().heatmap(),
+            false => Vec::new(),
+        };
+        let view = view.colors(&colors);
+        let view = match &self.tab().search {
+            Some((pattern, at)) => view.mark(*at..at.saturating_add(pattern.len() as u16)),
+            None => view,
+        };
+        self.hitboxes.borrow_mut().memory = area.inner(Margin::new(1, 1));
+        frame.render_widget(view, area);
+    }
+    fn render_program(&self, area: Rect, frame: &mut Frame) {
+        let outer_block = Block::bordered().title("Program");
+        let inner = outer_block.inner(area);
+        frame.render_widget(outer_block, area);
+
+        // the instructions around the cursor, or the PC without one, in the
+        // middle line, unless scrolled away or at either end of memory
+        let chip8 = &self.tab().chip8;
+        let pc = chip8.program_counter as usize;
+        let cursor = self.tab().program_cursor.map(usize::from);
+        let scroll = self.tab().program_scroll as isize * 2;
+        let focus = cursor.unwrap_or(pc).saturating_add_signed(scroll);
+        let (above, rows) = (inner.height as usize / 2, inner.height as usize);
+        let start = debugger::program_window(focus, above, rows, chip8.memory.len());
+        let symbols = &self.tab().symbols;
+        let mut lines = Vec::new();
+        let mut addresses = Vec::new();
+        let mut focus_line = 0;
+        // the instruction that stopped the program
+        let failed = self.tab().error.and_then(|error| error.address());
+        for addr in (start..chip8.memory.len().saturating_sub(1))
+            .step_by(2)
+            .take(rows)
+        {
+            if let Some(name) = symbols.name(addr as u16) {
+                lines.push(Line::from(format!("   {name}:")).blue());
+                addresses.push(None);
+            }
+            if addr == focus {
+                focus_line = lines.len();
+            }
+            let breakpoint = chip8.breakpoints.contains(&(addr as u16));
+            let (b1, b2) = (chip8.memory[addr], chip8.memory[addr + 1]);
+            let mnemonic = Instruction::decode(u16::from_be_bytes([b1, b2]));
+            let mnemonic = debugger::mnemonic(mnemonic, symbols);
+            let line = style_instruction(
+                pc,
+                addr,
+                breakpoint,
+                [b1, b2],
+                mnemonic,
+                self.theme.highlight,
+            );
+            addresses.push(Some(addr as u16));
+            lines.push(match (failed == Some(addr as u16), cursor == Some(addr)) {
+                (true, _) => line.black().on_red(),
+                (_, true) => line.on_dark_gray(),
+                _ => line,
+            });
+        }
+        // label lines push the focus down, keep it where it was without them
+        let skip = focus_line
+            .saturating_sub(above)
+            .min(lines.len().saturating_sub(rows));
+
+        let mut hitboxes = self.hitboxes.borrow_mut();
+        hitboxes.program = inner;
+        hitboxes.program_rows = addresses.into_iter().skip(skip).collect();
+
+        let list = List::new(lines.into_iter().skip(skip));
+        frame.render_widget(list, inner);
+    }
+
+    /// Which rom file is loaded and how: its path, size and SHA-1, the platform
+    /// its extension suggests, the quirks it runs with and where it was loaded
+    fn render_rom_info(&self, area: Rect, frame: &mut Frame) {
+        let chip8 = &self.tab().chip8;
+        let rom = &chip8.rom;
+        let detected = match library::platform_of(rom.path()) {
+            Some(platform) => platform.to_string(),
+            None => "none from the extension".to_string(),
+        };
+        let quirks = match Platform::of(chip8.quirks) {
+            Some(platform) => format!("{platform}'s"),
+            None if chip8.quirks == Quirks::default() => "the defaults".to_string(),
+            None => chip8.quirks.names().collect::<Vec<_>>().join(", "),
+        };
+        let rows = [
+            ("Path", rom.path().display().to_string()),
+            ("Size", format!("{} bytes", rom.contents.len())),
+            ("SHA-1", rom.sha1()),
+            ("Platform", detected),
+            ("Quirks", quirks),
+            ("Loaded at", format!("{:#05x}", chip8.start_address)),
+        ];
+        let lines: Vec<Line> = rows
+            .into_iter()
+            .map(|(name, value)| Line::from(vec![format!("{name:<10}").dim(), value.into()]))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(rom.name())),
+            area,
+        );
+    }
+
+    /// The watch expressions and their values, those that just changed in yellow
+    fn render_watches(&self, area: Rect, frame: &mut Frame) {
+        if area.is_empty() {
+            return;
+        }
+        let lines: Vec<Line> = self
+            .tab()
+            .watches
+            .iter()
+            .enumerate()
+            .map(|(i, watch)| {
+                let value = watch.value;
+                let line = Line::from(format!("{i:>2} {}  {value:#x} ({value})", watch.expression));
+                match watch.changed_at {
+                    Some(tick) if self.tick_count - tick < WATCH_FLASH_TICKS => line.yellow(),
+                    _ => line,
+                }
+            })
+            .collect();
+        let block = Block::bordered().title("Watch, :undisplay n to remove");
+        frame.render_widget(List::new(lines).block(block), area);
+    }
+
+    /// The newest log entries, or older ones scrolled back to
+    fn render_log(&self, area: Rect, frame: &mut Frame) {
+        if area.is_empty() {
+            return;
+        }
+        let entries = self.log.entries(Level::Debug);
+        let end = entries.len() - self.log_scroll.min(entries.len());
+        let start = end.saturating_sub(area.height.saturating_sub(2) as usize);
+        let lines: Vec<Line> = entries[start..end]
+            .iter()
+            .map(|entry| {
+                let line = Line::from(entry.to_string());
+                match entry.level {
+                    Level::Error => line.red(),
+                    Level::Warn => line.yellow(),
+                    Level::Info => line,
+                    Level::Debug => line.dim(),
+                }
+            })
+            .collect();
+        let title = match self.log_scroll {
+            0 => format!("Log {}, [ and ] to scroll", entries.len()),
+            back => format!("Log {}, {back} back", entries.len()),
+        };
+        frame.render_widget(List::new(lines).block(Block::bordered().title(title)), area);
+    }
+
+    /// Return addresses of the calls in progress, the current one on top
+    fn render_call_stack(&self, area: Rect, frame: &mut Frame) {
+        let stack = debugger::call_stack(&self.tab().chip8);
+        let block = Block::bordered().title(format!("Stack {}", stack.len()));
+        let lines: Vec<Line> = match stack.is_empty() {
+            true => vec![Line::from("empty").dim()],
+            false => stack
+                .iter()
+                .enumerate()
+                .map(|(i, call)| {
+                    let call = call.describe(&self.tab().symbols);
+                    let line = Line::from(format!("{:>2} {call}", stack.len() - i));
+                    match i {
+                        0 => line.fg(self.theme.highlight),
+                        _ => line.dim(),
+                    }
+                })
+                .collect(),
+        };
+        frame.render_widget(List::new(lines).block(block), area);
+    }
+
+    /// Sparklines of the registers' recent values, laid out like their bars, when
+    /// there is room below them
+    fn render_register_history(&self, area: Rect, frame: &mut Frame) {
+        if area.height < 5 {
+            return;
+        }
+        let history = &self.tab().register_history;
+        let columns = Layout::horizontal([Constraint::Fill(1); 4]).split(area);
+        let names = (0..16)
+            .map(|x| format!("v{x:X}"))
+            .chain(["dt".into(), "st".into()]);
+        for (n, name) in names.enumerate() {
+            let (column, row) = match n {
+                0..16 => (n / 4, n % 4),
+                _ => (n - 16, 4),
+            };
+            let cell = columns[column];
+            let cell = Rect::new(cell.x, cell.y + row as u16, cell.width, 1);
+            let [label, line] =
+                Layout::horizontal([Constraint::Length(3), Constraint::Min(0)]).areas(cell);
+            // the most recent values that fit
+            let values: Vec<u64> = history.iter().map(|sample| sample[n].into()).collect();
+            let values = &values[values.len().saturating_sub(line.width as usize)..];
+            frame.render_widget(Span::from(name).dim(), label);
+            frame.render_widget(
+                Sparkline::default()
+                    .data(values)
+                    .max(u8::MAX.into())
+                    .style(Style::new().fg(self.theme.registers)),
+                line,
+            );
+        }
+    }
+
+    fn render_registers(&self, area: Rect, frame: &mut Frame) {
+        let outer_block = Block::bordered().title("Registers");
+        let content = outer_block.inner(area);
+        frame.render_widget(outer_block, area);
+        let register_layout = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ]);
+        let [main_reg, misc_reg, _, history] = register_layout.areas(content);
+        self.render_register_history(history, frame);
+
+        // the register being edited stands out
+        let chip8 = &self.tab().chip8;
+        let bar = |target: Target, label: String| {
+            let label = match self.editor.as_ref().map(|editor| editor.cursor) {
+                Some(EditCursor::Register(edited)) if edited == target => {
+                    Line::from(label).black().on_yellow()
+                }
+                _ => Line::from(label),
+            };
+            Bar::default().label(label).value(target.read(chip8).into())
+        };
+        let data: Vec<Bar> = (0..16)
+            .map(|x| bar(Target::V(x), format!("v{x:X}")))
+            .collect();
+
+        let bar_columns = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ]);
+        let bar_areas: [Rect; 4] = bar_columns.areas(main_reg);
+        let _ = &data.chunks(4).zip(bar_areas).for_each(|(f, a)| {
+            frame.render_widget(
+                BarChart::default()
+                    .bar_gap(0)
+                    .bar_width(1)
+                    .bar_style(Style::new().fg(self.theme.registers))
+                    .value_style(Style::new().black().bg(self.theme.registers))
+                    .data(BarGroup::default().bars(f))
+                    .max(255)
+                    .direction(Direction::Horizontal),
+                a,
+            );
+        });
+        let bar_columns = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ]);
+        //frame.render_
+        let bar_areas: [Rect; 3] = bar_columns.areas(misc_reg);
+        let _ = &[
+            bar(Target::Delay, "delay".into()),
+            bar(Target::Sound, "sound".into()),
+            bar(Target::I, "i".into()),
+        ]
+        .into_iter()
+        .zip(bar_areas)
+        .for_each(|(f, a)| {
+            frame.render_widget(
+                BarChart::default()
+                    .bar_gap(0)
+                    .bar_width(1)
+                    .bar_style(Style::new().fg(self.theme.timers))
+                    .value_style(Style::new().black().bg(self.theme.timers))
+                    .data(BarGroup::default().bars(&[f]))
+                    .max(2000)
+                    .direction(Direction::Horizontal),
+                a,
+            );
+        });
+    }
+
+    /// The border of the display, with what the tab is doing
+    fn display_block(&self) -> Block<'_> {
+        Block::bordered()
+            .title(self.tab().chip8.rom.name())
+            .title(self.tab().activity())
+            .title(
+                Line::from(self.tab().recording_label())
+                    .red()
+                    .right_aligned(),
+            )
+            .title_bottom(self.tab().status.clone().unwrap_or_default())
+            .title_bottom(Line::from(self.tab().slot_label()).centered())
+            .title_bottom(Line::from(self.tab().speed_label()).right_aligned())
+    }
+
+    fn display(&self) -> impl Widget + '_ {
+        Canvas::default()
+            .block(self.display_block())
+            .marker(self.marker.marker(self.tab().chip8.height()))
+            .background_color(self.theme.background)
+            .paint(|ctx| match self.tab().glow() {
+                Some(phosphor) => ctx.draw(&Glow {
+                    phosphor,
+                    foreground: self.theme.foreground,
+                    background: self.theme.background,
+                }),
+                None => ctx.draw(&Pixels {
+                    chip8: &self.tab().chip8,
+                    color: self.theme.foreground,
+                }),
+            })
+    }
+}
+
+fn style_instruction<'a>(
+    pc: usize,
+    addr: usize,
+    breakpoint: bool,
+    [b1, b2]: [u8; 2],
+    mnemonic: String,
+    highlight: Color,
+) -> Line<'a> {
+    let gutter = match breakpoint {
+        true => Span::from("●").red(),
+        false => Span::from(" "),
+    };
+    let arrow = match addr == pc {
+        true => Span::from("▶ ").fg(highlight),
+        false => Span::from("  "),
+    };
+    let line_count = Span::from(format!("{addr:#05x}  ")).dim();
+    let bytes = Span::from(format!("{b1:02x} {b2:02x}  ")).dim();
+
+    let instruction = Span::from(mnemonic);
+    let (line_count, instruction) = match addr.cmp(&pc) {
+        Ordering::Less => (line_count, instruction.dim()),
+        Ordering::Equal => (line_count.fg(highlight), instruction.fg(highlight).bold()),
+        Ordering::Greater => (line_count, instruction),
+    };
+    Line::from(vec![gutter, arrow, line_count, bytes, instruction])
+}
@@ -0,0 +1,227 @@
+//! The display as a raster image, for terminals that can show one
+//!
+//! Sixel and the kitty graphics protocol put real pixels on the screen where
+//! the cells of the display would be, each of the display's pixels a whole
+//! number of the terminal's across so they stay crisp. Terminals that can do
+//! neither get the half blocks and braille of the canvas.
+use std::{
+    fmt::Write as _,
+    io::{self, Error},
+};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::{chip8::Chip8, phosphor::Phosphor, theme};
+
+/// How the terminal frontend shows the display, named as on the command line
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum Graphics {
+    /// whichever of the others the terminal says it supports
+    #[default]
+    Auto,
+    Kitty,
+    Sixel,
+    /// the canvas, in the terminal's cells
+    Cells,
+}
+
+impl Graphics {
+    /// `auto` as what the terminal supports, going by the environment variables
+    /// `var` reads. The others stay as they are.
+    pub fn detect(self, var: impl Fn(&str) -> Option<String>) -> Graphics {
+        if self != Graphics::Auto {
+            return self;
+        }
+        let term = var("TERM").unwrap_or_default();
+        let program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || matches!(program.as_str(), "WezTerm" | "ghostty")
+        {
+            Graphics::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.starts_with("contour")
+        {
+            Graphics::Sixel
+        } else {
+            Graphics::Cells
+        }
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+/// The display as pixels of a few colors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raster {
+    width: usize,
+    height: usize,
+    /// the background first, then brighter up to the foreground
+    palette: Vec<Rgb>,
+    /// row by row, indices into the palette
+    pixels: Vec<u8>,
+}
+
+impl Raster {
+    /// The display of `chip8` in `foreground` on `background`, fading through
+    /// the colors between as `phosphor` has pixels glowing if given one
+    pub fn new(
+        chip8: &Chip8,
+        phosphor: Option<&Phosphor>,
+        foreground: Color,
+        background: Color,
+    ) -> Self {
+        let (width, height) = (chip8.width(), chip8.height());
+        // a phosphor left over from another resolution has nothing to say
+        let phosphor = phosphor.filter(|p| (p.width(), p.height()) == (width, height));
+        let levels = phosphor.map_or(1, Phosphor::frames);
+        let palette = (0..=levels)
+            .map(|level| {
+                let color =
+                    theme::blend(background, foreground, f32::from(level) / f32::from(levels));
+                theme::rgb(color).unwrap_or_default()
+            })
+            .collect();
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| match phosphor {
+                Some(phosphor) => (phosphor.brightness(x, y) * f32::from(levels)).round() as u8,
+                None => u8::from(chip8.pixel(x, y)),
+            })
+            .collect();
+        Raster {
+            width,
+            height,
+            palette,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The palette index of the pixel at `x`, `y` of the picture `scale` times over
+    fn at(&self, x: usize, y: usize, scale: usize) -> u8 {
+        self.pixels[y / scale * self.width + x / scale]
+    }
+
+    /// Sixel drawing the picture `scale` times over from the cursor
+    pub fn sixel(&self, scale: usize) -> String {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut out = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+        for (i, (r, g, b)) in self.palette.iter().enumerate() {
+            let percent = |c: &u8| u32::from(*c) * 100 / 255;
+            let _ = write!(out, "#{i};2;{};{};{}", percent(r), percent(g), percent(b));
+        }
+        // six rows a band, each color of it drawn over the band in turn
+        for top in (0..height).step_by(6) {
+            let rows = top..(top + 6).min(height);
+            for color in 0..self.palette.len() as u8 {
+                let sixels: Vec<u8> = (0..width)
+                    .map(|x| {
+                        rows.clone()
+                            .filter(|&y| self.at(x, y, scale) == color)
+                            .fold(0, |bits, y| bits | 1 << (y - top))
+                    })
+                    .collect();
+                if sixels.iter().all(|&bits| bits == 0) {
+                    continue;
+                }
+                let _ = write!(out, "#{color}");
+                for run in sixels.chunk_by(|a, b| a == b) {
+                    let c = char::from(0x3F + run[0]);
+                    match run.len() {
+                        1..=3 => out.extend(std::iter::repeat_n(c, run.len())),
+                        len => {
+                            let _ = write!(out, "!{len}{c}");
+                        }
+                    }
+                }
+                out.push('$');
+            }
+            out.push('-');
+        }
+        out.push_str("\x1b\\");
+        out
+    }
+
+    /// Kitty graphics protocol commands showing the picture `scale` times over
+    /// at the cursor, in place of the one they showed before
+    pub fn kitty(&self, scale: usize) -> Result<String, Error> {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let (r, g, b) = self.palette[self.at(x, y, scale) as usize];
+                [r, g, b]
+            })
+            .collect();
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(&data).map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)?;
+
+        // sent in chunks of at most 4096 bytes of base64
+        let encoded = base64(&png);
+        let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+        let mut out = String::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let more = u8::from(i + 1 < chunks.len());
+            let chunk = std::str::from_utf8(chunk).map_err(io::Error::other)?;
+            // what to do with it goes with the first
+            let keys = match i {
+                0 => "a=T,f=100,i=1,p=1,q=2,C=1,",
+                _ => "",
+            };
+            let _ = write!(out, "\x1b_G{keys}m={more};{chunk}\x1b\\");
+        }
+        Ok(out)
+    }
+}
+
+/// The kitty graphics protocol command taking away the picture shown
+pub const KITTY_DELETE: &str = "\x1b_Ga=d,d=i,i=1,q=2\x1b\\";
+
+/// The largest whole number of times a `width` by `height` picture fits in
+/// `columns` by `rows` cells of `cell` pixels each, at least once
+pub fn scale(width: usize, height: usize, columns: u16, rows: u16, cell: (u16, u16)) -> usize {
+    let across = usize::from(columns) * usize::from(cell.0) / width.max(1);
+    let down = usize::from(rows) * usize::from(cell.1) / height.max(1);
+    across.min(down).max(1)
+}
+
+/// Standard base64, padded
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(char::from(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize])),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,173 @@
+//! Input macros, timed sequences of keypad presses bound to a single terminal key
+//!
+//! A sequence is a comma separated list of steps, keys are in hex:
+//!
+//! - `tap 5 x3` presses and releases key 5 three times (`x3` is optional)
+//! - `hold 6 10` holds key 6 for 10 frames
+//! - `wait 4` leaves the keypad alone for 4 frames
+//!
+//! Macros can also be recorded from the keypad while a rom runs, one for each
+//! rom, kept by its name in `~/.config/chipy8/macros.toml`.
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How long each half of a tap lasts
+const TAP_FRAMES: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct InputMacro {
+    /// the key held, if any, and for how many frames
+    steps: Vec<(Option<u8>, u32)>,
+}
+
+impl InputMacro {
+    /// The macro holding the key of each frame in turn, as recorded
+    pub fn from_frames(frames: impl IntoIterator<Item = Option<u8>>) -> Self {
+        let mut steps: Vec<(Option<u8>, u32)> = Vec::new();
+        for key in frames {
+            match steps.last_mut() {
+                Some((last, count)) if *last == key => *count += 1,
+                _ => steps.push((key, 1)),
+            }
+        }
+        Self { steps }
+    }
+
+    /// Frames the macro lasts
+    pub fn len(&self) -> u32 {
+        self.steps.iter().map(|(_, frames)| frames).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The key held on each frame of the macro, `None` when no key is held
+    pub fn frames(&self) -> impl Iterator<Item = Option<u8>> + '_ {
+        self.steps
+            .iter()
+            .flat_map(|(key, frames)| std::iter::repeat_n(*key, *frames as usize))
+    }
+}
+
+impl FromStr for InputMacro {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let key = |k: Option<&str>| {
+            k.and_then(|k| u8::from_str_radix(k, 16).ok())
+                .filter(|k| *k < 16)
+                .ok_or_else(|| format!("expected a key 0..F in `{text}`"))
+        };
+        let count = |n: Option<&str>| {
+            n.and_then(|n| n.parse::<u32>().ok())
+                .ok_or_else(|| format!("expected a frame count in `{text}`"))
+        };
+
+        let mut steps = Vec::new();
+        for step in text.split(',') {
+            let mut words = step.split_whitespace();
+            match words.next() {
+                Some("tap") => {
+                    let k = key(words.next())?;
+                    let times = match words.next() {
+                        Some(times) => count(times.strip_prefix('x'))?,
+                        None => 1,
+                    };
+                    for _ in 0..times {
+                        steps.push((Some(k), TAP_FRAMES));
+                        steps.push((None, TAP_FRAMES));
+                    }
+                }
+                Some("hold") => steps.push((Some(key(words.next())?), count(words.next())?)),
+                Some("wait") => steps.push((None, count(words.next())?)),
+                _ => return Err(format!("unknown macro step `{}`", step.trim())),
+            }
+            if let Some(extra) = words.next() {
+                return Err(format!("unexpected `{extra}` in `{}`", step.trim()));
+            }
+        }
+        Ok(Self { steps })
+    }
+}
+
+/// As `hold` and `wait` steps, which parse back to the same macro
+impl fmt::Display for InputMacro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, frames)) in self.steps.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match key {
+                Some(key) => write!(f, "hold {key:x} {frames}")?,
+                None => write!(f, "wait {frames}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<InputMacro> for String {
+    fn from(input_macro: InputMacro) -> Self {
+        input_macro.to_string()
+    }
+}
+
+impl TryFrom<String> for InputMacro {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+/// The macros recorded in the terminal frontend, one for each rom
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordedMacros {
+    /// by the rom's name
+    pub roms: BTreeMap<String, InputMacro>,
+}
+
+impl RecordedMacros {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("chipy8").join("macros.toml"))
+    }
+
+    /// Load the macros at `path`, or the default location when none is given.
+    /// There are none until one has been recorded.
+    pub fn load(path: Option<&Path>) -> Result<Self, Error> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(Self::default_path) else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        toml::from_str(text).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: Option<&Path>) -> Result<(), Error> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(Self::default_path)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+}
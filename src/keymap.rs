@@ -0,0 +1,111 @@
+//! Which keyboard keys press the keypad
+//!
+//! The keypad's sixteen keys are laid on a 4x4 block of the keyboard, 0 to F
+//! left to right and top to bottom: `1234`, `qwer`, `asdf` and `zxcv` on a
+//! QWERTY keyboard. The presets put the same block under other layouts, or the
+//! sixteen characters can be given in order, `"1234qwerasdfzxcv"`. The
+//! terminal frontend's own keys, [`FRONTEND_KEYS`], go before the keypad.
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// The characters the terminal frontend binds to its own commands, which it
+/// takes whatever the keymap says. No preset uses them.
+pub const FRONTEND_KEYS: &str = " ?:+=-.[]@bghiklmnoptuyFHJKLMNPQRSTVYZ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Keymap {
+    /// the character typed for each keypad key
+    keys: [char; 16],
+}
+
+impl Keymap {
+    pub const QWERTY: Keymap = Keymap {
+        keys: [
+            '1', '2', '3', '4', 'q', 'w', 'e', 'r', 'a', 's', 'd', 'f', 'z', 'x', 'c', 'v',
+        ],
+    };
+    /// The digits are shifted on AZERTY, the block's top row is `&é"'`
+    pub const AZERTY: Keymap = Keymap {
+        keys: [
+            '&', 'é', '"', '\'', 'a', 'z', 'e', 'r', 'q', 's', 'd', 'f', 'w', 'x', 'c', 'v',
+        ],
+    };
+    /// Under the block are `.`, `p`, `o`, `u` and `k`, which step and mark in
+    /// the debugger, so each row is the first four keys of its Dvorak row the
+    /// frontend leaves free
+    pub const DVORAK: Keymap = Keymap {
+        keys: [
+            '1', '2', '3', '4', '\'', ',', 'f', 'c', 'a', 'e', 'd', 's', ';', 'q', 'j', 'x',
+        ],
+    };
+
+    pub const PRESETS: [(&'static str, Keymap); 3] = [
+        ("qwerty", Keymap::QWERTY),
+        ("azerty", Keymap::AZERTY),
+        ("dvorak", Keymap::DVORAK),
+    ];
+
+    /// The keypad key typing `c` presses
+    pub fn key(&self, c: char) -> Option<u8> {
+        self.keys.iter().position(|&k| k == c).map(|key| key as u8)
+    }
+
+    /// The character that presses keypad `key`
+    pub fn char(&self, key: u8) -> char {
+        self.keys[key as usize & 0xF]
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::QWERTY
+    }
+}
+
+/// The preset's name, or the sixteen characters
+impl fmt::Display for Keymap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Keymap::PRESETS.iter().find(|(_, keymap)| keymap == self) {
+            Some((name, _)) => f.write_str(name),
+            None => self.keys.iter().try_for_each(|c| write!(f, "{c}")),
+        }
+    }
+}
+
+/// `qwerty`, `azerty` or `dvorak`, or sixteen different characters for keys 0 to F
+impl FromStr for Keymap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, keymap)) = Keymap::PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        {
+            return Ok(*keymap);
+        }
+        let chars: Vec<char> = s.chars().collect();
+        let keys: [char; 16] = chars
+            .try_into()
+            .map_err(|_| format!("`{s}` is neither qwerty, azerty, dvorak nor 16 characters"))?;
+        if let Some(at) = (1..16).find(|&at| keys[..at].contains(&keys[at])) {
+            return Err(format!("`{}` is in `{s}` twice", keys[at]));
+        }
+        Ok(Keymap { keys })
+    }
+}
+
+impl From<Keymap> for String {
+    fn from(keymap: Keymap) -> Self {
+        keymap.to_string()
+    }
+}
+
+impl TryFrom<String> for Keymap {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
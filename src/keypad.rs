@@ -0,0 +1,61 @@
+//! What the keypad has been doing
+//!
+//! How many times each key was pressed, for a heatmap of the keys a rom uses,
+//! and how long ago each last went down or came up, for the keypad panel to
+//! flash a key as it's pressed and fade it out as it's let go.
+
+/// Frames of 60Hz a key flashes for when pressed, and fades over when let go
+pub const PULSE_FRAMES: u8 = 8;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeypadActivity {
+    /// bit k set while key k is held, as last seen
+    keys: u16,
+    presses: [u32; 16],
+    /// frames left of each key's flash or fade
+    pulse: [u8; 16],
+}
+
+impl KeypadActivity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take in the keys held, a bit each, as they are `frames` frames on
+    pub fn update(&mut self, keys: u16, frames: u32) {
+        let fade = frames.min(u8::MAX.into()) as u8;
+        for key in 0..16 {
+            let (was, is) = (self.keys >> key & 1 == 1, keys >> key & 1 == 1);
+            self.pulse[key] = match (was, is) {
+                (false, true) => {
+                    self.presses[key] += 1;
+                    PULSE_FRAMES
+                }
+                (true, false) => PULSE_FRAMES,
+                _ => self.pulse[key].saturating_sub(fade),
+            };
+        }
+        self.keys = keys;
+    }
+
+    /// Whether `key` is held, as last seen
+    pub fn is_held(&self, key: u8) -> bool {
+        self.keys >> (key & 0xF) & 1 == 1
+    }
+
+    /// Times `key` went down
+    pub fn presses(&self, key: u8) -> u32 {
+        self.presses[key as usize & 0xF]
+    }
+
+    /// Presses of the key pressed most, 0 before any
+    pub fn most_presses(&self) -> u32 {
+        self.presses.iter().copied().max().unwrap_or(0)
+    }
+
+    /// How much is left of `key`'s flash, or its fade once let go, from 1 just
+    /// pressed or let go to 0 settled
+    pub fn pulse(&self, key: u8) -> f32 {
+        f32::from(self.pulse[key as usize & 0xF]) / f32::from(PULSE_FRAMES)
+    }
+}
@@ -1,27 +1,61 @@
-use chip8::{Chip8, WIDTH_PIX};
-use ratatui::{style::Color, widgets::canvas::Shape};
+//! A CHIP-8 interpreter, with terminal, iced and native frontends
+//!
+//! Without the default `std` feature only [`chip8`] and [`rom`] are built, as
+//! `no_std` with `alloc`, to embed the interpreter where there's no operating
+//! system, a microcontroller driving an LED matrix say.
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod asm;
 pub mod chip8;
+#[cfg(feature = "std")]
 pub mod cli;
+#[cfg(feature = "std")]
+pub mod compare;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod debugger;
+#[cfg(feature = "std")]
+pub mod determinism;
+#[cfg(feature = "std")]
+pub mod disasm;
+#[cfg(feature = "std")]
+pub mod graphics;
+#[cfg(feature = "std")]
+pub mod input_macro;
+#[cfg(feature = "std")]
+pub mod keymap;
+#[cfg(feature = "std")]
+pub mod keypad;
+#[cfg(feature = "std")]
+pub mod library;
+#[cfg(feature = "std")]
+pub mod log;
+#[cfg(feature = "std")]
+pub mod phosphor;
+#[cfg(feature = "std")]
+pub mod recording;
+#[cfg(feature = "std")]
+pub mod reference;
+#[cfg(feature = "std")]
+pub mod rewind;
 pub mod rom;
+#[cfg(feature = "std")]
+pub mod screen;
+#[cfg(feature = "std")]
+pub mod screenshot;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod sound;
+#[cfg(feature = "std")]
+pub mod symbols;
+#[cfg(feature = "std")]
+pub mod theme;
+#[cfg(feature = "std")]
+pub mod timing;
+#[cfg(feature = "std")]
 pub mod widget;
-
-impl Shape for Chip8 {
-    fn draw(&self, painter: &mut ratatui::widgets::canvas::Painter) {
-        let pixel_string = &self
-            .display
-            .iter()
-            .map(|r| format!("{:08b}", r))
-            .collect::<Vec<_>>()
-            .join("");
-        pixel_string
-            .chars()
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, p)| match p {
-                '1' => painter.paint(i % WIDTH_PIX, i / WIDTH_PIX, Color::White),
-                '0' => (), //painter.paint(i % WIDTH_PIX, i / WIDTH_PIX, Color::bg(self)),
-                _ => panic!("unexpected display value"),
-            });
-    }
-}
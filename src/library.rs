@@ -0,0 +1,180 @@
+//! The rom library
+//!
+//! The roms in a directory, `library` in the config file or `--library`, and
+//! what was last chosen for each: its speed, platform and keymap, so it starts
+//! the same way next time, and when it was last played. That is kept as TOML in
+//! the platform data directory, e.g. `~/.local/share/chipy8/library.toml`, by
+//! the hash of the rom so it survives the file being renamed or moved.
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{chip8::Platform, keymap::Keymap, rom::Rom};
+
+/// Extensions of the files a library scan picks up
+pub const ROM_EXTENSIONS: [&str; 5] = ["ch8", "c8", "c8h", "sc8", "xo8"];
+
+/// What is remembered of one rom
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RomSettings {
+    /// instructions per second
+    pub speed: Option<u32>,
+    pub platform: Option<Platform>,
+    pub keymap: Option<Keymap>,
+    /// seconds since the Unix epoch
+    pub last_played: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Library {
+    /// by the rom's hash, in hex
+    pub roms: BTreeMap<String, RomSettings>,
+}
+
+/// A rom found by [`scan`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryRom {
+    pub path: PathBuf,
+    /// in bytes
+    pub size: u64,
+    /// [`Rom::hash`] of its contents
+    pub hash: u64,
+}
+
+impl LibraryRom {
+    /// The file name without the extension
+    pub fn name(&self) -> &str {
+        self.path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+    }
+
+    /// The platform the file's extension suggests
+    pub fn platform(&self) -> Option<Platform> {
+        platform_of(&self.path)
+    }
+}
+
+impl Library {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("chipy8").join("library.toml"))
+    }
+
+    /// Load the library at `path`, or the default location when none is given.
+    /// Nothing is remembered until it has been saved once.
+    pub fn load(path: Option<&Path>) -> Result<Self, Error> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(Self::default_path) else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        toml::from_str(text).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: Option<&Path>) -> Result<(), Error> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(Self::default_path)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// What is remembered of the rom with `hash`, nothing if it's new
+    pub fn settings(&self, hash: u64) -> RomSettings {
+        self.roms.get(&key(hash)).cloned().unwrap_or_default()
+    }
+
+    pub fn settings_mut(&mut self, hash: u64) -> &mut RomSettings {
+        self.roms.entry(key(hash)).or_default()
+    }
+
+    /// Remember `rom`, found by a scan, on the platform its extension suggests
+    /// unless another was chosen for it before
+    pub fn add(&mut self, rom: &LibraryRom) {
+        if let Some(platform) = rom.platform() {
+            self.settings_mut(rom.hash).platform.get_or_insert(platform);
+        }
+    }
+}
+
+fn key(hash: u64) -> String {
+    format!("{hash:016x}")
+}
+
+/// The roms in `dir` by name, files with one of the [`ROM_EXTENSIONS`]
+pub fn scan(dir: &Path) -> Result<Vec<LibraryRom>, Error> {
+    let mut roms = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_rom = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| ROM_EXTENSIONS.contains(&extension.to_lowercase().as_str()));
+        if !is_rom || !path.is_file() {
+            continue;
+        }
+        let rom = Rom::new(&path)?;
+        roms.push(LibraryRom {
+            size: rom.contents.len() as u64,
+            hash: rom.hash(),
+            path,
+        });
+    }
+    roms.sort_by_key(|rom| rom.name().to_lowercase());
+    Ok(roms)
+}
+
+/// The platform the extension of `path` suggests: `.sc8` for SUPER-CHIP, `.xo8`
+/// for XO-CHIP and `.c8h` for two page hi-res
+pub fn platform_of(path: &Path) -> Option<Platform> {
+    match path.extension()?.to_str()? {
+        "sc8" => Some(Platform::Schip),
+        "xo8" => Some(Platform::Xochip),
+        "c8h" => Some(Platform::HiresChip8),
+        _ => None,
+    }
+}
+
+/// Seconds since the Unix epoch, for [`RomSettings::last_played`]
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// How long before `now` the time `then` was, roughly: `just now`, `5 minutes
+/// ago`, `3 days ago`
+pub fn ago(then: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(then);
+    let (count, unit) = match seconds {
+        0..60 => return "just now".to_string(),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        86400..2592000 => (seconds / 86400, "day"),
+        2592000..31536000 => (seconds / 2592000, "month"),
+        _ => (seconds / 31536000, "year"),
+    };
+    match count {
+        1 => format!("1 {unit} ago"),
+        _ => format!("{count} {unit}s ago"),
+    }
+}
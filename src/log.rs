@@ -0,0 +1,130 @@
+//! The frontend's log
+//!
+//! Nothing can be printed while the terminal is in raw mode without tearing up
+//! the screen, so pauses, errors, diagnostics and, with `--trace -`, every
+//! instruction executed go here instead, to be shown in a panel. Only the most
+//! recent entries are kept.
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use crate::chip8::{TraceEntry, TraceSink};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub level: Level,
+    pub message: String,
+}
+
+/// `warn  No audio output`
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<5} {}", self.level.to_string(), self.message)
+    }
+}
+
+/// A shared handle on the entries, clones write to the same log
+#[derive(Debug, Clone)]
+pub struct Log {
+    entries: Arc<Mutex<VecDeque<Entry>>>,
+    capacity: usize,
+}
+
+impl Log {
+    /// A log keeping the last `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, level: Level, message: impl Into<String>) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        let message = message.into();
+        entries.push_back(Entry { level, message });
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.push(Level::Error, message)
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        self.push(Level::Warn, message)
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        self.push(Level::Info, message)
+    }
+
+    pub fn debug(&self, message: impl Into<String>) {
+        self.push(Level::Debug, message)
+    }
+
+    /// The entries at `level` or more severe, oldest first
+    pub fn entries(&self, level: Level) -> Vec<Entry> {
+        self.entries.lock().map_or_else(
+            |_| Vec::new(),
+            |entries| {
+                entries
+                    .iter()
+                    .filter(|entry| entry.level <= level)
+                    .cloned()
+                    .collect()
+            },
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map_or(0, |entries| entries.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+/// Each instruction traced is a debug entry
+impl TraceSink for Log {
+    fn record(&mut self, entry: &TraceEntry) {
+        self.debug(entry.to_string())
+    }
+}
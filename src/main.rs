@@ -1,46 +1,600 @@
+use chipy8::config::Config;
+use chipy8::determinism::InputScript;
+use chipy8::graphics::{self, Graphics, Raster};
+use chipy8::input_macro::{InputMacro, RecordedMacros};
+use chipy8::keymap::{self, Keymap};
+use chipy8::keypad::KeypadActivity;
+use chipy8::library::{self, Library, LibraryRom};
+use chipy8::log::{Level, Log};
+use chipy8::phosphor::Phosphor;
+use chipy8::recording::Recording;
+use chipy8::rewind::History;
 use chipy8::rom::Rom;
-use chipy8::widget::HexInput;
-use chipy8::{chip8::Chip8, cli::Cli};
+use chipy8::screen::{self, KeyAction, Screen, Screens};
+use chipy8::session::{Breakpoint, Panels, Session, TabSession};
+use chipy8::sound::{Beeper, Bell};
+use chipy8::symbols::Symbols;
+use chipy8::theme::Theme;
+use chipy8::timing::{Clock, FrameTiming, Rate};
+use chipy8::widget::{
+    Glow, HexInput, MemoryView, PixelMarker, Pixels, ScaledDisplay, SpritePreview, Timeline,
+};
+use chipy8::{
+    asm,
+    chip8::{
+        Chip8, Chip8Error, Instruction, Platform, Profile, Quirks, State, StepOutcome, TimerSource,
+        Tracer, HEIGHT_BYTE, VIP_CYCLES_PER_FRAME, VIP_CYCLES_PER_SECOND, WIDTH_BYTE,
+    },
+    cli::{Cli, Commands, DEFAULT_SPEED},
+    compare,
+    debugger::{self, DebugCommand, EditCursor, Editor, StateDiff, Target, WatchExpression},
+    determinism, disasm, reference, screenshot,
+};
 use clap::Parser;
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyboardEnhancementFlags, MouseButton, MouseEvent, MouseEventKind, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
 use ratatui::{
     prelude::*,
-    widgets::{canvas::Canvas, BarChart, Block, List, Paragraph},
+    widgets::{
+        canvas::Canvas, Bar, BarChart, BarGroup, Block, Clear, List, Paragraph, Sparkline, Tabs,
+    },
     DefaultTerminal,
 };
 
+use std::env;
 use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::panic;
+use std::sync::atomic::{self, AtomicU32};
 use std::{
+    cell::{Cell, RefCell},
     cmp::Ordering,
-    path::PathBuf,
+    collections::VecDeque,
+    path::{Path, PathBuf},
     time::{Duration, Instant},
 };
-use strum::Display;
-use symbols::Marker;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+mod draw;
 
 fn main() -> Result<(), Box<dyn Error>> {
     //// Setup
 
     let cli = Cli::parse();
+    if let Some(command) = &cli.command {
+        return run_command(command);
+    }
+    let session = match cli.rom_paths.is_empty() {
+        true => Some(offer_session()?),
+        false => None,
+    };
+
+    let mut app = App::new(&cli, session)?;
 
     let mut terminal = ratatui::init();
+    // the kitty keyboard protocol reports key releases, where the terminal has it
+    let key_releases = matches!(
+        crossterm::terminal::supports_keyboard_enhancement(),
+        Ok(true)
+    );
+    if key_releases {
+        crossterm::execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+            )
+        )?;
+        app.use_key_releases();
+    }
 
-    let app = App::new(cli.rom_path, cli.paused);
+    // clicks on the keypad and the program, the wheel over memory
+    crossterm::execute!(io::stdout(), EnableMouseCapture)?;
+    set_panic_hook(key_releases);
 
     // Clean the slate
     terminal.clear()?;
     //// Start!
-    let app_result = app.run(terminal);
+    let app_result = app.run(terminal, &cli);
 
     //// Cleanup
-    ratatui::restore();
+    restore_terminal(key_releases);
+    for tab in &app.tabs {
+        if let Some(path) = tab
+            .chip8
+            .default_flags_path()
+            .filter(|_| !cli.deterministic)
+        {
+            if let Err(e) = tab.chip8.save_flags(&path) {
+                eprintln!("Could not save the flags of {}: {e}", tab.chip8.rom.name());
+            }
+        }
+        if let Some(profile) = tab.chip8.profile() {
+            println!("{}\n{profile}\n", tab.chip8.rom.name());
+        }
+    }
+    if let Err(e) = app.session(&cli).save(None) {
+        eprintln!("Could not save the session: {e}");
+    }
+    if !cli.deterministic {
+        app.remember(&cli);
+        if let Err(e) = app.library.save(None) {
+            eprintln!("Could not save the library: {e}");
+        }
+    }
     app_result
 }
 
+/// When the state in `slot` of `chip8`'s rom was saved, None if it never was
+fn saved_at(chip8: &Chip8, slot: u8) -> Option<u64> {
+    let modified = fs::metadata(chip8.state_slot_path(slot)?)
+        .ok()?
+        .modified()
+        .ok()?;
+    let since = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(since.as_secs())
+}
+
+/// The rom at `path` loaded at `start_address` and run without a display for up
+/// to `steps` instructions, fewer if it ends, waits for a key or fails
+fn run_headless(
+    path: &Path,
+    platform: Option<Platform>,
+    start_address: u16,
+    steps: u64,
+) -> Result<Chip8, Box<dyn Error>> {
+    let mut builder = Chip8::builder(Rom::new(path)?)
+        .start_address(start_address)
+        .speed(700);
+    if let Some(platform) = platform {
+        builder = builder.platform(platform);
+    }
+    let mut chip8 = builder.build()?;
+    for _ in 0..steps {
+        if chip8.is_spinning() || chip8.is_waiting_for_key() {
+            break;
+        }
+        if let Err(e) = chip8.step() {
+            eprintln!("{e}");
+            break;
+        }
+    }
+    Ok(chip8)
+}
+
+/// Run a subcommand instead of the emulator
+fn run_command(command: &Commands) -> Result<(), Box<dyn Error>> {
+    match command {
+        Commands::Disasm { rom } => {
+            let listing = disasm::disassemble_rom(&Rom::new(rom)?);
+            write!(io::stdout(), "{listing}")?;
+        }
+        Commands::Asm { source, output } => {
+            let rom = asm::assemble(&fs::read_to_string(source)?)?;
+            let output = output
+                .clone()
+                .unwrap_or_else(|| source.with_extension("ch8"));
+            fs::write(output, rom)?;
+        }
+        Commands::Coverage {
+            rom,
+            steps,
+            start_addr,
+        } => {
+            let chip8 = run_headless(rom, None, *start_addr, *steps)?;
+            let start = chip8.start_address;
+            let end = start + chip8.rom.contents.len() as u16;
+            let coverage = chip8.coverage();
+            for (range, usage) in coverage.regions(start..end) {
+                let len = range.len();
+                let hottest = range.clone().map(|address| coverage.runs(address)).max();
+                let runs = match hottest {
+                    Some(runs @ 1..) => format!(", run up to {runs} times"),
+                    _ => String::new(),
+                };
+                println!(
+                    "{:#05x}..{:#05x}  {usage} ({len} bytes{runs})",
+                    range.start, range.end
+                );
+            }
+        }
+        Commands::Screenshot {
+            rom,
+            steps,
+            platform,
+            scale,
+            output,
+            start_addr,
+        } => {
+            let chip8 = run_headless(rom, *platform, *start_addr, *steps)?;
+            let output = output
+                .clone()
+                .unwrap_or_else(|| screenshot::file_name(chip8.rom.name(), library::now()).into());
+            screenshot::save(&chip8, *scale, &output)?;
+            println!("Saved {}", output.display());
+        }
+        Commands::Compare {
+            rom,
+            platform,
+            against,
+            reference,
+            steps,
+            input_script,
+            start_addr,
+        } => {
+            let rom = Rom::new(rom)?;
+            let machine = |platform: Option<Platform>| {
+                let builder = Chip8::builder(rom.clone())
+                    .start_address(*start_addr)
+                    .seed(determinism::DEFAULT_SEED);
+                match platform {
+                    Some(platform) => builder.platform(platform).build(),
+                    None => builder.build(),
+                }
+            };
+            let script = match input_script {
+                Some(path) => InputScript::load(path)?,
+                None => InputScript::default(),
+            };
+            let input = |step: usize| script.keys_at(step as u64);
+            let mut first = machine(*platform)?;
+            let divergence = match reference {
+                true => reference::lockstep(&mut first, *steps, input).map_err(|d| d.to_string()),
+                false => {
+                    let mut second = machine(*against)?;
+                    compare::lockstep(&mut first, &mut second, *steps, input)
+                        .map_err(|d| d.to_string())
+                }
+            };
+            match divergence {
+                Ok(()) => println!("No difference in {steps} steps"),
+                Err(divergence) => println!("{divergence}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ask whether to pick up the last session, the only option without a rom to open
+fn offer_session() -> Result<Session, Box<dyn Error>> {
+    let session = Session::load(None)?
+        .filter(|session| !session.tabs.is_empty())
+        .ok_or("no rom given and no previous session to restore")?;
+    let roms: Vec<_> = session
+        .tabs
+        .iter()
+        .filter_map(|tab| tab.rom.file_name()?.to_str())
+        .collect();
+    print!("Restore the last session with {}? [Y/n] ", roms.join(", "));
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    match answer.trim() {
+        "" | "y" | "Y" => Ok(session),
+        _ => Err("no rom given".into()),
+    }
+}
+
+/// Instructions that fell due since the last tick run together every tick
+const TICK_RATE: Duration = Duration::from_millis(4);
+
+/// Instructions that can be stepped back over while paused
+const REWIND_DEPTH: usize = 2048;
+
+/// Instructions a step over or out runs before giving up, a CALL that never
+/// returns would otherwise hang the terminal
+const STEP_OVER_LIMIT: u32 = 1_000_000;
+
+/// How long a watch that changed stands out, half a second of ticks
+const WATCH_FLASH_TICKS: u64 = 125;
+
+/// Rows of the sprite preview until changed, the height of the font's digits
+const DEFAULT_SPRITE_HEIGHT: u8 = 5;
+
+/// The instruction last started, the address above the opcode, for the panic hook
+static LAST_INSTRUCTION: AtomicU32 = AtomicU32::new(NO_INSTRUCTION);
+
+/// [`LAST_INSTRUCTION`] before any has run
+const NO_INSTRUCTION: u32 = u32::MAX;
+
+/// Undo what the terminal was set up with, `key_releases` when the keyboard
+/// enhancement flags were pushed
+fn restore_terminal(key_releases: bool) {
+    if key_releases {
+        crossterm::execute!(io::stdout(), PopKeyboardEnhancementFlags).ok();
+    }
+    crossterm::execute!(io::stdout(), DisableMouseCapture).ok();
+    ratatui::restore();
+}
+
+/// Put the terminal back before a panic's message prints, so it can be read and
+/// the shell still works after, and say which instruction was running
+fn set_panic_hook(key_releases: bool) {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal(key_releases);
+        hook(info);
+        let last = LAST_INSTRUCTION.load(atomic::Ordering::Relaxed);
+        if last != NO_INSTRUCTION {
+            let (address, opcode) = ((last >> 16) as u16, last as u16);
+            let instruction = Instruction::decode(opcode);
+            eprintln!(
+                "The last instruction run was {opcode:04x} `{instruction}` at {address:#05x}"
+            );
+        }
+    }));
+}
+
+/// Frames of register values the sparklines remember, about a second
+const REGISTER_HISTORY: usize = 64;
+
+/// Most lines of changes the diff panel shows
+const DIFF_LINES: u16 = 10;
+
+/// Entries the log panel shows at once
+const LOG_LINES: u16 = 8;
+
+/// Bytes PgUp and PgDn scroll the memory view by
+const MEMORY_PAGE: i32 = 0x100;
+
+/// Instructions PgUp and PgDn scroll the Program panel by
+const PROGRAM_PAGE: i32 = 16;
+
+/// Instructions a turn of the mouse wheel scrolls the Program panel by
+const WHEEL_LINES: i32 = 3;
+
+/// Frames pixels fade out over when the phosphor is turned on without saying
+const PHOSPHOR_FRAMES: u8 = 4;
+
+/// Save state slots, F1 to F4 pick one
+const STATE_SLOTS: u8 = 4;
+
+/// Pixels to the side of each of the display's in a screenshot
+const SCREENSHOT_SCALE: usize = 8;
+
+/// Frames a recording stops at, five minutes
+const RECORDING_LIMIT: u32 = 60 * 60 * 5;
+
+/// How many times as fast turbo runs
+const TURBO: u32 = 8;
+
+/// Instructions a second + and - keep between, one to a thousand a frame
+const MIN_SPEED: u32 = 60;
+const MAX_SPEED: u32 = 60_000;
+
+/// How long to block on input while there is nothing to emulate
+const IDLE_POLL: Duration = Duration::from_millis(500);
+
+/// What the help screen lists, the keys of the emulator and the debugger
+const HELP: &[(&str, &str)] = &[
+    ("space", "run or pause"),
+    ("esc", "quit"),
+    ("?", "these keys"),
+    ("p", "settings"),
+    ("L", "library"),
+    (":", "console"),
+    ("Z", "display alone or debugger"),
+    ("V P N", "registers, program, input"),
+    ("n o u", "step, over, out"),
+    (".", "advance a frame"),
+    ("b", "breakpoint"),
+    ("up down", "program cursor"),
+    ("left right", "rewind"),
+    ("home end", "rewind all the way"),
+    ("k K", "mark, clear the mark"),
+    ("J", "skip a failed instruction"),
+    ("Enter", "edit memory"),
+    ("m M", "memory, heatmap"),
+    ("H", "keypad heatmap"),
+    ("PgUp PgDn", "scroll the memory or program"),
+    ("h", "profile"),
+    ("l [ ]", "log, scroll it"),
+    ("t", "timing"),
+    ("i", "rom info"),
+    ("y Y", "sprite at I, its height"),
+    ("+ -", "speed"),
+    ("T", "turbo"),
+    ("F1-F4", "save slot"),
+    ("F5 F9", "save, load"),
+    ("S", "screenshot"),
+    ("R", "record"),
+    ("Q @", "record a macro, play it"),
+    ("F", "phosphor"),
+    ("g", "pixel marker"),
+    ("backspace", "reset"),
+    ("tab", "next rom"),
+];
+
 struct App {
-    chip8: Chip8,
+    tabs: Vec<Tab>,
+    /// the tab being shown, only it runs
+    active: usize,
     tick_count: u64,
+    /// the emulator or the debugger, and what is open over it
+    screens: Screens,
+    /// the line being typed at the `:` prompt
+    console: Option<String>,
+    /// what the last command line said
+    console_output: Option<String>,
+    config: Config,
+    /// what is remembered of each rom played
+    library: Library,
+    /// the input macro recorded for each rom, Q records and @ plays
+    recorded: RecordedMacros,
+    /// where the library's roms are, from --library or the config file
+    library_dir: Option<PathBuf>,
+    /// the roms found when the library was last opened
+    library_roms: Vec<LibraryRom>,
+    /// colors from --theme or the config file
+    theme: Theme,
+    /// how the display's pixels are drawn, g cycles
+    marker: PixelMarker,
+    /// the trace every tab writes to, with --trace
+    tracer: Option<Tracer>,
+    /// the terminal reports key releases
+    key_releases: bool,
+    timing: FrameTiming,
+    /// frames drawn and instructions run, for the status bar
+    fps: Rate,
+    ips: Rate,
+    show_timing: bool,
+    /// panels the debugger shows around the display, V, P and N toggle them
+    panels: Panels,
+    /// the profile in place of the program, with --profile
+    show_profile: bool,
+    /// the first address of the memory view shown in place of the program, m toggles
+    memory_view: Option<u16>,
+    /// editing memory or registers in place, Enter while paused
+    editor: Option<Editor>,
+    /// color the memory view by how much of it has run, M toggles
+    show_heatmap: bool,
+    /// color the keypad by how often each key was pressed, H toggles
+    show_key_heatmap: bool,
+    /// rows of the sprite at I to preview over the display, y toggles and Y
+    /// changes them
+    sprite_preview: Option<u8>,
+    /// the panel telling which rom file is loaded and how, i toggles
+    show_rom_info: bool,
+    timers: Clock,
+    /// sounds while the active tab's sound timer runs, None when muted
+    beeper: Option<Beeper>,
+    /// rings the terminal bell instead where there's no audio output
+    bell: Option<Bell>,
+    log: Log,
+    /// the log panel under the program, l toggles
+    show_log: bool,
+    /// log entries scrolled back from the newest, [ and ] scroll
+    log_scroll: usize,
+    /// what the last frame drew where, for the mouse
+    hitboxes: RefCell<Hitboxes>,
+    /// the keypad key held down by the mouse
+    mouse_key: Option<u8>,
+    /// kitty or sixel to show the display as an image, cells otherwise
+    graphics: Graphics,
+    /// pixels to a cell of the terminal, while it says and an image can be sized
+    cell_size: Option<(u16, u16)>,
+    /// where the last frame left room for the display's image, when nothing
+    /// covers it
+    image_area: Cell<Option<Rect>>,
+    /// the image shown and where, not sent again while it stays the same
+    shown: Option<(Rect, Raster)>,
+}
+
+/// Where the last frame put what the mouse can click, empty for panels not shown
+#[derive(Default)]
+struct Hitboxes {
+    /// inside the input panel
+    input: Rect,
+    /// inside the program panel
+    program: Rect,
+    /// the address of the instruction on each row of the program panel, None
+    /// for a label
+    program_rows: Vec<Option<u16>>,
+    /// inside the memory view
+    memory: Rect,
+}
+
+/// A loaded rom and its emulator session
+struct Tab {
+    chip8: Chip8,
     mode: Mode,
+    /// in deterministic mode the keypad is driven by this script instead of the keyboard
+    script: Option<InputScript>,
+    /// instructions executed so far, the script's virtual clock
+    steps: u64,
+    /// instructions per second
+    speed: u32,
+    /// the speed the tab opened with, the status bar's multiplier is against it
+    base_speed: u32,
+    /// running everything TURBO times as fast, T toggles
+    turbo: bool,
+    /// when the next instructions are due
+    clock: Clock,
+    /// with --vip-timing the clock counts VIP cycles, those not yet spent
+    vip_cycles: Option<u32>,
+    /// why the tab paused itself, shown under the display
+    status: Option<String>,
+    /// the instruction that stopped the program, shown over the display until resumed
+    error: Option<Chip8Error>,
+    /// pause once the display and keypad have been static for this many frames
+    idle_pause: Option<u32>,
+    /// 60Hz frames since the display or keypad last changed
+    idle_frames: u32,
+    last_display: [u8; WIDTH_BYTE * HEIGHT_BYTE],
+    last_keys: u16,
+    /// debugger options, toggled from the settings, p
+    break_on_draw: bool,
+    break_on_collision: bool,
+    pause_on_sound: bool,
+    break_on_self_modify: bool,
+    /// keys for the keypad, from --keymap or the config file
+    keymap: Keymap,
+    /// each keypad key's presses and how long ago it went down or up
+    keypad: KeypadActivity,
+    /// the display captured since R started recording
+    recording: Option<Recording>,
+    /// how brightly pixels still glow while running, F toggles
+    phosphor: Option<Phosphor>,
+    /// frames pixels fade out over with the phosphor on
+    phosphor_frames: u8,
+    /// the save state slot F5 and F9 use, 1 to STATE_SLOTS
+    slot: u8,
+    /// when each slot was last saved, in seconds since the Unix epoch
+    saved: [Option<u64>; STATE_SLOTS as usize],
+    /// how long a key stays held after the terminal last reported it, None when
+    /// the terminal reports releases
+    key_hold: Option<Duration>,
+    /// when each key held without a release to come lets go
+    releases: [Option<Instant>; 16],
+    /// remaining frames of the input macro being played back
+    playback: Option<std::vec::IntoIter<Option<u8>>>,
+    /// the key held each frame since Q started recording a macro
+    macro_frames: Option<Vec<Option<u8>>>,
+    /// states before the most recent instructions, for stepping back
+    history: History,
+    /// names for addresses in the debugger, from --symbols or the rom's .sym file
+    symbols: Symbols,
+    /// expressions shown in the watch panel
+    watches: Vec<Watch>,
+    /// the instruction selected in the Program panel while paused, `b` toggles a
+    /// breakpoint there
+    program_cursor: Option<u16>,
+    /// instructions the Program panel is scrolled away from the cursor, or the
+    /// PC without one
+    program_scroll: i32,
+    /// the bytes last searched for with `find` and where they were found
+    search: Option<(Vec<u8>, u16)>,
+    /// a match the memory view should jump to
+    found: Option<u16>,
+    /// V0 to VF, the delay and the sound timer over the last frames, oldest first
+    register_history: VecDeque<[u8; 18]>,
+    /// where in `history` the paused program has been scrubbed back to
+    scrub: Option<Scrub>,
+    /// the state marked with k, the diff panel shows what changed since
+    marked: Option<Box<Chip8>>,
+    /// the app's log, shared by every tab
+    log: Log,
+}
+
+/// A look back through the rewind history, the present kept to come back to
+struct Scrub {
+    /// the state in the history shown, its length for the present
+    index: usize,
+    present: Chip8,
+    /// `steps` in the present
+    steps: u64,
+}
+
+/// An expression in the watch panel and what it was last worth
+struct Watch {
+    expression: WatchExpression,
+    value: u16,
+    /// the app tick it last changed on, it stands out for a while after
+    changed_at: Option<u64>,
 }
 
 #[derive(Clone, Copy, Debug, Display)]
@@ -49,208 +603,1479 @@ enum Mode {
     Paused,
 }
 
-impl App {
-    fn new(path: PathBuf, paused: bool) -> Self {
-        let initial_mode = match paused {
+/// Entries of the settings screen
+#[derive(Clone, Copy, Debug, Display, EnumIter)]
+enum Command {
+    #[strum(to_string = "Break on draw")]
+    BreakOnDraw,
+    #[strum(to_string = "Break on draw collision")]
+    BreakOnCollision,
+    #[strum(to_string = "Pause on sound")]
+    PauseOnSound,
+    #[strum(to_string = "Break on self-modifying code")]
+    BreakOnSelfModify,
+}
+
+impl Tab {
+    /// The rom at `path` set up as the command line asks, and otherwise as it
+    /// was last played
+    fn new(
+        path: &Path,
+        cli: &Cli,
+        config: &Config,
+        library: &Library,
+        log: Log,
+    ) -> Result<Self, Box<dyn Error>> {
+        let initial_mode = match cli.paused {
             true => Mode::Paused,
             false => Mode::Running,
         };
-        let rom = Rom::new(path).unwrap();
-        Self {
-            chip8: Chip8::new(rom),
-            tick_count: 0,
-            mode: initial_mode,
+        let rom = Rom::new(path)?;
+        let settings = library.settings(rom.hash());
+        let keymap = cli.keymap_with(rom.name(), &settings, config);
+        let speed = cli.speed_with(&settings, config);
+        let mut builder = cli.builder_with(rom, &settings, config);
+        if cli.deterministic {
+            builder = builder.speed(speed);
+        }
+        let mut chip8 = builder.build()?;
+        chip8.watchpoints = cli.watchpoints.clone();
+        chip8.set_profiling(cli.profile);
+        // a deterministic run can't depend on what earlier runs left behind
+        if let Some(path) = chip8.default_flags_path().filter(|_| !cli.deterministic) {
+            chip8.load_flags(&path)?;
         }
+        let saved = std::array::from_fn(|slot| saved_at(&chip8, slot as u8 + 1));
+        let phosphor_frames = cli.phosphor.or(config.phosphor);
+        let symbols = match &cli.symbols {
+            Some(path) => Symbols::load(path)?,
+            None => Symbols::beside(path)?,
+        };
+        let mut script = None;
+        if cli.deterministic {
+            script = Some(match &cli.input_script {
+                Some(path) => InputScript::load(path)?,
+                None => InputScript::default(),
+            });
+        }
+        Ok(Self {
+            last_display: chip8.display,
+            last_keys: chip8.keys,
+            chip8,
+            mode: initial_mode,
+            script,
+            steps: 0,
+            speed,
+            base_speed: speed,
+            turbo: false,
+            clock: Clock::new(Instant::now(), speed),
+            vip_cycles: cli.vip_timing.then_some(0),
+            status: None,
+            error: None,
+            idle_pause: cli.idle_pause,
+            idle_frames: 0,
+            break_on_draw: false,
+            break_on_collision: false,
+            pause_on_sound: false,
+            break_on_self_modify: false,
+            keymap,
+            recording: None,
+            phosphor: phosphor_frames.map(Phosphor::new),
+            keypad: KeypadActivity::new(),
+            phosphor_frames: phosphor_frames.unwrap_or(PHOSPHOR_FRAMES),
+            slot: 1,
+            saved,
+            key_hold: Some(Duration::from_millis(cli.key_hold)),
+            releases: [None; 16],
+            playback: None,
+            macro_frames: None,
+            history: History::new(REWIND_DEPTH),
+            symbols,
+            watches: Vec::new(),
+            program_cursor: None,
+            program_scroll: 0,
+            search: None,
+            found: None,
+            register_history: VecDeque::with_capacity(REGISTER_HISTORY),
+            scrub: None,
+            marked: None,
+            log,
+        })
     }
-    fn toggle_mode(mut self) -> Self {
+    fn toggle_mode(&mut self) {
         self.mode = match self.mode {
             Mode::Running => Mode::Paused,
             Mode::Paused => Mode::Running,
         };
-        self
+        self.program_cursor = None;
+        self.program_scroll = 0;
+        self.status = None;
+        self.error = None;
+    }
+
+    fn enabled(&self, command: Command) -> bool {
+        match command {
+            Command::BreakOnDraw => self.break_on_draw,
+            Command::BreakOnCollision => self.break_on_collision,
+            Command::PauseOnSound => self.pause_on_sound,
+            Command::BreakOnSelfModify => self.break_on_self_modify,
+        }
+    }
+
+    fn option(&mut self, command: Command) -> &mut bool {
+        match command {
+            Command::BreakOnDraw => &mut self.break_on_draw,
+            Command::BreakOnCollision => &mut self.break_on_collision,
+            Command::PauseOnSound => &mut self.pause_on_sound,
+            Command::BreakOnSelfModify => &mut self.break_on_self_modify,
+        }
+    }
+
+    fn pause(&mut self, status: String) {
+        self.mode = Mode::Paused;
+        self.report(status);
+    }
+
+    /// Show `status` under the display and log it
+    fn report(&mut self, status: String) {
+        self.report_at(Level::Info, status);
+    }
+
+    fn report_at(&mut self, level: Level, status: String) {
+        self.log.push(level, status.as_str());
+        self.status = Some(status);
+    }
+
+    /// Hold `key` down, until it's released or, where the terminal doesn't report
+    /// releases, for as long as the key hold lasts after the last press. Holding a
+    /// key in the terminal repeats the press, which keeps it held.
+    fn press(&mut self, key: u8) {
+        self.chip8.key_down(key);
+        self.releases[key as usize] = self.key_hold.map(|hold| Instant::now() + hold);
+    }
+
+    /// Press `key` until [`Tab::release`], however long keys are held for
+    fn hold(&mut self, key: u8) {
+        self.chip8.key_down(key);
+        self.releases[key as usize] = None;
+    }
+
+    fn release(&mut self, key: u8) {
+        self.chip8.key_up(key);
+        self.releases[key as usize] = None;
+    }
+
+    /// Let go of the keys whose hold has run out
+    fn release_held(&mut self, now: Instant) {
+        for key in 0..16 {
+            if self.releases[key as usize].is_some_and(|at| at <= now) {
+                self.release(key);
+            }
+        }
+    }
+
+    /// Blocked in Fx0A until a key goes down.
+    /// Scripted runs never block, the script only advances with the steps.
+    fn is_waiting(&self) -> bool {
+        self.script.is_none() && self.playback.is_none() && self.chip8.is_waiting_for_key()
+    }
+
+    /// What the title shows the tab doing
+    fn activity(&self) -> String {
+        match (self.mode, self.chip8.state) {
+            (_, State::Halted) => "Halted".to_string(),
+            (Mode::Running, State::WaitingForKey { .. }) => "Waiting for key".to_string(),
+            (mode, _) => mode.to_string(),
+        }
+    }
+
+    /// How long the recording has gone on, for the display's title
+    fn recording_label(&self) -> String {
+        self.recording.as_ref().map_or(String::new(), |recording| {
+            format!("● REC {}s", recording.ticks() / 60)
+        })
+    }
+
+    /// Nothing will change until the user acts
+    fn is_idle(&self) -> bool {
+        match self.mode {
+            Mode::Paused => true,
+            Mode::Running => {
+                (self.is_waiting() || self.chip8.is_halted())
+                    && self.chip8.delay == 0
+                    && self.chip8.sound == 0
+            }
+        }
+    }
+
+    /// Apply timer ticks that fell due on the wall clock.
+    /// Scripted runs count them in instructions instead, to stay deterministic.
+    fn tick_timers(&mut self, due: u32) {
+        if let (Mode::Running, None) = (self.mode, &self.script) {
+            let due = match self.turbo {
+                true => due * TURBO,
+                false => due,
+            };
+            // past 255 ticks both timers are at zero anyway
+            for _ in 0..due.min(u8::MAX.into()) {
+                self.chip8.tick_timers();
+            }
+            for _ in 0..due {
+                self.input_frame();
+            }
+        }
+        if let (Mode::Running, 1..) = (self.mode, due) {
+            self.count_idle(due);
+            self.sample_registers();
+            self.capture(due);
+            if let Some(phosphor) = &mut self.phosphor {
+                phosphor.update(&self.chip8, due);
+            }
+        }
+        self.keypad.update(self.chip8.keys, due);
+    }
+
+    /// Count `frames` more with the display and keypad as they were, pausing
+    /// once there have been as many as --idle-pause asks
+    fn count_idle(&mut self, frames: u32) {
+        if self.chip8.display != self.last_display || self.chip8.keys != self.last_keys {
+            self.idle_frames = 0;
+            self.last_display = self.chip8.display;
+            self.last_keys = self.chip8.keys;
+            return;
+        }
+        self.idle_frames = self.idle_frames.saturating_add(frames);
+        if self
+            .idle_pause
+            .is_some_and(|limit| self.idle_frames >= limit)
+        {
+            self.pause(format!("Display unchanged for {} frames", self.idle_frames));
+            self.idle_frames = 0;
+        }
+    }
+
+    /// A frame of input: the next of the macro playing back, and the keypad as it
+    /// is for the macro being recorded
+    fn input_frame(&mut self) {
+        if let Some(playback) = &mut self.playback {
+            match playback.next() {
+                Some(Some(key)) => {
+                    self.chip8.keys = 0;
+                    self.chip8.key_down(key);
+                }
+                Some(None) => self.chip8.keys = 0,
+                None => self.playback = None,
+            }
+        }
+        if let Some(frames) = &mut self.macro_frames {
+            frames.push((0..16).find(|&key| self.chip8.is_key_down(key)));
+        }
+    }
+
+    /// Remember V0 to VF, the delay and the sound timer for the sparklines, once a
+    /// frame
+    fn sample_registers(&mut self) {
+        let chip8 = &self.chip8;
+        let mut sample = [0; 18];
+        sample[..16].copy_from_slice(&chip8.registers);
+        sample[16] = chip8.delay;
+        sample[17] = chip8.sound;
+        if self.register_history.len() == REGISTER_HISTORY {
+            self.register_history.pop_front();
+        }
+        self.register_history.push_back(sample);
+    }
+
+    /// S, save a picture of the display in the working directory
+    fn screenshot(&mut self) {
+        let path = screenshot::file_name(self.chip8.rom.name(), library::now());
+        match screenshot::save(&self.chip8, SCREENSHOT_SCALE, Path::new(&path)) {
+            Ok(()) => self.report(format!("Screenshot saved to {path}")),
+            Err(e) => self.report_at(Level::Warn, format!("Could not save a screenshot: {e}")),
+        }
+    }
+
+    /// R, start recording the display, or stop and save what was recorded in
+    /// the working directory
+    fn toggle_recording(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            self.recording = Some(Recording::new());
+            self.report("Recording, R to stop".to_string());
+            return;
+        };
+        let name = screenshot::file_name(self.chip8.rom.name(), library::now());
+        let path = Path::new(&name).with_extension("gif");
+        match recording.save(SCREENSHOT_SCALE, &path) {
+            Ok(()) => self.report(format!("Recording saved to {}", path.display())),
+            Err(e) => self.report_at(Level::Warn, format!("Could not save the recording: {e}")),
+        }
+    }
+
+    /// F, let pixels fade out rather than go dark at once
+    fn toggle_phosphor(&mut self) {
+        self.phosphor = match self.phosphor {
+            Some(_) => None,
+            None => Some(Phosphor::new(self.phosphor_frames)),
+        };
+        let status = match self.phosphor {
+            Some(_) => format!("Pixels fade over {} frames", self.phosphor_frames),
+            None => "Pixels go dark at once".to_string(),
+        };
+        self.report(status);
+    }
+
+    /// The glow to draw, only while running, paused shows the display as it is
+    fn glow(&self) -> Option<&Phosphor> {
+        match self.mode {
+            Mode::Running => self.phosphor.as_ref(),
+            Mode::Paused => None,
+        }
+    }
+
+    /// Add `ticks` frames of the display to the recording, stopping it at the
+    /// limit
+    fn capture(&mut self, ticks: u32) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        recording.capture(&self.chip8, ticks);
+        if recording.ticks() >= RECORDING_LIMIT {
+            self.toggle_recording();
+        }
+    }
+
+    /// F1 to F4, the slot F5 saves to and F9 loads from
+    fn select_slot(&mut self, slot: u8) {
+        self.slot = slot;
+        self.report(format!("Slot {slot}, F5 to save and F9 to load"));
+    }
+
+    fn save_state(&mut self) {
+        let Some(path) = self.chip8.state_slot_path(self.slot) else {
+            self.report_at(Level::Warn, "No data directory to save states in".into());
+            return;
+        };
+        match self.chip8.save_state(&path) {
+            Ok(()) => {
+                self.saved[self.slot as usize - 1] = Some(library::now());
+                self.report(format!("State saved to {}", path.display()));
+            }
+            Err(e) => self.report_at(Level::Warn, format!("Could not save the state: {e}")),
+        }
     }
 
-    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<(), Box<dyn Error>> {
-        let tick_rate = Duration::from_millis(4);
+    /// What the status bar says of the slot, and how long ago it was saved
+    fn slot_label(&self) -> String {
+        match self.saved[self.slot as usize - 1] {
+            Some(then) => format!("slot {}, {}", self.slot, library::ago(then, library::now())),
+            None => format!("slot {}, empty", self.slot),
+        }
+    }
+
+    fn load_state(&mut self) {
+        let Some(path) = self.chip8.state_slot_path(self.slot) else {
+            self.report_at(Level::Warn, "No data directory to load states from".into());
+            return;
+        };
+        match Chip8::load_state(&path) {
+            Ok(mut chip8) => {
+                chip8.take_debugging(&mut self.chip8);
+                self.chip8 = chip8;
+                self.error = None;
+                self.scrub = None;
+                self.history.clear();
+                self.report(format!("State loaded from slot {}", self.slot));
+            }
+            Err(e) => self.report_at(Level::Warn, format!("Could not load the state: {e}")),
+        }
+    }
+
+    /// J while paused, typically on an instruction that failed: go on past it
+    /// without running it, a step back undoes that
+    fn skip_instruction(&mut self) {
+        if let Mode::Running = self.mode {
+            return;
+        }
+        self.settle_scrub();
+        self.history.push(self.chip8.clone());
+        let address = self.chip8.program_counter;
+        let opcode = self.chip8.opcode();
+        let end = self.chip8.memory.len() as u16;
+        self.chip8.program_counter = address.wrapping_add(2) % end;
+        self.error = None;
+        self.program_cursor = None;
+        self.program_scroll = 0;
+        let address_name = self.address_name(address);
+        self.report(format!("Skipped {opcode:04x} at {address_name}"));
+    }
+
+    /// Start the rom over, keeping the tab's debugger settings
+    fn reset(&mut self) {
+        self.chip8.reset();
+        self.error = None;
+        self.scrub = None;
+        self.history.clear();
+        self.register_history.clear();
+        // a script replays from its start
+        self.steps = 0;
+        self.idle_frames = 0;
+        self.report("Reset".to_string());
+    }
+
+    /// A color per address: hotter the more it has run, blue for sprite data
+    /// and dark for what has been neither
+    fn heatmap(&self) -> Vec<Color> {
+        let coverage = self.chip8.coverage();
+        (0..self.chip8.memory.len() as u16)
+            .map(|address| match coverage.runs(address) {
+                0 if coverage.sprite(address) => Color::Blue,
+                0 => Color::DarkGray,
+                1..100 => Color::Green,
+                100..10_000 => Color::Yellow,
+                10_000..1_000_000 => Color::LightRed,
+                _ => Color::Red,
+            })
+            .collect()
+    }
+
+    /// `draw_ball (0x2e6)` if the symbols name `address`, otherwise `0x2e6`
+    fn address_name(&self, address: u16) -> String {
+        match self.symbols.name(address) {
+            Some(name) => format!("{name} ({address:#x})"),
+            None => format!("{address:#x}"),
+        }
+    }
+
+    /// Run `count` instructions from a pause, stopping early wherever running
+    /// would have paused
+    fn step_paused(&mut self, count: u32) {
+        self.step_paused_until(count, |_| false);
+    }
+
+    /// Step from a pause until `done`, for at most `max_steps`, stopping early
+    /// wherever running would have paused
+    fn step_paused_until(&mut self, max_steps: u32, done: impl Fn(&Chip8) -> bool) {
+        for _ in 0..max_steps {
+            if self.is_waiting() || self.chip8.is_halted() {
+                break;
+            }
+            self.mode = Mode::Running;
+            self.step();
+            if let Mode::Paused = self.mode {
+                return;
+            }
+            if done(&self.chip8) {
+                break;
+            }
+        }
+        self.mode = Mode::Paused;
+    }
+
+    /// `n`, `o` and `u` while paused: one instruction, over a CALL to its RET, or
+    /// out of the subroutine running now, like [`Chip8::step_over`] and
+    /// [`Chip8::step_out`] but recorded for stepping back
+    fn step_key(&mut self, key: char) {
+        if let Mode::Running = self.mode {
+            return;
+        }
+        let depth = self.chip8.stack_pointer;
+        match key {
+            'o' => self.step_paused_until(STEP_OVER_LIMIT, |chip8| chip8.stack_pointer <= depth),
+            'u' if depth == 0 => self.report("Not in a subroutine".to_string()),
+            'u' => self.step_paused_until(STEP_OVER_LIMIT, |chip8| chip8.stack_pointer < depth),
+            _ => self.step_paused(1),
+        }
+    }
+
+    /// `.` while paused: one 60Hz frame, the instructions it holds at this speed
+    /// or in VIP cycles, then a tick of the timers
+    fn advance_frame(&mut self) {
+        if let Mode::Running = self.mode {
+            return;
+        }
+        match self.vip_cycles {
+            Some(_) => {
+                // the cycles of the instructions run so far and the one about to
+                let spent = Cell::new(self.chip8.vip_cycles());
+                self.step_paused_until(VIP_CYCLES_PER_FRAME, |chip8| {
+                    spent.set(spent.get() + chip8.vip_cycles());
+                    spent.get() > VIP_CYCLES_PER_FRAME
+                });
+            }
+            None => self.step_paused(self.speed.div_ceil(60)),
+        }
+        if self.script.is_none() {
+            self.chip8.tick_timers();
+            self.input_frame();
+        }
+        self.sample_registers();
+        self.capture(1);
+    }
+
+    /// Run a line typed at the `:` prompt, returning what to show for it
+    fn run_command(&mut self, line: &str) -> String {
+        let command = match DebugCommand::parse(line, &self.symbols) {
+            Ok(command) => command,
+            Err(e) => return e,
+        };
+        match command {
+            DebugCommand::Break(address, condition) => {
+                let address_name = self.address_name(address);
+                let status = match &condition {
+                    Some(condition) => format!("Breakpoint set at {address_name} if {condition}"),
+                    None => format!("Breakpoint set at {address_name}"),
+                };
+                self.chip8.set_breakpoint(address, condition);
+                status
+            }
+            DebugCommand::Watch(watchpoint) => {
+                let index = self.chip8.add_watchpoint(watchpoint.clone());
+                format!("Watchpoint {index}: {watchpoint}")
+            }
+            DebugCommand::Mem(address) => debugger::dump(&self.chip8, address),
+            DebugCommand::Set(target, value) => {
+                target.write(&mut self.chip8, value);
+                format!("{target} set to {value:#x}")
+            }
+            DebugCommand::Continue => {
+                if let Mode::Paused = self.mode {
+                    self.toggle_mode();
+                }
+                "Running".to_string()
+            }
+            DebugCommand::Display(expression) => {
+                let index = self.watches.len();
+                let value = expression.evaluate(&self.chip8);
+                let status = format!("Watch {index}: {expression}");
+                self.watches.push(Watch {
+                    expression,
+                    value,
+                    changed_at: None,
+                });
+                status
+            }
+            DebugCommand::Undisplay(index) if index < self.watches.len() => {
+                let watch = self.watches.remove(index);
+                format!("Removed watch {index}: {}", watch.expression)
+            }
+            DebugCommand::Undisplay(index) => format!("No watch {index}"),
+            DebugCommand::Find(pattern) => {
+                // the same search again moves on to the next match
+                let (pattern, after) = match (pattern, self.search.take()) {
+                    (None, Some((pattern, at))) => (pattern, Some(at)),
+                    (Some(pattern), Some((last, at))) if pattern == last => (pattern, Some(at)),
+                    (Some(pattern), _) => (pattern, None),
+                    (None, None) => return "Nothing to find again".to_string(),
+                };
+                let matches = debugger::find(&self.chip8, &pattern);
+                let next = matches
+                    .iter()
+                    .position(|&address| after.is_none_or(|after| address > after))
+                    .or((!matches.is_empty()).then_some(0));
+                let Some(n) = next else {
+                    return format!("{} not found", debugger::bytes(&pattern));
+                };
+                let address = matches[n];
+                self.search = Some((pattern, address));
+                self.found = Some(address);
+                let address_name = self.address_name(address);
+                format!("Found at {address_name}, {} of {}", n + 1, matches.len())
+            }
+            DebugCommand::Step(count) => {
+                self.status = None;
+                self.step_paused(count);
+                let pc = self.chip8.program_counter;
+                match &self.status {
+                    Some(status) => status.clone(),
+                    None => format!("Stopped at {pc:#x}"),
+                }
+            }
+        }
+    }
+
+    /// Set or clear a breakpoint on the instruction under the Program panel's
+    /// cursor, or the next one without a cursor
+    fn toggle_breakpoint(&mut self) {
+        let address = self.program_cursor.unwrap_or(self.chip8.program_counter);
+        self.toggle_breakpoint_at(address);
+    }
+
+    fn toggle_breakpoint_at(&mut self, address: u16) {
+        let address_name = self.address_name(address);
+        let status = match self.chip8.toggle_breakpoint(address) {
+            true => format!("Breakpoint set at {address_name}"),
+            false => format!("Breakpoint cleared at {address_name}"),
+        };
+        self.report(status);
+    }
+
+    /// Move the Program panel's cursor by `instructions`, only while paused. It
+    /// starts from the PC and goes away when the program runs on.
+    fn move_program_cursor(&mut self, instructions: i32) {
+        if let Mode::Running = self.mode {
+            return;
+        }
+        let from = self.program_cursor.unwrap_or(self.chip8.program_counter) as i32;
+        // whole instructions from the PC, inside memory
+        let last = self.chip8.memory.len() as i32 - 2;
+        let to = from + instructions * 2;
+        let to = match to < 0 || to > last {
+            true => from,
+            false => to,
+        };
+        self.program_cursor = Some(to as u16);
+        self.program_scroll = 0;
+    }
+
+    /// Scroll the Program panel `instructions` further from the cursor, or the
+    /// PC without one, to look through the code away from it
+    fn scroll_program(&mut self, instructions: i32) {
+        let focus = i32::from(self.program_cursor.unwrap_or(self.chip8.program_counter));
+        let last = self.chip8.memory.len() as i32 - 2;
+        let to = (focus + (self.program_scroll + instructions) * 2).clamp(focus % 2, last);
+        self.program_scroll = (to - focus) / 2;
+    }
+
+    /// Move `by` steps through the rewind history, only while paused. The steps
+    /// after the state shown are kept until the program runs on from it.
+    fn scrub(&mut self, by: isize) {
+        if let Mode::Running = self.mode {
+            return;
+        }
+        let len = self.history.len();
+        let index = self.scrub.as_ref().map_or(len, |scrub| scrub.index);
+        let to = index.saturating_add_signed(by).min(len);
+        if to == index {
+            self.report(match by < 0 {
+                true => "Nothing to step back to".to_string(),
+                false => "Nothing recorded after this".to_string(),
+            });
+            return;
+        }
+        let scrub = self.scrub.get_or_insert_with(|| Scrub {
+            index: len,
+            present: self.chip8.clone(),
+            steps: self.steps,
+        });
+        let mut shown = match self.history.get(to) {
+            Some(state) => state.clone(),
+            None => scrub.present.clone(),
+        };
+        shown.take_debugging(&mut self.chip8);
+        self.chip8 = shown;
+        scrub.index = to;
+        self.steps = scrub.steps.saturating_sub((len - to) as u64);
+        self.error = None;
+        let pc = self.chip8.program_counter;
+        self.report(match len - to {
+            0 => format!("Back to the present at {pc:#x}"),
+            back => format!("{back} of {len} steps back at {pc:#x}"),
+        });
+        if to == len {
+            self.scrub = None;
+        }
+    }
+
+    /// Remember the state as it is now, to see what changes from here
+    fn mark_state(&mut self) {
+        self.marked = Some(Box::new(self.chip8.clone()));
+        let pc = self.chip8.program_counter;
+        self.report(format!("State marked at {pc:#x}"));
+    }
+
+    /// Drop the steps after the state scrubbed back to, before running on from it
+    fn settle_scrub(&mut self) {
+        if let Some(scrub) = self.scrub.take() {
+            self.history.truncate(scrub.index);
+        }
+    }
+
+    fn play(&mut self, input_macro: &InputMacro) {
+        self.playback = Some(input_macro.frames().collect::<Vec<_>>().into_iter());
+    }
+
+    fn set_speed(&mut self, speed: u32) {
+        self.speed = speed;
+        self.clock = Clock::new(Instant::now(), self.rate());
+        if let TimerSource::Instructions(_) = self.chip8.timer_source {
+            self.chip8.timer_source = TimerSource::Instructions(speed);
+        }
+    }
+
+    /// Run the instructions that fell due, stopping early if one pauses the tab
+    fn on_tick(&mut self) {
+        let now = Instant::now();
+        self.release_held(now);
+        // no backlog builds up while nothing can run
+        if matches!(self.mode, Mode::Paused) || self.is_waiting() {
+            self.clock = Clock::new(now, self.rate());
+            self.vip_cycles = self.vip_cycles.map(|_| 0);
+            return;
+        }
+        // at most a second's worth after a stall
+        let due = self.clock.due(now).min(self.rate());
+        let Some(cycles) = self.vip_cycles else {
+            (0..due).for_each(|_| self.step());
+            return;
+        };
+        // each instruction waits until the cycles it takes on the VIP are due
+        let mut cycles = cycles + due;
+        while let Some(left) = cycles.checked_sub(self.chip8.vip_cycles()) {
+            cycles = left;
+            self.step();
+        }
+        self.vip_cycles = Some(cycles);
+    }
+
+    /// Clock ticks a second, instructions or VIP cycles
+    fn rate(&self) -> u32 {
+        let rate = match self.vip_cycles {
+            Some(_) => VIP_CYCLES_PER_SECOND,
+            None => self.speed,
+        };
+        match self.turbo {
+            true => rate * TURBO,
+            false => rate,
+        }
+    }
+
+    /// A quarter faster or slower, + and -
+    fn change_speed(&mut self, faster: bool) {
+        let speed = match faster {
+            true => self.speed + self.speed.div_ceil(4),
+            false => self.speed - self.speed / 5,
+        };
+        self.set_speed(speed.clamp(MIN_SPEED, MAX_SPEED));
+    }
+
+    fn toggle_turbo(&mut self) {
+        self.turbo = !self.turbo;
+        self.clock = Clock::new(Instant::now(), self.rate());
+    }
+
+    /// How many times as fast as it opened the tab runs, turbo included
+    fn multiplier(&self) -> f64 {
+        let turbo = match self.turbo {
+            true => TURBO as f64,
+            false => 1.0,
+        };
+        turbo * self.speed as f64 / self.base_speed as f64
+    }
+
+    /// How fast the tab runs, for the status bar
+    fn speed_label(&self) -> String {
+        let speed = match self.vip_cycles {
+            Some(_) => "VIP speed".to_string(),
+            None => format!("{} Hz, {} a frame", self.speed, self.speed.div_ceil(60)),
+        };
+        match self.turbo {
+            true => format!("{speed}, turbo x{TURBO}"),
+            false => speed,
+        }
+    }
+
+    fn step(&mut self) {
+        if let Mode::Running = self.mode {
+            if let Some(script) = &self.script {
+                self.chip8.keys = script.keys_at(self.steps);
+            }
+            if self.is_waiting() {
+                return;
+            }
+            self.settle_scrub();
+            let (address, opcode) = (self.chip8.program_counter, self.chip8.opcode());
+            LAST_INSTRUCTION.store(
+                u32::from(address) << 16 | u32::from(opcode),
+                atomic::Ordering::Relaxed,
+            );
+            let outcome = match self.chip8.step_recorded(&mut self.history) {
+                Ok(outcome) => outcome,
+                Err(error) => {
+                    self.mode = Mode::Paused;
+                    self.log.error(format!("{address:#x}: {error}"));
+                    self.error = Some(error);
+                    return;
+                }
+            };
+            self.steps += 1;
+            if self.script.is_none() && self.playback.is_none() && self.key_hold.is_some() {
+                // without key releases from the terminal, Fx0A only sees presses made
+                // during the wait, each released as soon as the core has seen it
+                match self.chip8.state {
+                    State::WaitingForKey { key: None, .. } => self.chip8.keys = 0,
+                    State::WaitingForKey { key: Some(key), .. } => self.chip8.key_up(key),
+                    State::Running | State::WaitingForVblank | State::Halted => {}
+                }
+            }
+
+            if let StepOutcome::Drew { collision } = outcome {
+                if self.break_on_draw || (self.break_on_collision && collision) {
+                    let collided = if collision { " with collision" } else { "" };
+                    self.pause(format!("Draw at {address:#x}{collided}"));
+                }
+            }
+            if self.pause_on_sound && opcode & 0xF0FF == 0xF018 {
+                let sound = self.chip8.sound;
+                self.pause(format!("Sound timer set to {sound} at {address:#x}"));
+            }
+            if let Some(written) = self
+                .chip8
+                .self_modified
+                .filter(|_| self.break_on_self_modify)
+            {
+                self.pause(format!("Code at {written:#x} overwritten at {address:#x}"));
+            }
+            if let Some(index) = self.chip8.watch_hit {
+                let watchpoint = &self.chip8.watchpoints[index];
+                self.pause(format!("Watchpoint {index} at {address:#x}: {watchpoint}"));
+            }
+            if self.chip8.at_breakpoint() {
+                let pc = self.chip8.program_counter;
+                let pc_name = self.address_name(pc);
+                let status = match self.chip8.breakpoint_condition(pc) {
+                    Some(condition) => format!("Breakpoint at {pc_name}, {condition}"),
+                    None => format!("Breakpoint at {pc_name}"),
+                };
+                self.pause(status);
+            }
+            if self.chip8.is_halted() && outcome != StepOutcome::Halted {
+                let pc = self.chip8.program_counter;
+                self.pause(format!("Program ended at {pc:#x}"));
+            }
+        }
+    }
+}
+
+impl App {
+    /// Open the roms on the command line, or the restored `session`
+    fn new(cli: &Cli, session: Option<Session>) -> Result<Self, Box<dyn Error>> {
+        let config = Config::load(cli.config.as_deref())?;
+        let session = session.unwrap_or_else(|| Session {
+            tabs: cli.rom_paths.iter().map(|p| TabSession::new(p)).collect(),
+            panels: config.layout.unwrap_or_default(),
+            ..Session::default()
+        });
+        let log = Log::default();
+        // one trace for all tabs, only the active one runs
+        let tracer = match cli.trace.as_deref() {
+            Some(path) if path == Path::new("-") => Some(Tracer::new(log.clone())),
+            path => path.map(Tracer::create).transpose()?,
+        };
+        let mute = cli.mute || config.mute;
+        let graphics = cli
+            .graphics
+            .or(config.graphics)
+            .unwrap_or_default()
+            .detect(|name| env::var(name).ok());
+        let beeper = match mute {
+            true => None,
+            false => Beeper::new().or_else(|| {
+                log.warn("No audio output, ringing the terminal bell instead");
+                None
+            }),
+        };
+        let mut app = Self {
+            tabs: Vec::new(),
+            active: 0,
+            tick_count: 0,
+            screens: Screens::of(&session.panels),
+            console: None,
+            console_output: None,
+            library: Library::load(None)?,
+            recorded: RecordedMacros::load(None)?,
+            library_dir: cli.library.clone().or_else(|| config.library.clone()),
+            library_roms: Vec::new(),
+            theme: config.theme(cli.theme),
+            marker: cli.marker.or(config.marker).unwrap_or_default(),
+            config,
+            tracer,
+            key_releases: false,
+            timing: FrameTiming::new(Duration::from_secs(3)),
+            fps: Rate::new(Duration::from_secs(1)),
+            ips: Rate::new(Duration::from_secs(1)),
+            show_timing: session.show_timing,
+            panels: session.panels,
+            show_profile: cli.profile,
+            memory_view: None,
+            editor: None,
+            show_heatmap: false,
+            show_key_heatmap: false,
+            sprite_preview: None,
+            show_rom_info: false,
+            timers: Clock::timers(Instant::now()),
+            bell: match (mute, &beeper) {
+                (false, None) => Some(Bell::new()),
+                _ => None,
+            },
+            beeper,
+            log,
+            show_log: false,
+            log_scroll: 0,
+            hitboxes: RefCell::default(),
+            mouse_key: None,
+            graphics,
+            cell_size: None,
+            image_area: Cell::new(None),
+            shown: None,
+        };
+        for saved in &session.tabs {
+            app.open(&saved.rom, cli)?;
+            let tab = app.tab_mut();
+            if let Some(speed) = saved.speed {
+                tab.set_speed(speed);
+            }
+            tab.break_on_draw = saved.break_on_draw;
+            tab.break_on_collision = saved.break_on_collision;
+            tab.pause_on_sound = saved.pause_on_sound;
+            tab.break_on_self_modify = saved.break_on_self_modify;
+            for breakpoint in &saved.breakpoints {
+                tab.chip8
+                    .set_breakpoint(breakpoint.address, breakpoint.condition.clone());
+            }
+        }
+        app.active = session.active.min(app.tabs.len() - 1);
+        Ok(app)
+    }
+
+    /// Open the rom at `path` in a new tab and show it
+    fn open(&mut self, path: &Path, cli: &Cli) -> Result<(), Box<dyn Error>> {
+        let log = self.log.clone();
+        let mut tab = Tab::new(path, cli, &self.config, &self.library, log)?;
+        tab.chip8.tracer = self.tracer.clone();
+        if self.key_releases {
+            tab.key_hold = None;
+        }
+        self.tabs.push(tab);
+        self.active = self.tabs.len() - 1;
+        Ok(())
+    }
+
+    /// The speed tabs start at without a remembered one
+    fn default_speed(&self, cli: &Cli) -> u32 {
+        cli.speed.or(self.config.speed).unwrap_or(DEFAULT_SPEED)
+    }
+
+    /// Remember how each open rom was played for the next time, its speed and
+    /// the platform and keymap chosen on the command line
+    fn remember(&mut self, cli: &Cli) {
+        let now = library::now();
+        let default_speed = self.default_speed(cli);
+        for tab in &self.tabs {
+            let settings = self.library.settings_mut(tab.chip8.rom.hash());
+            settings.speed = (tab.speed != default_speed).then_some(tab.speed);
+            settings.platform = cli.platform.or(settings.platform);
+            settings.keymap = cli.keymap.or(settings.keymap);
+            settings.last_played = Some(now);
+        }
+    }
+
+    /// List the library's roms, L
+    fn open_library(&mut self) {
+        let Some(dir) = &self.library_dir else {
+            let status = "No library, set `library` in the config file or pass --library";
+            self.tab_mut().report_at(Level::Warn, status.to_string());
+            return;
+        };
+        match library::scan(dir) {
+            Ok(roms) => {
+                self.library_roms = roms;
+                self.screens.open(Screen::RomBrowser { selected: 0 });
+            }
+            Err(e) => {
+                let status = format!("Could not read the library at {}: {e}", dir.display());
+                self.tab_mut().report_at(Level::Warn, status);
+            }
+        }
+    }
+
+    /// Show the selected rom of the library, opening it in a new tab unless one
+    /// has it already. A rom new to the library runs on the platform its
+    /// extension suggests.
+    fn open_from_library(&mut self, selected: usize, cli: &Cli) {
+        let Some(rom) = self.library_roms.get(selected).cloned() else {
+            return;
+        };
+        self.screens.close();
+        let path = fs::canonicalize(&rom.path).unwrap_or(rom.path.clone());
+        if let Some(open) = self
+            .tabs
+            .iter()
+            .position(|tab| tab.chip8.rom.path() == path)
+        {
+            self.active = open;
+            return;
+        }
+        self.library.add(&rom);
+        if let Err(e) = self.open(&path, cli) {
+            let status = format!("Could not open {}: {e}", rom.path.display());
+            self.tab_mut().report_at(Level::Error, status);
+        }
+    }
+    /// What to restore on the next launch
+    fn session(&self, cli: &Cli) -> Session {
+        Session {
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| TabSession {
+                    speed: (tab.speed != self.default_speed(cli)).then_some(tab.speed),
+                    break_on_draw: tab.break_on_draw,
+                    break_on_collision: tab.break_on_collision,
+                    pause_on_sound: tab.pause_on_sound,
+                    break_on_self_modify: tab.break_on_self_modify,
+                    breakpoints: tab
+                        .chip8
+                        .breakpoints
+                        .iter()
+                        .map(|&address| Breakpoint {
+                            address,
+                            condition: tab.chip8.breakpoint_condition(address).cloned(),
+                        })
+                        .collect(),
+                    ..TabSession::new(tab.chip8.rom.path())
+                })
+                .collect(),
+            active: self.active,
+            show_timing: self.show_timing,
+            panels: Panels {
+                zen: self.screens.base() == Screen::Emulator,
+                ..self.panels
+            },
+        }
+    }
+
+    fn tab(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+    fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+
+    pub fn run(&mut self, mut terminal: DefaultTerminal, cli: &Cli) -> Result<(), Box<dyn Error>> {
+        let tick_rate = TICK_RATE;
         let mut last_tick = Instant::now();
+        let mut last_frame = Instant::now();
+        let mut emulation = Duration::ZERO;
         loop {
+            let frame_start = Instant::now();
+            self.cell_size = self.cell_size();
             terminal.draw(|frame| self.draw(frame))?;
-            let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+            if self.show_image()? {
+                terminal.clear()?;
+            }
+            self.fps.record(Instant::now(), 1);
+            let idle = self.tab().is_idle();
+            if !idle {
+                self.timing
+                    .record(emulation, frame_start.elapsed(), frame_start - last_frame);
+            }
+            last_frame = frame_start;
+            emulation = Duration::ZERO;
+
+            // sleep until something happens rather than redrawing every tick
+            let timeout = match idle {
+                true => IDLE_POLL,
+                false => tick_rate
+                    .saturating_sub(last_tick.elapsed())
+                    .min(self.timers.next().saturating_duration_since(Instant::now())),
+            };
             if event::poll(timeout)? {
-                if let Event::Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Esc => break Ok(()),
-                        KeyCode::Char(' ') => self = self.toggle_mode(),
-                        KeyCode::Char('1') => self.chip8.input = 0,
-                        KeyCode::Char('2') => self.chip8.input = 1,
-                        KeyCode::Char('3') => self.chip8.input = 2,
-                        KeyCode::Char('4') => self.chip8.input = 3,
-                        KeyCode::Char('q') => self.chip8.input = 4,
-                        KeyCode::Char('w') => self.chip8.input = 5,
-                        KeyCode::Char('e') => self.chip8.input = 6,
-                        KeyCode::Char('r') => self.chip8.input = 7,
-                        KeyCode::Char('a') => self.chip8.input = 8,
-                        KeyCode::Char('s') => self.chip8.input = 9,
-                        KeyCode::Char('d') => self.chip8.input = 10,
-                        KeyCode::Char('f') => self.chip8.input = 11,
-                        KeyCode::Char('z') => self.chip8.input = 12,
-                        KeyCode::Char('x') => self.chip8.input = 13,
-                        KeyCode::Char('c') => self.chip8.input = 14,
-                        KeyCode::Char('v') => self.chip8.input = 15,
-                        _ => {}
-                    }
+                match event::read()? {
+                    Event::Mouse(mouse) => self.on_mouse(mouse),
+                    Event::Key(key) if self.on_key(key, cli) => break Ok(()),
+                    // the terminal wipes images as it resizes
+                    Event::Resize(..) => self.shown = None,
+                    _ => {}
                 }
             }
 
+            // timers keep their own schedule however late this iteration is
+            let due = self.timers.due(Instant::now());
+            self.tab_mut().tick_timers(due);
+
             if last_tick.elapsed() >= tick_rate {
+                let tick_start = Instant::now();
+                let steps = self.tab().steps;
                 self.on_tick();
+                emulation += tick_start.elapsed();
                 last_tick = Instant::now();
+                let ran = self.tab().steps.saturating_sub(steps);
+                self.ips.record(last_tick, ran);
+            }
+            let tab = self.tab();
+            let sounding = matches!(tab.mode, Mode::Running) && tab.chip8.sound > 0;
+            if let Some(beeper) = &self.beeper {
+                beeper.set(sounding);
+            }
+            if let Some(bell) = &mut self.bell {
+                if bell.ring(sounding, Instant::now()) {
+                    crossterm::execute!(io::stdout(), crossterm::style::Print('\x07'))?;
+                }
             }
         }
     }
 
-    fn on_tick(&mut self) {
-        self.tick_count += 1;
-        match self.mode {
-            Mode::Running => {
-                self.chip8.step();
+    /// A key, to the console or the editor while they're open and otherwise
+    /// to the screen on top, returning whether it quits
+    fn on_key(&mut self, key: KeyEvent, cli: &Cli) -> bool {
+        // only the keypad has anything to do on a release
+        if key.kind == KeyEventKind::Release {
+            if let Some(released) = self.keypad(key.code) {
+                self.tab_mut().release(released);
             }
-            _ => (),
+            return false;
+        }
+        if self.console.is_some() {
+            self.on_console_key(key.code);
+            return false;
+        }
+        if self.editor.is_some() {
+            self.edit(key.code);
+            return false;
+        }
+        let commands: Vec<Command> = Command::iter().collect();
+        let top = self.screens.top();
+        let len = match top {
+            Screen::RomBrowser { .. } => self.library_roms.len(),
+            Screen::Settings { .. } => commands.len(),
+            _ => 0,
         };
+        match (self.screens.on_key(key.code, len), top) {
+            (KeyAction::Base, _) => return self.on_emulator_key(key.code),
+            (KeyAction::Choose(selected), Screen::RomBrowser { .. }) => {
+                self.open_from_library(selected, cli)
+            }
+            (KeyAction::Choose(selected), Screen::Settings { .. }) => {
+                let option = self.tab_mut().option(commands[selected]);
+                *option = !*option;
+            }
+            _ => {}
+        }
+        false
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        let horizontal = Layout::horizontal([Constraint::Length(66), Constraint::Min(1)]);
-        let [left, right] = horizontal.areas(frame.area());
-
-        let left_vertical = Layout::vertical([Constraint::Length(18), Constraint::Min(6)]);
-        let [display, n3] = left_vertical.areas(left);
-        frame.render_widget(self.display(), display);
-        frame.render_widget(Paragraph::new("n3").block(Block::bordered()), n3);
-
-        let right_vertical = Layout::vertical([Constraint::Min(1), Constraint::Length(7)]);
-        let [n1, n2] = right_vertical.areas(right);
-
-        self.render_registers(n3, frame);
-        self.render_program(n1, frame);
-        frame.render_widget(
-            HexInput::new(self.chip8.input).block(Block::bordered().title("Input")),
-            n2,
-        );
-    }
-    fn render_program(&self, area: Rect, frame: &mut Frame) {
-        let outer_block = Block::bordered().title("Program");
-        let inner = outer_block.inner(area);
-        frame.render_widget(outer_block, area);
-
-        let memory = self.chip8.memory;
-        let pc = self.chip8.program_counter as usize;
-        let display_range = pc - 4..pc + 28;
-        let program_display = &memory[display_range];
-        let lines: Vec<Line> = program_display
-            .chunks(2)
-            .into_iter()
-            .enumerate()
-            .map(|(i, b)| style_instruction(pc, i * 2 + (pc - 4), b[0], b[1]))
-            .collect();
-
-        let list = List::new(lines);
-        frame.render_widget(list, inner);
-    }
-
-    fn render_registers(&self, area: Rect, frame: &mut Frame) {
-        let outer_block = Block::bordered().title("Registers");
-        let content = outer_block.inner(area);
-        frame.render_widget(outer_block, area);
-        let register_layout = Layout::vertical([Constraint::Length(4), Constraint::Length(1)]);
-        let [main_reg, misc_reg] = register_layout.areas(content);
-
-        let labels: Vec<String> = (0..16)
-            .collect::<Vec<i32>>()
-            .into_iter()
-            .map(|i| "v".to_owned() + &format!("{i:1x}").to_uppercase())
-            .collect();
-
-        let data: Vec<(&str, u64)> = labels
-            .iter()
-            .zip(self.chip8.registers)
-            .map(|(l, i)| (l.as_str(), i as u64))
-            .collect();
-
-        let bar_columns = Layout::horizontal([
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-        ]);
-        let bar_areas: [Rect; 4] = bar_columns.areas(main_reg);
-        let _ = &data
-            .chunks(4)
-            .into_iter()
-            .zip(bar_areas)
-            .for_each(|(f, a)| {
-                frame.render_widget(
-                    BarChart::default()
-                        .bar_gap(0)
-                        .bar_width(1)
-                        .bar_style(Style::new().green())
-                        .value_style(Style::new().black().on_green())
-                        .data(f)
-                        .max(255)
-                        .direction(Direction::Horizontal),
-                    a,
-                );
-            });
-        let bar_columns = Layout::horizontal([
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-            Constraint::Fill(1),
-        ]);
-        //frame.render_
-        let bar_areas: [Rect; 3] = bar_columns.areas(misc_reg);
-        let _ = &[
-            ("delay", self.chip8.delay as u64),
-            ("sound", self.chip8.sound as u64),
-            ("i", self.chip8.i as u64),
-        ]
-        .into_iter()
-        .zip(bar_areas)
-        .for_each(|(f, a)| {
-            frame.render_widget(
-                BarChart::default()
-                    .bar_gap(0)
-                    .bar_width(1)
-                    .bar_style(Style::new().blue())
-                    .value_style(Style::new().black().on_blue())
-                    .data(&[f])
-                    .max(2000)
-                    .direction(Direction::Horizontal),
-                a,
-            );
-        });
+    /// The line typed at the `:` prompt, run on Enter
+    fn on_console_key(&mut self, code: KeyCode) {
+        let Some(line) = &mut self.console else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.console = None,
+            KeyCode::Enter => {
+                let line = self.console.take().unwrap_or_default();
+                let output = self.tab_mut().run_command(&line);
+                self.log.info(format!(":{line}"));
+                for output in output.lines() {
+                    self.log.info(output);
+                }
+                self.console_output = Some(output);
+                if let Some(address) = self.tab_mut().found.take() {
+                    self.memory_view = Some(address & !0xF);
+                }
+            }
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            KeyCode::Char(c) => line.push(c),
+            _ => {}
+        }
     }
 
-    fn display(&self) -> impl Widget + '_ {
-        Canvas::default()
-            .block(
-                Block::bordered()
-                    .title(self.chip8.rom.name())
-                    .title(self.mode.to_string()),
-            )
-            .marker(Marker::HalfBlock)
-            .paint(|ctx| {
-                ctx.draw(&self.chip8);
-            })
+    /// The keys of the emulator and the debugger, the keypad first, returning
+    /// whether it quits
+    fn on_emulator_key(&mut self, code: KeyCode) -> bool {
+        if let Some(pressed) = self.keypad(code) {
+            self.tab_mut().press(pressed);
+            return false;
+        }
+        let tab_count = self.tabs.len();
+        match code {
+            KeyCode::Esc => return true,
+            KeyCode::Char(' ') => self.tab_mut().toggle_mode(),
+            KeyCode::Char('p') => self.screens.open(Screen::Settings { selected: 0 }),
+            KeyCode::Char('?') => self.screens.open(Screen::Help),
+            KeyCode::Char('L') => self.open_library(),
+            KeyCode::Char(':') => self.console = Some(String::new()),
+            KeyCode::Char('t') => self.show_timing = !self.show_timing,
+            KeyCode::Char('V') => self.panels.registers = !self.panels.registers,
+            KeyCode::Char('P') => self.panels.program = !self.panels.program,
+            KeyCode::Char('N') => self.panels.input = !self.panels.input,
+            KeyCode::Char('Z') => self.screens.toggle_base(),
+            KeyCode::Char('i') => self.show_rom_info = !self.show_rom_info,
+            KeyCode::Char('y') => {
+                self.sprite_preview = match self.sprite_preview {
+                    Some(_) => None,
+                    None => Some(DEFAULT_SPRITE_HEIGHT),
+                }
+            }
+            KeyCode::Char('Y') => {
+                if let Some(height) = &mut self.sprite_preview {
+                    *height = *height % 15 + 1;
+                }
+            }
+            KeyCode::Char('h') => self.show_profile = !self.show_profile,
+            KeyCode::Char('l') => self.show_log = !self.show_log,
+            KeyCode::Char('+' | '=') => self.tab_mut().change_speed(true),
+            KeyCode::Char('-') => self.tab_mut().change_speed(false),
+            KeyCode::Char('T') => self.tab_mut().toggle_turbo(),
+            KeyCode::Char('g') => self.cycle_marker(),
+            KeyCode::Char('[') => self.scroll_log(1),
+            KeyCode::Char(']') => self.scroll_log(-1),
+            KeyCode::Char('m') => self.toggle_memory_view(),
+            KeyCode::Char('M') => self.show_heatmap = !self.show_heatmap,
+            KeyCode::Char('H') => self.show_key_heatmap = !self.show_key_heatmap,
+            KeyCode::Char('k') => self.tab_mut().mark_state(),
+            KeyCode::Char('K') => self.tab_mut().marked = None,
+            KeyCode::Enter => self.start_editing(),
+            KeyCode::PageUp => self.page(-1),
+            KeyCode::PageDown => self.page(1),
+            KeyCode::F(slot @ 1..=STATE_SLOTS) => self.tab_mut().select_slot(slot),
+            KeyCode::F(5) => self.tab_mut().save_state(),
+            KeyCode::Char('S') => self.tab_mut().screenshot(),
+            KeyCode::Char('R') => self.tab_mut().toggle_recording(),
+            KeyCode::Char('F') => self.tab_mut().toggle_phosphor(),
+            KeyCode::F(9) => self.tab_mut().load_state(),
+            KeyCode::Left => self.tab_mut().scrub(-1),
+            KeyCode::Right => self.tab_mut().scrub(1),
+            KeyCode::Home => self.tab_mut().scrub(isize::MIN),
+            KeyCode::End => self.tab_mut().scrub(isize::MAX),
+            KeyCode::Up => self.tab_mut().move_program_cursor(-1),
+            KeyCode::Down => self.tab_mut().move_program_cursor(1),
+            KeyCode::Char('b') => self.tab_mut().toggle_breakpoint(),
+            KeyCode::Char('J') => self.tab_mut().skip_instruction(),
+            KeyCode::Char('Q') => self.toggle_macro_recording(),
+            KeyCode::Char(c @ ('n' | 'o' | 'u')) => self.tab_mut().step_key(c),
+            KeyCode::Char('.') => self.tab_mut().advance_frame(),
+            KeyCode::Backspace => self.tab_mut().reset(),
+            KeyCode::Tab => self.active = (self.active + 1) % tab_count,
+            KeyCode::BackTab => self.active = (self.active + tab_count - 1) % tab_count,
+            // scripted input only
+            _ if self.tab().script.is_some() => {}
+            KeyCode::Char('@') => self.play_recorded(),
+            KeyCode::Char(c) if self.config.macros.contains_key(&c) => {
+                let input_macro = &self.config.macros[&c];
+                self.tabs[self.active].play(input_macro);
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Q, start recording the keypad, or stop and keep what was recorded as the
+    /// rom's macro
+    fn toggle_macro_recording(&mut self) {
+        let tab = self.tab_mut();
+        let Some(mut frames) = tab.macro_frames.take() else {
+            tab.macro_frames = Some(Vec::new());
+            tab.report("Recording the keypad, Q to stop".to_string());
+            return;
+        };
+        // the wait for Q after the last key isn't part of it
+        while frames.last() == Some(&None) {
+            frames.pop();
+        }
+        let input_macro = InputMacro::from_frames(frames);
+        if input_macro.is_empty() {
+            tab.report("No keys were pressed, nothing was recorded".to_string());
+            return;
+        }
+        let name = tab.chip8.rom.name().to_string();
+        let status = format!("Recorded {} frames, @ plays them", input_macro.len());
+        self.recorded.roms.insert(name, input_macro);
+        match self.recorded.save(None) {
+            Ok(()) => self.tab_mut().report(status),
+            Err(e) => self
+                .tab_mut()
+                .report_at(Level::Warn, format!("Could not save the macro: {e}")),
+        }
+    }
+
+    /// @, play the macro recorded for the active tab's rom
+    fn play_recorded(&mut self) {
+        let tab = &mut self.tabs[self.active];
+        match self.recorded.roms.get(tab.chip8.rom.name()) {
+            Some(input_macro) => tab.play(input_macro),
+            None => tab.report("No macro recorded for this rom, Q records one".to_string()),
+        }
+    }
+
+    /// The keypad key `code` presses in the active tab, unless it plays a macro,
+    /// is one of the frontend's own or the tab's input is scripted
+    fn keypad(&self, code: KeyCode) -> Option<u8> {
+        match code {
+            KeyCode::Char(c)
+                if !self.config.macros.contains_key(&c)
+                    && !keymap::FRONTEND_KEYS.contains(c)
+                    && self.tab().script.is_none() =>
+            {
+                self.tab().keymap.key(c)
+            }
+            _ => None,
+        }
+    }
+
+    /// Clicks on the keypad press its keys until let go, clicks on the program
+    /// set and clear breakpoints, and the wheel scrolls the memory view
+    fn on_mouse(&mut self, mouse: MouseEvent) {
+        let at = Position::new(mouse.column, mouse.row);
+        let hitboxes = self.hitboxes.borrow();
+        let key = HexInput::key_at(hitboxes.input, at.x, at.y);
+        let address = match hitboxes.program.contains(at) {
+            true => hitboxes
+                .program_rows
+                .get((at.y - hitboxes.program.y) as usize)
+                .copied()
+                .flatten(),
+            false => None,
+        };
+        let row = match hitboxes.memory.contains(at) {
+            true => MemoryView::row_len(hitboxes.memory.width) as i32,
+            false => 0,
+        };
+        let program = hitboxes.program.contains(at);
+        drop(hitboxes);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(key) = key.filter(|_| self.tab().script.is_none()) {
+                    self.tab_mut().hold(key);
+                    self.mouse_key = Some(key);
+                } else if let Some(address) = address {
+                    self.tab_mut().toggle_breakpoint_at(address);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some(key) = self.mouse_key.take() {
+                    self.tab_mut().release(key);
+                }
+            }
+            MouseEventKind::ScrollUp if program => self.tab_mut().scroll_program(-WHEEL_LINES),
+            MouseEventKind::ScrollDown if program => self.tab_mut().scroll_program(WHEEL_LINES),
+            MouseEventKind::ScrollUp => self.scroll_memory_view(-row),
+            MouseEventKind::ScrollDown => self.scroll_memory_view(row),
+            _ => {}
+        }
+    }
+
+    /// The terminal reports key releases, so keys stay held until then
+    fn use_key_releases(&mut self) {
+        self.key_releases = true;
+        for tab in &mut self.tabs {
+            tab.key_hold = None;
+        }
+    }
+
+    /// Show memory from the row of the PC, or the program again
+    fn toggle_memory_view(&mut self) {
+        self.memory_view = match self.memory_view {
+            Some(_) => None,
+            None => Some(self.tab().chip8.program_counter & !0xF),
+        };
+    }
+
+    /// Scroll the log panel `by` entries back, negative towards the newest
+    fn scroll_log(&mut self, by: isize) {
+        let back = self.log_scroll.saturating_add_signed(by);
+        self.log_scroll = back.min(self.log.len().saturating_sub(1));
+    }
+    /// Draw the display with the next marker
+    fn cycle_marker(&mut self) {
+        self.marker = self.marker.next();
+        let status = format!("Drawing pixels in {}", self.marker);
+        self.tab_mut().report(status);
+    }
+    /// Edit the memory in view, or the registers when it isn't shown, only while
+    /// paused
+    fn start_editing(&mut self) {
+        if let Mode::Running = self.tab().mode {
+            return;
+        }
+        self.editor = Some(Editor::new(match self.memory_view {
+            Some(start) => EditCursor::Memory(start),
+            None => EditCursor::Register(Target::V(0)),
+        }));
+    }
+    /// Keys while editing: arrows move, hex digits write, Tab switches between
+    /// memory and the registers and Esc stops
+    fn edit(&mut self, key: KeyCode) {
+        let Some(mut editor) = self.editor.take() else {
+            return;
+        };
+        let chip8 = &mut self.tabs[self.active].chip8;
+        // registers are four columns of four, then a row of three
+        let (row, column) = match editor.cursor {
+            EditCursor::Memory(_) => (16, 1),
+            EditCursor::Register(_) => (1, 4),
+        };
+        match key {
+            KeyCode::Esc => return,
+            KeyCode::Enter => {
+                if let Some(written) = editor.commit(chip8) {
+                    self.console_output = Some(written);
+                }
+            }
+            KeyCode::Up => editor.move_by(-row, chip8),
+            KeyCode::Down => editor.move_by(row, chip8),
+            KeyCode::Left => editor.move_by(-column, chip8),
+            KeyCode::Right => editor.move_by(column, chip8),
+            KeyCode::PageUp => editor.move_by(-MEMORY_PAGE, chip8),
+            KeyCode::PageDown => editor.move_by(MEMORY_PAGE, chip8),
+            KeyCode::Tab => {
+                let start = self.memory_view.unwrap_or(chip8.program_counter & !0xF);
+                editor = Editor::new(match editor.cursor {
+                    EditCursor::Memory(_) => EditCursor::Register(Target::V(0)),
+                    EditCursor::Register(_) => EditCursor::Memory(start),
+                });
+            }
+            KeyCode::Char(c) => {
+                if let Some(written) = editor.type_digit(c, chip8) {
+                    self.console_output = Some(written);
+                }
+            }
+            _ => {}
+        }
+        // the memory view follows the cursor
+        if let EditCursor::Memory(address) = editor.cursor {
+            let start = self.memory_view.get_or_insert(address & !0xF);
+            if address < *start || address >= start.saturating_add(MEMORY_PAGE as u16) {
+                *start = address & !0xF;
+            }
+        }
+        self.editor = Some(editor);
+    }
+    /// PgUp and PgDn, through the memory view while it's open and otherwise
+    /// the program
+    fn page(&mut self, pages: i32) {
+        match self.memory_view {
+            Some(_) => self.scroll_memory_view(pages * MEMORY_PAGE),
+            None => self.tab_mut().scroll_program(pages * PROGRAM_PAGE),
+        }
+    }
+
+    fn scroll_memory_view(&mut self, by: i32) {
+        let end = self.tab().chip8.memory.len() as i32 - MEMORY_PAGE;
+        if let Some(start) = &mut self.memory_view {
+            *start = (*start as i32 + by).clamp(0, end.max(0)) as u16;
+        }
+    }
+
+    fn on_tick(&mut self) {
+        self.tick_count += 1;
+        let tick = self.tick_count;
+        let tab = self.tab_mut();
+        let failed = tab.error.is_some();
+        tab.on_tick();
+        // the log has the error and what led to it
+        let failed = !failed && tab.error.is_some();
+        for watch in &mut tab.watches {
+            let value = watch.expression.evaluate(&tab.chip8);
+            if value != watch.value {
+                watch.value = value;
+                watch.changed_at = Some(tick);
+            }
+        }
+        if failed {
+            self.show_log = true;
+            self.log_scroll = 0;
+        }
     }
-}
-fn style_instruction<'a>(pc: usize, addr: usize, b1: u8, b2: u8) -> Line<'a> {
-    let line_count = Span::from(format!("{addr:#4x}  ")).dim();
-
-    let instruction = Span::from(format!("{b1:2x} {b2:2x}"));
-    let (line_count, instruction) = match addr.cmp(&pc) {
-        Ordering::Less => (line_count.dim(), instruction.dim()),
-        Ordering::Equal => (line_count.green(), instruction.green()),
-        Ordering::Greater => (line_count.dim(), instruction),
-    };
-    Line::from(vec![line_count, instruction])
 }
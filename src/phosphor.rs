@@ -0,0 +1,74 @@
+//! Phosphor decay
+//!
+//! Roms erase a sprite by drawing it again, so anything that moves is unlit for
+//! part of every frame and flickers. The phosphor of a CRT glowed on for a while
+//! after the beam left it, which hid that. [`Phosphor`] keeps how brightly each
+//! pixel still glows, full while lit and fading out over a few frames after.
+use crate::chip8::Chip8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phosphor {
+    /// frames a pixel takes to fade out
+    frames: u8,
+    width: usize,
+    height: usize,
+    /// row by row, 0 dark to `frames` lit
+    glow: Vec<u8>,
+}
+
+impl Phosphor {
+    /// Pixels fading out over `frames` frames of 60Hz, at least one
+    pub fn new(frames: u8) -> Self {
+        Phosphor {
+            frames: frames.max(1),
+            width: 0,
+            height: 0,
+            glow: Vec::new(),
+        }
+    }
+
+    /// Frames a pixel takes to fade out
+    pub fn frames(&self) -> u8 {
+        self.frames
+    }
+
+    /// Take in the display of `chip8` as it is at the end of `ticks` frames
+    pub fn update(&mut self, chip8: &Chip8, ticks: u32) {
+        let (width, height) = (chip8.width(), chip8.height());
+        // a change of resolution starts afresh
+        if (width, height) != (self.width, self.height) {
+            *self = Phosphor {
+                width,
+                height,
+                glow: vec![0; width * height],
+                ..*self
+            };
+        }
+        let fade = ticks.min(u8::MAX.into()) as u8;
+        for y in 0..height {
+            for x in 0..width {
+                let glow = &mut self.glow[y * width + x];
+                *glow = match chip8.pixel(x, y) {
+                    true => self.frames,
+                    false => glow.saturating_sub(fade),
+                };
+            }
+        }
+    }
+
+    /// How brightly the pixel at `x`, `y` glows, from 0 dark to 1 lit
+    pub fn brightness(&self, x: usize, y: usize) -> f32 {
+        match (x < self.width, self.glow.get(y * self.width + x)) {
+            (true, Some(&glow)) => f32::from(glow) / f32::from(self.frames),
+            _ => 0.0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
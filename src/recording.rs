@@ -0,0 +1,163 @@
+//! Recording the display
+//!
+//! Frames are captured at the 60Hz of the timers, and only kept when the
+//! display changed, the frame before simply showing for longer. Written out as
+//! an animated GIF, or an APNG whose frame delays keep the exact 1/60s pacing
+//! that GIF's hundredths of a second can only round to.
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufWriter, Error, ErrorKind, Write},
+    path::Path,
+};
+
+use crate::chip8::Chip8;
+
+/// One picture of the display and how long it showed
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Frame {
+    width: usize,
+    height: usize,
+    /// row by row
+    pixels: Vec<bool>,
+    /// 60Hz ticks it showed for
+    ticks: u32,
+}
+
+impl Frame {
+    /// Whether the pixel at `x`, `y` of a `width` by `height` picture is lit,
+    /// stretching or shrinking this frame's own to fit
+    fn pixel(&self, x: usize, y: usize, width: usize, height: usize) -> bool {
+        self.pixels[y * self.height / height * self.width + x * self.width / width]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    frames: Vec<Frame>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture the display of `chip8` as it is for `ticks` frames of 60Hz
+    pub fn capture(&mut self, chip8: &Chip8, ticks: u32) {
+        let (width, height) = (chip8.width(), chip8.height());
+        let pixels: Vec<bool> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| chip8.pixel(x, y)))
+            .collect();
+        match self.frames.last_mut() {
+            Some(last) if last.pixels == pixels && last.width == width => {
+                last.ticks += ticks;
+            }
+            _ => self.frames.push(Frame {
+                width,
+                height,
+                pixels,
+                ticks,
+            }),
+        }
+    }
+
+    /// Different pictures captured, a still display being one
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 60Hz ticks captured
+    pub fn ticks(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.ticks).sum()
+    }
+
+    /// The size of the first frame, the others fit to it, `scale` times over
+    fn size(&self, scale: usize) -> (usize, usize) {
+        self.frames
+            .first()
+            .map_or((0, 0), |frame| (frame.width * scale, frame.height * scale))
+    }
+
+    /// Each frame as `width` by `height` pixels, row by row
+    fn scaled(&self, scale: usize) -> impl Iterator<Item = (&Frame, Vec<bool>)> {
+        let (width, height) = self.size(scale);
+        self.frames.iter().map(move |frame| {
+            let pixels = (0..height)
+                .flat_map(|y| (0..width).map(move |x| frame.pixel(x, y, width, height)))
+                .collect();
+            (frame, pixels)
+        })
+    }
+
+    /// Write an animated GIF that loops, each frame's delay rounded to the
+    /// hundredth of a second without the rounding adding up
+    pub fn write_gif(&self, scale: usize, writer: impl Write) -> Result<(), Error> {
+        let (width, height) = self.size(scale);
+        let palette = [0, 0, 0, 0xFF, 0xFF, 0xFF];
+        let mut encoder = gif::Encoder::new(writer, width as u16, height as u16, &palette)
+            .map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        let mut ticks = 0;
+        for (frame, pixels) in self.scaled(scale) {
+            let start = ticks * 100 / 60;
+            ticks += frame.ticks;
+            let buffer = pixels.into_iter().map(u8::from).collect();
+            let frame = gif::Frame {
+                width: width as u16,
+                height: height as u16,
+                buffer: Cow::Owned(buffer),
+                delay: (ticks * 100 / 60 - start) as u16,
+                ..gif::Frame::default()
+            };
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    /// Write an animated PNG that loops
+    pub fn write_apng(&self, scale: usize, writer: impl Write) -> Result<(), Error> {
+        let (width, height) = self.size(scale);
+        let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(self.frames.len() as u32, 0)
+            .map_err(io::Error::other)?;
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        for (frame, pixels) in self.scaled(scale) {
+            let ticks = frame.ticks.min(u16::MAX.into()) as u16;
+            writer
+                .set_frame_delay(ticks, 60)
+                .map_err(io::Error::other)?;
+            let data: Vec<u8> = pixels
+                .into_iter()
+                .map(|lit| if lit { 0xFF } else { 0 })
+                .collect();
+            writer.write_image_data(&data).map_err(io::Error::other)?;
+        }
+        writer.finish().map_err(io::Error::other)
+    }
+
+    /// Save to `path`, an APNG for `.png` or `.apng` and otherwise a GIF
+    pub fn save(&self, scale: usize, path: &Path) -> Result<(), Error> {
+        if self.is_empty() || scale == 0 {
+            let reason = match scale {
+                0 => "scale must be at least 1",
+                _ => "nothing was recorded",
+            };
+            return Err(Error::new(ErrorKind::InvalidInput, reason));
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("png" | "apng") => self.write_apng(scale, &mut writer)?,
+            _ => self.write_gif(scale, &mut writer)?,
+        }
+        writer.flush()
+    }
+}
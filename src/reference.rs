@@ -0,0 +1,442 @@
+//! A deliberately simple reference interpreter
+//!
+//! This is a second, independent implementation of the instruction set, written
+//! straight from the spec with masks and one pixel at a time. It shares the `Chip8`
+//! state type so both interpreters can be run in lockstep with [`lockstep`], which
+//! reports the first step where they disagree.
+use std::fmt;
+
+use crate::chip8::{Chip8, Chip8Error, Instruction, State, WIDTH_BYTE};
+
+/// Execute one instruction, `random` is the byte Cxkk masks
+pub fn step(state: &mut Chip8, random: u8) -> Result<(), Chip8Error> {
+    // Fx0A first waits for a key to go down, then for that key to come back up
+    match state.state {
+        State::Running => {}
+        // nothing until the timer tick
+        State::WaitingForVblank => return Ok(()),
+        State::Halted => return Ok(()),
+        State::WaitingForKey { register, key } => {
+            let lowest = (0..16).find(|k| state.keys & (1 << k) != 0);
+            match (key, lowest) {
+                (None, Some(_)) => {
+                    state.state = State::WaitingForKey {
+                        register,
+                        key: lowest,
+                    }
+                }
+                (Some(k), _) if state.keys & (1 << k) == 0 => {
+                    state.registers[register as usize] = k;
+                    state.state = State::Running;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+    }
+    let address = state.program_counter;
+    let pc = address as usize;
+    if pc + 1 >= state.memory.len() {
+        return Err(Chip8Error::MemoryOutOfBounds { address });
+    }
+    let opcode = (state.memory[pc] as u16) << 8 | state.memory[pc + 1] as u16;
+    let x = ((opcode & 0x0F00) >> 8) as usize;
+    let y = ((opcode & 0x00F0) >> 4) as usize;
+    let n = (opcode & 0x000F) as usize;
+    let kk = (opcode & 0x00FF) as u8;
+    let nnn = opcode & 0x0FFF;
+    let quirks = state.quirks;
+    // the register 8xy6/8xyE shift
+    let shifted = if quirks.shift_uses_vy { y } else { x };
+
+    // bytes at I past the 4K wrap around, or fault without the quirk
+    let size = state.memory.len();
+    let memory_at = |len: usize| {
+        let i = state.i as usize;
+        match quirks.wrap_memory || i + len <= state.memory.len() {
+            true => Ok(move |offset: usize| (i + offset) % size),
+            false => Err(Chip8Error::MemoryFault {
+                address,
+                instruction: Instruction::decode(opcode),
+                index: i.max(size),
+            }),
+        }
+    };
+
+    let mut next = state.program_counter.wrapping_add(2);
+    let skip = state.program_counter.wrapping_add(4);
+
+    if opcode == 0x00E0 || (opcode == 0x0230 && state.two_page) {
+        for byte in state.display.iter_mut() {
+            *byte = 0;
+        }
+    } else if opcode & 0xFFF0 == 0x00C0 {
+        let (width, height) = (state.width(), state.height());
+        for y in (0..height).rev() {
+            for x in 0..width {
+                let lit = y >= n && get(state, x, y - n);
+                set(state, x, y, lit);
+            }
+        }
+    } else if opcode == 0x00FB {
+        let (width, height) = (state.width(), state.height());
+        for y in 0..height {
+            for x in (0..width).rev() {
+                let lit = x >= 4 && get(state, x - 4, y);
+                set(state, x, y, lit);
+            }
+        }
+    } else if opcode == 0x00FC {
+        let (width, height) = (state.width(), state.height());
+        for y in 0..height {
+            for x in 0..width {
+                let lit = x + 4 < width && get(state, x + 4, y);
+                set(state, x, y, lit);
+            }
+        }
+    } else if opcode == 0x00FD {
+        next = state.program_counter;
+        state.state = State::Halted;
+    } else if opcode == 0x00FE || opcode == 0x00FF {
+        state.hires = opcode == 0x00FF;
+        for byte in state.display.iter_mut() {
+            *byte = 0;
+        }
+    } else if opcode == 0x00EE {
+        if state.stack_pointer == 0 {
+            return Err(Chip8Error::StackUnderflow { address });
+        }
+        next = state.stack[state.stack_pointer as usize].wrapping_add(2);
+        state.stack_pointer = state.stack_pointer.wrapping_sub(1);
+    } else if opcode & 0xF000 == 0x1000 {
+        next = nnn;
+        if nnn == address {
+            state.state = State::Halted;
+        }
+    } else if opcode & 0xF000 == 0x2000 {
+        if state.stack_pointer == 15 {
+            return Err(Chip8Error::StackOverflow { address });
+        }
+        state.stack_pointer = state.stack_pointer.wrapping_add(1);
+        state.stack[state.stack_pointer as usize] = state.program_counter;
+        next = nnn;
+    } else if opcode & 0xF000 == 0x3000 {
+        if state.registers[x] == kk {
+            next = skip;
+        }
+    } else if opcode & 0xF000 == 0x4000 {
+        if state.registers[x] != kk {
+            next = skip;
+        }
+    } else if opcode & 0xF000 == 0x5000 {
+        if state.registers[x] == state.registers[y] {
+            next = skip;
+        }
+    } else if opcode & 0xF000 == 0x6000 {
+        state.registers[x] = kk;
+    } else if opcode & 0xF000 == 0x7000 {
+        state.registers[x] = state.registers[x].wrapping_add(kk);
+    } else if opcode & 0xF00F == 0x8000 {
+        state.registers[x] = state.registers[y];
+    } else if opcode & 0xF00F == 0x8001 {
+        state.registers[x] |= state.registers[y];
+        if quirks.vf_reset {
+            state.registers[0xF] = 0;
+        }
+    } else if opcode & 0xF00F == 0x8002 {
+        state.registers[x] &= state.registers[y];
+        if quirks.vf_reset {
+            state.registers[0xF] = 0;
+        }
+    } else if opcode & 0xF00F == 0x8003 {
+        state.registers[x] ^= state.registers[y];
+        if quirks.vf_reset {
+            state.registers[0xF] = 0;
+        }
+    } else if opcode & 0xF00F == 0x8004 {
+        let sum = state.registers[x] as u16 + state.registers[y] as u16;
+        state.registers[x] = (sum % 256) as u8;
+        state.registers[0xF] = if sum > 255 { 1 } else { 0 };
+    } else if opcode & 0xF00F == 0x8005 {
+        let (vx, vy) = (state.registers[x], state.registers[y]);
+        state.registers[x] = vx.wrapping_sub(vy);
+        state.registers[0xF] = if vx >= vy { 1 } else { 0 };
+    } else if opcode & 0xF00F == 0x8006 {
+        let value = state.registers[shifted];
+        state.registers[x] = value / 2;
+        state.registers[0xF] = value % 2;
+    } else if opcode & 0xF00F == 0x8007 {
+        let (vx, vy) = (state.registers[x], state.registers[y]);
+        state.registers[x] = vy.wrapping_sub(vx);
+        state.registers[0xF] = if vy >= vx { 1 } else { 0 };
+    } else if opcode & 0xF00F == 0x800E {
+        let value = state.registers[shifted];
+        state.registers[x] = value.wrapping_mul(2);
+        state.registers[0xF] = if value >= 128 { 1 } else { 0 };
+    } else if opcode & 0xF00F == 0x9000 {
+        if state.registers[x] != state.registers[y] {
+            next = skip;
+        }
+    } else if opcode & 0xF000 == 0xA000 {
+        state.i = nnn;
+    } else if opcode & 0xF000 == 0xB000 {
+        let offset = if quirks.jump_uses_vx { x } else { 0 };
+        next = nnn + state.registers[offset] as u16;
+    } else if opcode & 0xF000 == 0xC000 {
+        state.registers[x] = random & kk;
+    } else if opcode & 0xF000 == 0xD000 {
+        let at = memory_at(if n == 0 { 32 } else { n })?;
+        draw(state, x, y, n, at);
+        if quirks.display_wait {
+            state.state = State::WaitingForVblank;
+        }
+    } else if opcode & 0xF0FF == 0xE09E {
+        if state.keys & (1 << (state.registers[x] % 16)) != 0 {
+            next = skip;
+        }
+    } else if opcode & 0xF0FF == 0xE0A1 {
+        if state.keys & (1 << (state.registers[x] % 16)) == 0 {
+            next = skip;
+        }
+    } else if opcode & 0xF0FF == 0xF007 {
+        state.registers[x] = state.delay;
+    } else if opcode & 0xF0FF == 0xF00A {
+        state.state = State::WaitingForKey {
+            register: x as u8,
+            key: None,
+        };
+    } else if opcode & 0xF0FF == 0xF015 {
+        state.delay = state.registers[x];
+    } else if opcode & 0xF0FF == 0xF018 {
+        state.sound = state.registers[x];
+    } else if opcode & 0xF0FF == 0xF01E {
+        state.i = state.i.wrapping_add(state.registers[x] as u16) & 0x0FFF;
+    } else if opcode & 0xF0FF == 0xF029 {
+        state.i = (state.registers[x] % 16) as u16 * 5;
+    } else if opcode & 0xF0FF == 0xF030 {
+        // the large font follows the 16 five byte small characters
+        state.i = 80 + (state.registers[x] % 16) as u16 * 10;
+    } else if opcode & 0xF0FF == 0xF033 {
+        let at = memory_at(3)?;
+        let vx = state.registers[x];
+        state.memory[at(0)] = vx / 100;
+        state.memory[at(1)] = vx / 10 % 10;
+        state.memory[at(2)] = vx % 10;
+    } else if opcode & 0xF0FF == 0xF055 {
+        let at = memory_at(x + 1)?;
+        for r in 0..=x {
+            state.memory[at(r)] = state.registers[r];
+        }
+        if quirks.load_store_increments_i {
+            state.i = state.i.wrapping_add(x as u16 + 1) & 0x0FFF;
+        }
+    } else if opcode & 0xF0FF == 0xF065 {
+        let at = memory_at(x + 1)?;
+        for r in 0..=x {
+            state.registers[r] = state.memory[at(r)];
+        }
+        if quirks.load_store_increments_i {
+            state.i = state.i.wrapping_add(x as u16 + 1) & 0x0FFF;
+        }
+    } else if opcode & 0xF0FF == 0xF075 {
+        for r in 0..=x {
+            state.flags[r] = state.registers[r];
+        }
+    } else if opcode & 0xF0FF == 0xF085 {
+        for r in 0..=x {
+            state.registers[r] = state.flags[r];
+        }
+    } else {
+        return Err(Chip8Error::UnknownOpcode { address, opcode });
+    }
+
+    state.program_counter = next;
+    Ok(())
+}
+
+fn tick_timers(state: &mut Chip8) {
+    if state.state == State::WaitingForVblank {
+        state.state = State::Running;
+    }
+    if state.delay > 0 {
+        state.delay -= 1;
+    }
+    if state.sound > 0 {
+        state.sound -= 1;
+    }
+}
+
+fn get(state: &Chip8, x: usize, y: usize) -> bool {
+    state.display[y * WIDTH_BYTE + x / 8] & (0x80 >> (x % 8)) != 0
+}
+
+fn set(state: &mut Chip8, x: usize, y: usize, lit: bool) {
+    let mask = 0x80 >> (x % 8);
+    let byte = &mut state.display[y * WIDTH_BYTE + x / 8];
+    if lit {
+        *byte |= mask;
+    } else {
+        *byte &= !mask;
+    }
+}
+
+/// Xor a sprite onto the display one pixel at a time, clipping or wrapping at the edges.
+/// With `n` zero the sprite is 16x16, stored as two bytes per row. `at` gives the
+/// memory index of a sprite byte.
+fn draw(state: &mut Chip8, x: usize, y: usize, n: usize, at: impl Fn(usize) -> usize) {
+    let (width, height) = (state.width(), state.height());
+    let left = state.registers[x] as usize % width;
+    let top = state.registers[y] as usize % height;
+    let (rows, columns) = if n == 0 { (16, 16) } else { (n, 8) };
+    let mut collision = false;
+    for row in 0..rows {
+        for column in 0..columns {
+            let (mut px, mut py) = (left + column, top + row);
+            if state.quirks.wrap_sprites {
+                px %= width;
+                py %= height;
+            }
+            if px >= width || py >= height {
+                continue;
+            }
+            let byte = state.memory[at(row * columns / 8 + column / 8)];
+            if byte & (0x80 >> (column % 8)) == 0 {
+                continue;
+            }
+            if get(state, px, py) {
+                collision = true;
+            }
+            let lit = !get(state, px, py);
+            set(state, px, py, lit);
+        }
+    }
+    state.registers[0xF] = if collision { 1 } else { 0 };
+}
+
+/// The first point where the reference and the emulator disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// how many instructions had already executed in agreement
+    pub step: usize,
+    /// address and opcode of the instruction that diverged
+    pub address: u16,
+    pub opcode: u16,
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "step {}: {:#06x} at {:#05x} diverged on {}, reference {} but emulator {}",
+            self.step, self.opcode, self.address, self.field, self.expected, self.actual
+        )
+    }
+}
+
+/// Step `chip8` and the reference interpreter side by side for up to `steps`
+/// instructions, `input` picks the keys held before each step as a bitmask.
+/// Stops early once both agree the program failed.
+pub fn lockstep(
+    chip8: &mut Chip8,
+    steps: usize,
+    mut input: impl FnMut(usize) -> u16,
+) -> Result<(), Divergence> {
+    for step in 0..steps {
+        chip8.keys = input(step);
+        let mut reference = chip8.clone();
+        let address = chip8.program_counter;
+        let pc = address as usize;
+        let opcode = match chip8.memory.get(pc..pc + 2) {
+            Some(&[high, low]) => (high as u16) << 8 | low as u16,
+            _ => 0,
+        };
+
+        // one timer tick per instruction, like the other headless runs
+        let result = chip8.step().map(|_| ());
+        chip8.tick_timers();
+        // the emulator's random byte is unknown, but Cxkk left `random & kk` in Vx,
+        // and feeding that back through the mask reproduces it
+        let random = chip8.registers[((opcode & 0x0F00) >> 8) as usize];
+        let expected = self::step(&mut reference, random);
+        tick_timers(&mut reference);
+
+        if expected != result {
+            return Err(Divergence {
+                step,
+                address,
+                opcode,
+                field: "result",
+                expected: format!("{expected:?}"),
+                actual: format!("{result:?}"),
+            });
+        }
+
+        if let Some((field, expected, actual)) = compare(&reference, chip8) {
+            return Err(Divergence {
+                step,
+                address,
+                opcode,
+                field,
+                expected,
+                actual,
+            });
+        }
+        if result.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Compare the architectural state of two machines, field by field
+pub(crate) fn compare(expected: &Chip8, actual: &Chip8) -> Option<(&'static str, String, String)> {
+    fn differs<T: PartialEq + fmt::Debug>(
+        field: &'static str,
+        expected: &T,
+        actual: &T,
+    ) -> Option<(&'static str, String, String)> {
+        (expected != actual).then(|| (field, format!("{expected:x?}"), format!("{actual:x?}")))
+    }
+    differs(
+        "program counter",
+        &expected.program_counter,
+        &actual.program_counter,
+    )
+    .or_else(|| differs("registers", &expected.registers, &actual.registers))
+    .or_else(|| differs("i", &expected.i, &actual.i))
+    .or_else(|| differs("delay", &expected.delay, &actual.delay))
+    .or_else(|| differs("sound", &expected.sound, &actual.sound))
+    .or_else(|| {
+        differs(
+            "stack pointer",
+            &expected.stack_pointer,
+            &actual.stack_pointer,
+        )
+    })
+    .or_else(|| differs("stack", &expected.stack, &actual.stack))
+    .or_else(|| differs("hires", &expected.hires, &actual.hires))
+    .or_else(|| differs("two page", &expected.two_page, &actual.two_page))
+    .or_else(|| differs("flags", &expected.flags, &actual.flags))
+    .or_else(|| differs("state", &expected.state, &actual.state))
+    .or_else(|| {
+        let address =
+            (0..expected.memory.len()).find(|&a| expected.memory[a] != actual.memory[a])?;
+        Some((
+            "memory",
+            format!("{:#04x} at {address:#05x}", expected.memory[address]),
+            format!("{:#04x}", actual.memory[address]),
+        ))
+    })
+    .or_else(|| {
+        let byte =
+            (0..expected.display.len()).find(|&b| expected.display[b] != actual.display[b])?;
+        Some((
+            "display",
+            format!("{:08b} at byte {byte}", expected.display[byte]),
+            format!("{:08b}", actual.display[byte]),
+        ))
+    })
+}
@@ -0,0 +1,90 @@
+//! Rewind
+//!
+//! The last few thousand machine states, one per instruction, so a paused program
+//! can be stepped backwards to just before whatever went wrong. States are whole
+//! copies of the machine, a few kilobytes each, which keeps going back trivially
+//! exact at the cost of memory the history bounds.
+use std::collections::VecDeque;
+
+use crate::chip8::{Chip8, Chip8Error, StepOutcome};
+
+/// Bounded history of machine states, the oldest is dropped to make room
+#[derive(Debug, Clone)]
+pub struct History {
+    states: VecDeque<Chip8>,
+    capacity: usize,
+}
+
+impl History {
+    /// Remembering at most `capacity` states
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            states: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Remember `chip8`, as it is before the next step
+    pub fn push(&mut self, chip8: Chip8) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.states.len() == self.capacity {
+            self.states.pop_front();
+        }
+        self.states.push_back(chip8);
+    }
+
+    /// Take back the most recent state
+    pub fn pop(&mut self) -> Option<Chip8> {
+        self.states.pop_back()
+    }
+
+    /// The state `index` steps after the oldest, to look at without going back to it
+    pub fn get(&self, index: usize) -> Option<&Chip8> {
+        self.states.get(index)
+    }
+
+    /// Forget all but the oldest `len` states, the ones after a state the program
+    /// was scrubbed back to and resumed from
+    pub fn truncate(&mut self, len: usize) {
+        self.states.truncate(len);
+    }
+
+    /// Forget everything, after the machine jumped somewhere the history doesn't lead
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+impl Chip8 {
+    /// Step like [`Chip8::step`], remembering the state before it in `history`.
+    /// A step that fails changes nothing and isn't remembered.
+    pub fn step_recorded(&mut self, history: &mut History) -> Result<StepOutcome, Chip8Error> {
+        let before = self.clone_without_coverage();
+        let outcome = self.step()?;
+        history.push(before);
+        Ok(outcome)
+    }
+
+    /// Go back to the state before the last recorded step, false if there is none.
+    /// What [`Chip8::take_debugging`] carries over stays as it is now.
+    pub fn step_back(&mut self, history: &mut History) -> bool {
+        match history.pop() {
+            Some(mut previous) => {
+                previous.take_debugging(self);
+                *self = previous;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -1,22 +1,70 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::{
     fs,
     path::{Path, PathBuf},
 };
-#[derive(Clone, PartialEq)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip8::hex;
+
+/// Without std a rom has a name rather than a path
+#[cfg(not(feature = "std"))]
+type PathBuf = String;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Rom {
     path: PathBuf,
+    #[serde(with = "hex")]
     pub contents: Vec<u8>,
 }
 impl Rom {
+    #[cfg(feature = "std")]
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
         let path_buf = path.as_ref().to_path_buf();
-        let contents = fs::read(&path_buf).unwrap();
+        let contents = fs::read(&path_buf)?;
         Ok(Self {
             path: path_buf,
             contents,
         })
     }
+    /// Build a rom from an in-memory image, `name` stands in for the file path
+    pub fn from_bytes(name: &str, contents: Vec<u8>) -> Self {
+        Self {
+            path: PathBuf::from(name),
+            contents,
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// FNV-1a of the contents, to tell roms apart whatever their file is called
+    pub fn hash(&self) -> u64 {
+        self.contents
+            .iter()
+            .fold(0xcbf29ce484222325, |hash, &byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+    /// SHA-1 of the contents in hex, as rom databases list them
+    #[cfg(feature = "std")]
+    pub fn sha1(&self) -> String {
+        use sha1::{Digest, Sha1};
+        Sha1::digest(&self.contents)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+    #[cfg(feature = "std")]
     pub fn name(&self) -> &str {
         self.path.file_stem().unwrap().to_str().unwrap()
     }
+    #[cfg(not(feature = "std"))]
+    pub fn name(&self) -> &str {
+        &self.path
+    }
 }
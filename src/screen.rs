@@ -0,0 +1,286 @@
+//! The screens of the terminal frontend
+//!
+//! Screens stack: at the bottom the emulator, the display alone, or the
+//! debugger, the display with its panels around it. Z swaps one for the other.
+//! The rom browser, settings and help open over them and close back to what
+//! was underneath. Keys go to the screen on top, and the stack is drawn from
+//! the bottom up, so what is open shows over what it was opened from.
+//!
+//! The screens opened over the others are handled here: moving their
+//! selection, closing them, and drawing them as a [`Menu`] or the [`Help`].
+//! What choosing an entry does is up to the frontend.
+use crossterm::event::KeyCode;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::session::Panels;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    /// the display alone, filling the terminal
+    Emulator,
+    /// the display with the program, registers and input around it
+    Debugger,
+    /// the roms of the library, L, and the one selected
+    RomBrowser { selected: usize },
+    /// the debugger's options, p, and the one selected
+    Settings { selected: usize },
+    /// the keys, ?
+    Help,
+}
+
+impl Screen {
+    /// Whether the screen fills the terminal, rather than opening over another
+    pub fn is_base(&self) -> bool {
+        matches!(self, Screen::Emulator | Screen::Debugger)
+    }
+
+    /// The entry selected, for screens that list some
+    pub fn selected_mut(&mut self) -> Option<&mut usize> {
+        match self {
+            Screen::RomBrowser { selected } | Screen::Settings { selected } => Some(selected),
+            _ => None,
+        }
+    }
+
+    /// The key that opens the screen, and closes it again
+    pub fn key(&self) -> Option<char> {
+        match self {
+            Screen::Emulator | Screen::Debugger => None,
+            Screen::RomBrowser { .. } => Some('L'),
+            Screen::Settings { .. } => Some('p'),
+            Screen::Help => Some('?'),
+        }
+    }
+}
+
+/// What a key did to the screen on top
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// nothing, the emulator or the debugger is on top and has the key
+    Base,
+    /// moved the selection, closed the screen, or was ignored
+    Done,
+    /// Enter, on the entry selected
+    Choose(usize),
+}
+
+/// Screens open, the one at the bottom always filling the terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screens {
+    stack: Vec<Screen>,
+}
+
+impl Screens {
+    /// Only `base`, the emulator or the debugger
+    pub fn new(base: Screen) -> Self {
+        debug_assert!(base.is_base());
+        Screens { stack: vec![base] }
+    }
+
+    /// The emulator for a layout that is all display, the debugger otherwise
+    pub fn of(panels: &Panels) -> Self {
+        match panels.zen {
+            true => Screens::new(Screen::Emulator),
+            false => Screens::new(Screen::Debugger),
+        }
+    }
+
+    pub fn base(&self) -> Screen {
+        self.stack[0]
+    }
+
+    /// Swap the emulator for the debugger or back, under whatever is open
+    pub fn toggle_base(&mut self) {
+        self.stack[0] = match self.stack[0] {
+            Screen::Emulator => Screen::Debugger,
+            _ => Screen::Emulator,
+        };
+    }
+
+    /// The screen keys go to
+    pub fn top(&self) -> Screen {
+        self.stack[self.stack.len() - 1]
+    }
+
+    pub fn top_mut(&mut self) -> &mut Screen {
+        let top = self.stack.len() - 1;
+        &mut self.stack[top]
+    }
+
+    /// Open `screen` over the others, or bring it to the top if it's open
+    /// already, starting afresh
+    pub fn open(&mut self, screen: Screen) {
+        if screen.is_base() {
+            return;
+        }
+        self.stack
+            .retain(|open| std::mem::discriminant(open) != std::mem::discriminant(&screen));
+        self.stack.push(screen);
+    }
+
+    /// Close the screen on top, back to the one it was opened from. The base
+    /// stays.
+    pub fn close(&mut self) -> Option<Screen> {
+        match self.stack.len() {
+            1 => None,
+            _ => self.stack.pop(),
+        }
+    }
+
+    /// From the bottom up, the order to draw them in
+    pub fn iter(&self) -> impl Iterator<Item = Screen> + '_ {
+        self.stack.iter().copied()
+    }
+
+    /// Take `code` on the screen on top, listing `len` entries if it lists any.
+    /// Up and down move the selection, esc or the key that opened it closes it.
+    pub fn on_key(&mut self, code: KeyCode, len: usize) -> KeyAction {
+        let top = self.top();
+        if top.is_base() {
+            return KeyAction::Base;
+        }
+        if code == KeyCode::Esc || matches!(code, KeyCode::Char(c) if top.key() == Some(c)) {
+            self.close();
+            return KeyAction::Done;
+        }
+        let top = self.top_mut();
+        match code {
+            KeyCode::Up | KeyCode::Down => {
+                let by = if code == KeyCode::Up { -1 } else { 1 };
+                if let Some(selected) = top.selected_mut() {
+                    *selected = selected
+                        .saturating_add_signed(by)
+                        .min(len.saturating_sub(1));
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(selected) = top.selected_mut().filter(|_| len > 0) {
+                    return KeyAction::Choose(*selected);
+                }
+            }
+            _ => {}
+        }
+        KeyAction::Done
+    }
+}
+
+/// `width` by `height` in the middle of `area`, as much of it as fits
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height.min(area.height))])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// A list in a box over the middle of the screen, one entry selected, for the
+/// rom browser and the settings
+pub struct Menu<'a> {
+    title: String,
+    items: Vec<Line<'a>>,
+    selected: usize,
+    width: u16,
+    highlight: Color,
+    empty: &'a str,
+}
+
+impl<'a> Menu<'a> {
+    pub fn new(title: impl Into<String>, items: Vec<Line<'a>>, selected: usize) -> Self {
+        Menu {
+            title: title.into(),
+            items,
+            selected,
+            width: 40,
+            highlight: Color::Green,
+            empty: "",
+        }
+    }
+
+    /// Columns wide, borders included
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// The background of the entry selected
+    pub fn highlight(mut self, highlight: Color) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    /// What to say when there are no entries
+    pub fn empty(mut self, empty: &'a str) -> Self {
+        self.empty = empty;
+        self
+    }
+}
+
+impl Widget for Menu<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let height = self.items.len().max(1) as u16 + 2;
+        let area = centered(area, self.width, height);
+        let list = match self.items.is_empty() {
+            true => List::new([Line::from(self.empty).dim()]),
+            false => List::new(self.items),
+        }
+        .block(Block::bordered().title(self.title))
+        .highlight_style(Style::new().black().bg(self.highlight));
+        Clear.render(area, buf);
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        StatefulWidget::render(list, area, buf, &mut state);
+    }
+}
+
+/// The keys and what they do, in two columns in a box over the middle of the
+/// screen
+pub struct Help<'a> {
+    keys: &'a [(&'a str, &'a str)],
+    highlight: Color,
+}
+
+impl<'a> Help<'a> {
+    pub fn new(keys: &'a [(&'a str, &'a str)]) -> Self {
+        Help {
+            keys,
+            highlight: Color::Green,
+        }
+    }
+
+    /// The color of the keys
+    pub fn highlight(mut self, highlight: Color) -> Self {
+        self.highlight = highlight;
+        self
+    }
+}
+
+impl Widget for Help<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = |keys: &[(&str, &str)]| -> Vec<Line> {
+            keys.iter()
+                .map(|&(key, action)| {
+                    Line::from(vec![
+                        Span::from(format!("{key:>9} ")).fg(self.highlight),
+                        Span::from(action.to_string()),
+                    ])
+                })
+                .collect()
+        };
+        let (left, right) = self.keys.split_at(self.keys.len().div_ceil(2));
+        let area = centered(area, 82, left.len() as u16 + 2);
+        Clear.render(area, buf);
+        let block = Block::bordered().title("Keys, ? or esc to close");
+        let [left_area, right_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(block.inner(area));
+        block.render(area, buf);
+        Paragraph::new(lines(left)).render(left_area, buf);
+        Paragraph::new(lines(right)).render(right_area, buf);
+    }
+}
@@ -0,0 +1,99 @@
+//! Pictures of the display
+//!
+//! The display as it is, each pixel scaled up to a square, lit pixels white on
+//! black. PNG for sharing, or PBM, plain text a bug report can quote.
+use std::{
+    fs::File,
+    io::{self, BufWriter, Error, ErrorKind, Write},
+    path::Path,
+};
+
+use crate::chip8::Chip8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Pbm,
+}
+
+impl ImageFormat {
+    /// By the extension of `path`, PNG unless it's `.pbm`
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("pbm") => ImageFormat::Pbm,
+            _ => ImageFormat::Png,
+        }
+    }
+}
+
+/// The display's pixels `scale` times over, row by row
+fn pixels(chip8: &Chip8, scale: usize) -> impl Iterator<Item = impl Iterator<Item = bool> + '_> {
+    (0..chip8.height() * scale)
+        .map(move |y| (0..chip8.width() * scale).map(move |x| chip8.pixel(x / scale, y / scale)))
+}
+
+/// Write the display as an 8 bit grayscale PNG
+pub fn write_png(chip8: &Chip8, scale: usize, writer: impl Write) -> Result<(), Error> {
+    let (width, height) = (chip8.width() * scale, chip8.height() * scale);
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = pixels(chip8, scale)
+        .flatten()
+        .map(|lit| if lit { 0xFF } else { 0 })
+        .collect();
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&data).map_err(io::Error::other)
+}
+
+/// Write the display as a plain PBM, where 1 is black
+pub fn write_pbm(chip8: &Chip8, scale: usize, mut writer: impl Write) -> Result<(), Error> {
+    let (width, height) = (chip8.width() * scale, chip8.height() * scale);
+    writeln!(writer, "P1\n{width} {height}")?;
+    for row in pixels(chip8, scale) {
+        let row: Vec<&str> = row.map(|lit| if lit { "0" } else { "1" }).collect();
+        writeln!(writer, "{}", row.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Save the display to `path`, as a PNG or PBM by its extension
+pub fn save(chip8: &Chip8, scale: usize, path: &Path) -> Result<(), Error> {
+    if scale == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "scale must be at least 1",
+        ));
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    match ImageFormat::of(path) {
+        ImageFormat::Png => write_png(chip8, scale, &mut writer)?,
+        ImageFormat::Pbm => write_pbm(chip8, scale, &mut writer)?,
+    }
+    writer.flush()
+}
+
+/// `PONG-2024-05-17-093012.png` for a screenshot of PONG taken at `time`, in
+/// seconds since the Unix epoch, UTC
+pub fn file_name(rom_name: &str, time: u64) -> String {
+    let (days, seconds) = (time / 86400, time % 86400);
+    let (year, month, day) = civil_date(days as i64);
+    let (hour, minute, second) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    format!("{rom_name}-{year}-{month:02}-{day:02}-{hour:02}{minute:02}{second:02}.png")
+}
+
+/// The year, month and day `days` after 1970-01-01, from Howard Hinnant's
+/// `civil_from_days`
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,157 @@
+//! The last session, saved on exit and offered again on the next launch
+//!
+//! Stored as TOML in the platform data directory, e.g.
+//! `~/.local/share/chipy8/session.toml`.
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chip8::Condition;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub tabs: Vec<TabSession>,
+    /// index of the tab that was showing
+    pub active: usize,
+    pub show_timing: bool,
+    pub panels: Panels,
+}
+
+/// Which panels of the terminal frontend are showing around the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Panels {
+    /// the registers and the call stack
+    pub registers: bool,
+    /// the program, or the memory or profile in its place
+    pub program: bool,
+    pub input: bool,
+    /// only the display, filling the terminal
+    pub zen: bool,
+}
+
+impl Default for Panels {
+    fn default() -> Self {
+        Panels {
+            registers: true,
+            program: true,
+            input: true,
+            zen: false,
+        }
+    }
+}
+
+/// A rom that was open and the debugger options set on it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TabSession {
+    pub rom: PathBuf,
+    /// instructions per second, when not the default
+    pub speed: Option<u32>,
+    pub break_on_draw: bool,
+    pub break_on_collision: bool,
+    pub pause_on_sound: bool,
+    pub break_on_self_modify: bool,
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+/// A breakpoint, stopping only once its condition holds if it has one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub address: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
+}
+
+impl TabSession {
+    /// Remember `rom` by absolute path, so the session restores from any directory
+    pub fn new(rom: &Path) -> Self {
+        Self {
+            rom: fs::canonicalize(rom).unwrap_or_else(|_| rom.to_path_buf()),
+            ..Self::default()
+        }
+    }
+}
+
+impl Session {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("chipy8").join("session.toml"))
+    }
+
+    /// Load the session at `path`, or the default location when none is given.
+    /// `None` when no session has been saved yet.
+    pub fn load(path: Option<&Path>) -> Result<Option<Self>, Error> {
+        let Some(path) = path.map(Path::to_path_buf).or_else(Self::default_path) else {
+            return Ok(None);
+        };
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        toml::from_str(text).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: Option<&Path>) -> Result<(), Error> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(Self::default_path)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// The rom that was showing, for frontends that open a single rom
+    pub fn active_rom(&self) -> Option<&Path> {
+        self.tabs
+            .get(self.active)
+            .or(self.tabs.first())
+            .map(|tab| tab.rom.as_path())
+    }
+
+    /// The rom a single rom frontend opens, the first of `rom_paths` or else the
+    /// one that was showing in the last session
+    pub fn rom_to_open(rom_paths: &[PathBuf]) -> Result<PathBuf, Error> {
+        if let Some(path) = rom_paths.first() {
+            return Ok(path.clone());
+        }
+        Self::load(None)?
+            .and_then(|session| session.active_rom().map(Path::to_path_buf))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "no rom given and no previous session to restore",
+                )
+            })
+    }
+
+    /// Record that a single rom frontend ran `rom`. The session is left alone
+    /// when it was restored from it, so the terminal frontend's layout survives.
+    pub fn opened(&mut self, rom: &Path) {
+        let tab = TabSession::new(rom);
+        if self.active_rom() != Some(tab.rom.as_path()) {
+            *self = Self {
+                tabs: vec![tab],
+                ..Self::default()
+            };
+        }
+    }
+
+    /// [`Session::opened`] on the saved session
+    pub fn save_opened(rom: &Path) -> Result<(), Error> {
+        let mut session = Self::load(None)?.unwrap_or_default();
+        session.opened(rom);
+        session.save(None)
+    }
+}
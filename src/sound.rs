@@ -0,0 +1,124 @@
+//! Sound
+//!
+//! CHIP-8 has a single tone, a buzzer that sounds while the sound timer is above
+//! zero. [`Beeper`] plays it as a square wave on the default output device when
+//! built with the `audio` feature, and is silent without it. Where there's no
+//! output, a [`Bell`] says when to ring the terminal's bell instead.
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "audio")]
+use rodio::{OutputStream, Sink, Source};
+
+/// The buzzer's pitch, close to the VIP's
+pub const FREQUENCY: u32 = 440;
+
+/// The terminal bell rings at most this often
+pub const BELL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[cfg(feature = "audio")]
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Sounds the buzzer while asked to
+pub struct Beeper {
+    #[cfg(feature = "audio")]
+    // the stream stops playing when dropped
+    _stream: OutputStream,
+    #[cfg(feature = "audio")]
+    sink: Sink,
+}
+
+impl Beeper {
+    /// A beeper on the default output device, None without one or without the
+    /// `audio` feature
+    #[cfg(feature = "audio")]
+    pub fn new() -> Option<Self> {
+        let (stream, handle) = OutputStream::try_default().ok()?;
+        let sink = Sink::try_new(&handle).ok()?;
+        sink.pause();
+        sink.set_volume(0.2);
+        sink.append(Square { sample: 0 });
+        Some(Self {
+            _stream: stream,
+            sink,
+        })
+    }
+
+    #[cfg(not(feature = "audio"))]
+    pub fn new() -> Option<Self> {
+        None
+    }
+
+    /// Sound the buzzer or stop it
+    pub fn set(&self, on: bool) {
+        #[cfg(feature = "audio")]
+        match on {
+            true => self.sink.play(),
+            false => self.sink.pause(),
+        }
+        #[cfg(not(feature = "audio"))]
+        let _ = on;
+    }
+}
+
+/// An endless square wave at [`FREQUENCY`]
+#[cfg(feature = "audio")]
+struct Square {
+    sample: u32,
+}
+
+#[cfg(feature = "audio")]
+impl Iterator for Square {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let period = SAMPLE_RATE / FREQUENCY;
+        self.sample = (self.sample + 1) % period;
+        Some(if self.sample < period / 2 { 1.0 } else { -1.0 })
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Source for Square {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// When the terminal bell stands in for the buzzer: as it starts sounding and
+/// again every [`BELL_INTERVAL`] while it goes on, never more often, so a rom
+/// buzzing every other frame doesn't flood the terminal
+#[derive(Debug, Clone, Default)]
+pub struct Bell {
+    /// when it last rang
+    last: Option<Instant>,
+}
+
+impl Bell {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to ring now, with the buzzer `on` or not
+    pub fn ring(&mut self, on: bool, now: Instant) -> bool {
+        match self.last {
+            _ if !on => false,
+            Some(last) if now.duration_since(last) < BELL_INTERVAL => false,
+            _ => {
+                self.last = Some(now);
+                true
+            }
+        }
+    }
+}
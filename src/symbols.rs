@@ -0,0 +1,83 @@
+//! Symbol files
+//!
+//! Names for addresses, so the debugger can say `draw_ball` where it would say
+//! `0x2e6`. The text format is one `<name> <address>` pair per line with the
+//! address in hex, the way Octo and most assemblers can list their labels. A name
+//! may end in `:` and be separated from its address by `=`. Blank lines and lines
+//! starting with `#` or `;` are ignored.
+//!
+//! A rom's symbols are looked for next to it, `pong.ch8` having `pong.sym`.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbols {
+    names: BTreeMap<u16, String>,
+    addresses: HashMap<String, u16>,
+}
+
+impl Symbols {
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let invalid = |line: usize, reason: &str| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("symbol file line {}: {reason}", line + 1),
+            )
+        };
+        let mut symbols = Self::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            let line = line.replacen('=', " ", 1);
+            let mut fields = line.split_whitespace();
+            let (Some(name), Some(address), None) = (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid(n, "expected `<name> <address>`"));
+            };
+            let address = u16::from_str_radix(address.trim_start_matches("0x"), 16)
+                .map_err(|_| invalid(n, "bad address"))?;
+            symbols.insert(name.trim_end_matches(':'), address);
+        }
+        Ok(symbols)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The symbols in the `.sym` file next to `rom`, none if there isn't one
+    pub fn beside(rom: &Path) -> Result<Self, Error> {
+        match Self::load(rom.with_extension("sym")) {
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            symbols => symbols,
+        }
+    }
+
+    /// Name `address`, a later name for the same address wins when showing it
+    pub fn insert(&mut self, name: &str, address: u16) {
+        self.names.insert(address, name.to_string());
+        self.addresses.insert(name.to_string(), address);
+    }
+
+    pub fn name(&self, address: u16) -> Option<&str> {
+        self.names.get(&address).map(String::as_str)
+    }
+
+    pub fn address(&self, name: &str) -> Option<u16> {
+        self.addresses.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
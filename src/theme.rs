@@ -0,0 +1,176 @@
+//! Colors of the terminal frontend
+//!
+//! A theme is picked by name with `--theme` or `theme` in the config file, and
+//! single colors changed under `[colors]`. Colors are named as in ratatui,
+//! `green`, `lightblue`, `reset` for the terminal's own, or written `#ffb000`.
+use std::{fmt, str::FromStr};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Theme {
+    /// lit pixels
+    pub foreground: Color,
+    /// unlit pixels
+    pub background: Color,
+    /// the bars of V0 to VF
+    pub registers: Color,
+    /// the bars of the timers and I
+    pub timers: Color,
+    /// the next instruction, selections, and whatever is current
+    pub highlight: Color,
+}
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme {
+        foreground: Color::White,
+        background: Color::Reset,
+        registers: Color::Green,
+        timers: Color::Blue,
+        highlight: Color::Green,
+    };
+    pub const GREEN: Theme = Theme {
+        foreground: Color::Rgb(0x33, 0xFF, 0x33),
+        background: Color::Rgb(0x0A, 0x1A, 0x0A),
+        registers: Color::Rgb(0x33, 0xFF, 0x33),
+        timers: Color::Rgb(0x1A, 0x99, 0x1A),
+        highlight: Color::LightGreen,
+    };
+    pub const AMBER: Theme = Theme {
+        foreground: Color::Rgb(0xFF, 0xB0, 0x00),
+        background: Color::Rgb(0x1A, 0x10, 0x00),
+        registers: Color::Rgb(0xFF, 0xB0, 0x00),
+        timers: Color::Rgb(0xCC, 0x7A, 0x00),
+        highlight: Color::Rgb(0xFF, 0xCC, 0x33),
+    };
+    pub const HIGH_CONTRAST: Theme = Theme {
+        foreground: Color::White,
+        background: Color::Black,
+        registers: Color::White,
+        timers: Color::White,
+        highlight: Color::Yellow,
+    };
+
+    const PRESETS: [(&'static str, Theme); 4] = [
+        ("default", Theme::DEFAULT),
+        ("green", Theme::GREEN),
+        ("amber", Theme::AMBER),
+        ("high-contrast", Theme::HIGH_CONTRAST),
+    ];
+
+    /// This theme with the colors set in `colors` changed
+    pub fn with(self, colors: &Colors) -> Theme {
+        Theme {
+            foreground: colors.foreground.unwrap_or(self.foreground),
+            background: colors.background.unwrap_or(self.background),
+            registers: colors.registers.unwrap_or(self.registers),
+            timers: colors.timers.unwrap_or(self.timers),
+            highlight: colors.highlight.unwrap_or(self.highlight),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DEFAULT
+    }
+}
+
+/// The preset's name, `custom` for another
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = Theme::PRESETS
+            .iter()
+            .find(|(_, theme)| theme == self)
+            .map_or("custom", |(name, _)| name);
+        f.write_str(name)
+    }
+}
+
+/// `default`, `green` for green phosphor, `amber` or `high-contrast`
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::PRESETS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, theme)| *theme)
+            .ok_or_else(|| {
+                format!("no theme `{s}`, there's default, green, amber and high-contrast")
+            })
+    }
+}
+
+impl TryFrom<String> for Theme {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// `amount` of the way from `from` to `to`, 0 to 1. Colors without a fixed RGB
+/// value, the terminal's own or indexed ones, switch over halfway, and `reset`
+/// counts as black, as a dark terminal's background.
+pub fn blend(from: Color, to: Color, amount: f32) -> Color {
+    match (rgb(from), rgb(to)) {
+        (Some(from), Some(to)) => {
+            let mix = |from: u8, to: u8| {
+                (f32::from(from) + (f32::from(to) - f32::from(from)) * amount).round() as u8
+            };
+            Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+        }
+        _ if amount < 0.5 => from,
+        _ => to,
+    }
+}
+
+/// The usual values of the named colors, `None` for indexed ones
+pub fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    Some(match color {
+        Color::Reset | Color::Black => (0, 0, 0),
+        Color::Red => (128, 0, 0),
+        Color::Green => (0, 128, 0),
+        Color::Yellow => (128, 128, 0),
+        Color::Blue => (0, 0, 128),
+        Color::Magenta => (128, 0, 128),
+        Color::Cyan => (0, 128, 128),
+        Color::Gray => (192, 192, 192),
+        Color::DarkGray => (128, 128, 128),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (0, 0, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(_) => return None,
+    })
+}
+
+/// Colors to change in a theme, those not given are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    #[serde(deserialize_with = "color")]
+    pub foreground: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub background: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub registers: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub timers: Option<Color>,
+    #[serde(deserialize_with = "color")]
+    pub highlight: Option<Color>,
+}
+
+fn color<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Color>, D::Error> {
+    let name = String::deserialize(d)?;
+    name.parse()
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("no color `{name}`")))
+}
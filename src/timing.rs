@@ -0,0 +1,192 @@
+//! Frame timing diagnostics and wall clock schedules
+//!
+//! Frontends record how long each frame spent emulating and rendering and how far
+//! apart frames were. The summary over a rolling window tells whether choppiness
+//! comes from the emulator, the renderer or the frame pacing itself.
+//!
+//! A [`Rate`] counts frames drawn or instructions run over a rolling window, for
+//! how fast things actually go rather than how fast they were asked to.
+//!
+//! A [`Clock`] decides when instructions run and when the delay and sound timers
+//! tick, from the wall clock rather than from however often the frontend happens
+//! to get around to drawing.
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// A frame arriving later than this missed the 60Hz deadline
+pub const DEADLINE: Duration = Duration::from_micros(16_667);
+
+/// The delay and sound timers count down at 60Hz
+pub const TIMER_PERIOD: Duration = DEADLINE;
+
+/// A fixed rate schedule on the wall clock
+///
+/// Ticks missed while the frontend was stalled are all reported at once, so
+/// whatever it drives catches up instead of running slow.
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    period: Duration,
+    next: Instant,
+}
+
+impl Clock {
+    /// Ticking `rate` times a second
+    pub fn new(now: Instant, rate: u32) -> Self {
+        Self::with_period(now, Duration::from_secs(1) / rate.max(1))
+    }
+
+    /// The delay and sound timers' 60Hz
+    pub fn timers(now: Instant) -> Self {
+        Self::with_period(now, TIMER_PERIOD)
+    }
+
+    fn with_period(now: Instant, period: Duration) -> Self {
+        Self {
+            period,
+            next: now + period,
+        }
+    }
+
+    /// How many ticks fell due between the previous call and `now`
+    pub fn due(&mut self, now: Instant) -> u32 {
+        if now < self.next {
+            return 0;
+        }
+        let ticks = ((now - self.next).as_nanos() / self.period.as_nanos()) as u32 + 1;
+        self.next += self.period * ticks;
+        ticks
+    }
+
+    /// When the next tick falls due
+    pub fn next(&self) -> Instant {
+        self.next
+    }
+}
+
+struct Sample {
+    at: Instant,
+    emulation: Duration,
+    render: Duration,
+    interval: Duration,
+}
+
+pub struct FrameTiming {
+    window: Duration,
+    samples: VecDeque<Sample>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimingSummary {
+    pub frames: usize,
+    pub mean_emulation: Duration,
+    pub max_emulation: Duration,
+    pub mean_render: Duration,
+    pub max_render: Duration,
+    /// frames that came later than [`DEADLINE`] after the previous one
+    pub missed_deadlines: usize,
+    /// standard deviation of the time between frames
+    pub jitter: Duration,
+}
+
+impl FrameTiming {
+    /// Keep samples from the last `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, emulation: Duration, render: Duration, interval: Duration) {
+        let at = Instant::now();
+        while self
+            .samples
+            .front()
+            .is_some_and(|s| at.duration_since(s.at) > self.window)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            at,
+            emulation,
+            render,
+            interval,
+        });
+    }
+
+    pub fn summary(&self) -> TimingSummary {
+        let frames = self.samples.len();
+        if frames == 0 {
+            return TimingSummary::default();
+        }
+        let mean = |f: fn(&Sample) -> Duration| {
+            self.samples.iter().map(f).sum::<Duration>() / frames as u32
+        };
+        let max = |f: fn(&Sample) -> Duration| self.samples.iter().map(f).max().unwrap_or_default();
+
+        let mean_interval = mean(|s| s.interval).as_secs_f64();
+        let variance = self
+            .samples
+            .iter()
+            .map(|s| (s.interval.as_secs_f64() - mean_interval).powi(2))
+            .sum::<f64>()
+            / frames as f64;
+
+        TimingSummary {
+            frames,
+            mean_emulation: mean(|s| s.emulation),
+            max_emulation: max(|s| s.emulation),
+            mean_render: mean(|s| s.render),
+            max_render: max(|s| s.render),
+            missed_deadlines: self
+                .samples
+                .iter()
+                .filter(|s| s.interval > DEADLINE)
+                .count(),
+            jitter: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// How often something happens, over the last `window`
+pub struct Rate {
+    window: Duration,
+    /// when and how many times
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl Rate {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// It happened `count` times at `now`
+    pub fn record(&mut self, now: Instant, count: u64) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|&(at, _)| now.duration_since(at) > self.window)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, count));
+    }
+
+    /// Times a second since the oldest sample, which only marks the start
+    pub fn per_second(&self, now: Instant) -> f64 {
+        let Some(&(start, _)) = self.samples.front() else {
+            return 0.0;
+        };
+        let elapsed = now.duration_since(start).as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+        let count: u64 = self.samples.iter().skip(1).map(|&(_, count)| count).sum();
+        count as f64 / elapsed
+    }
+}
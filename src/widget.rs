@@ -1,27 +1,101 @@
+use std::ops::Range;
+
+use drawille::Canvas;
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
+    layout::{Position, Rect},
     prelude::BlockExt,
-    style::{Color, Stylize},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
     text::Span,
-    widgets::{Block, Widget},
+    widgets::{
+        canvas::{Painter, Shape},
+        Block, Widget,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::{
+    chip8::{Chip8, HEIGHT_PIX},
+    keypad::KeypadActivity,
+    phosphor::Phosphor,
+    theme,
 };
 
 pub struct HexInput<'a> {
-    pub input: u8,
+    /// bit k set while key k is held
+    pub keys: u16,
+    held: Color,
+    idle: Color,
+    activity: Option<&'a KeypadActivity>,
+    heatmap: bool,
     block: Option<Block<'a>>,
 }
 impl<'a> HexInput<'a> {
-    pub fn new(input: u8) -> Self {
-        HexInput { input, block: None }
+    pub fn new(keys: u16) -> Self {
+        HexInput {
+            keys,
+            held: Color::Green,
+            idle: Color::Blue,
+            activity: None,
+            heatmap: false,
+            block: None,
+        }
+    }
+    /// The colors of the keys held and of the others
+    pub fn colors(mut self, held: Color, idle: Color) -> Self {
+        self.held = held;
+        self.idle = idle;
+        self
+    }
+    /// Flash keys as they're pressed and fade them as they're let go
+    pub fn activity(mut self, activity: &'a KeypadActivity) -> Self {
+        self.activity = Some(activity);
+        self
+    }
+    /// Behind each key, red as deep as it was pressed often, with an activity
+    pub fn heatmap(mut self, heatmap: bool) -> Self {
+        self.heatmap = heatmap;
+        self
     }
     pub fn block(mut self, block: Block<'a>) -> Self {
         self.block = Some(block);
         self
     }
+    /// The key shown at `x`, `y` when drawn in `area`, inside the block
+    pub fn key_at(area: Rect, x: u16, y: u16) -> Option<u8> {
+        if !area.contains(Position::new(x, y)) {
+            return None;
+        }
+        // each key is three columns wide, four to a row
+        let (column, row) = ((x - area.x) / 3, y - area.y);
+        (column < 4 && row < 4).then_some((row * 4 + column) as u8)
+    }
+
+    fn style(&self, key: u8) -> Style {
+        let held = self.keys & (1 << key) != 0;
+        let pulse = self.activity.map_or(0.0, |activity| activity.pulse(key));
+        let style = match self.activity {
+            Some(activity) if self.heatmap && activity.presses(key) > 0 => {
+                let share = activity.presses(key) as f32 / activity.most_presses() as f32;
+                Style::new().bg(theme::blend(Color::Reset, Color::LightRed, share))
+            }
+            _ => Style::new(),
+        };
+        match (held, pulse > 0.0) {
+            // a flash as it goes down
+            (true, true) => style.fg(Color::Black).bg(self.held).bold(),
+            (true, false) => style.fg(self.held).bold(),
+            // fading back as it comes up
+            (false, true) => style.fg(theme::blend(self.idle, self.held, pulse)),
+            (false, false) => style.fg(self.idle),
+        }
+    }
 }
-//// Displays all 16 possible input keys, 0..F
-/// The selected input is highlighted
+/// Displays all 16 possible input keys, 0..F
+/// The keys held are highlighted
 impl Widget for HexInput<'_> {
     fn render(self, container_area: Rect, buf: &mut Buffer) {
         self.block.render(container_area, buf);
@@ -31,21 +105,461 @@ impl Widget for HexInput<'_> {
         }
 
         let keys = "1234qwerasdfzxcv".chars();
+        for (i, k) in keys.enumerate() {
+            let span = Span::styled(format!(" {k} "), self.style(i as u8));
+            let x = widget_area.left() + (i as u16 % 4) * 3;
+            let y = widget_area.top() + (i / 4) as u16;
+            buf.set_span(x, y, &span, 3);
+        }
+    }
+}
 
-        let spans = keys.enumerate().map(|(i, k)| {
-            let span = Span::default().content(k.to_string());
-            if i == self.input as usize {
-                span.fg(Color::Green)
-            } else {
-                span.fg(Color::Blue)
+/// Memory as a hex dump with the printable bytes alongside, a row of 16 bytes per
+/// line from [`MemoryView::scroll`] on, or 8 when the area is too narrow for 16
+pub struct MemoryView<'a> {
+    memory: &'a [u8],
+    /// the address of the first row, rounded down to a row
+    start: u16,
+    pc: Option<u16>,
+    i: Option<u16>,
+    cursor: Option<u16>,
+    mark: Range<u16>,
+    colors: &'a [Color],
+    highlight: Color,
+    block: Option<Block<'a>>,
+}
+impl<'a> MemoryView<'a> {
+    pub fn new(memory: &'a [u8]) -> Self {
+        MemoryView {
+            memory,
+            start: 0,
+            pc: None,
+            i: None,
+            cursor: None,
+            mark: 0..0,
+            colors: &[],
+            highlight: Color::Green,
+            block: None,
+        }
+    }
+    pub fn scroll(mut self, start: u16) -> Self {
+        self.start = start;
+        self
+    }
+    /// The background of the instruction at [`MemoryView::pc`]
+    pub fn highlight(mut self, color: Color) -> Self {
+        self.highlight = color;
+        self
+    }
+    /// Highlight the two bytes of the instruction at `pc`
+    pub fn pc(mut self, pc: u16) -> Self {
+        self.pc = Some(pc);
+        self
+    }
+    /// Highlight the byte `i` points at
+    pub fn i(mut self, i: u16) -> Self {
+        self.i = Some(i);
+        self
+    }
+    /// Highlight the byte being edited, scrolling to it if it is out of view
+    pub fn cursor(mut self, address: u16) -> Self {
+        self.cursor = Some(address);
+        self
+    }
+    /// Highlight `range`, a search match say
+    pub fn mark(mut self, range: Range<u16>) -> Self {
+        self.mark = range;
+        self
+    }
+    /// Color each byte by `colors`, one per address from 0, a heatmap say
+    pub fn colors(mut self, colors: &'a [Color]) -> Self {
+        self.colors = colors;
+        self
+    }
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+    /// Bytes per row in an area `width` columns wide
+    pub fn row_len(width: u16) -> usize {
+        // "0x000  " then three columns and one of text per byte
+        match width >= 7 + 16 * 4 {
+            true => 16,
+            false => 8,
+        }
+    }
+}
+impl Widget for MemoryView<'_> {
+    fn render(self, container_area: Rect, buf: &mut Buffer) {
+        self.block.render(container_area, buf);
+        let area = self.block.inner_if_some(container_area);
+        if area.is_empty() {
+            return;
+        }
+        let row_len = Self::row_len(area.width);
+        let style = |address: usize| {
+            let address = address as u16;
+            match (self.pc, self.i) {
+                _ if self.cursor == Some(address) => Style::new().black().on_cyan(),
+                _ if self.mark.contains(&address) => Style::new().black().on_magenta(),
+                (Some(pc), _) if address == pc || address == pc.wrapping_add(1) => {
+                    Style::new().black().bg(self.highlight)
+                }
+                (_, Some(i)) if address == i => Style::new().black().on_yellow(),
+                _ => match self.colors.get(address as usize) {
+                    Some(&color) => Style::new().fg(color),
+                    None => Style::new(),
+                },
             }
-        });
+        };
+        let mut first = self.start as usize / row_len * row_len;
+        if let Some(cursor) = self.cursor {
+            let row = cursor as usize / row_len * row_len;
+            let last = first + (area.height as usize - 1) * row_len;
+            first = match (row < first, row > last) {
+                (true, _) => row,
+                (_, true) => first + row - last,
+                _ => first,
+            };
+        }
+        let rows = self.memory.get(first..).unwrap_or_default().chunks(row_len);
+        for (y, (n, row)) in (area.top()..area.bottom()).zip(rows.enumerate()) {
+            let address = first + n * row_len;
+            let width = area.width as usize;
+            buf.set_stringn(
+                area.x,
+                y,
+                format!("{address:#05x}"),
+                width,
+                Style::new().dim(),
+            );
+            for (k, &byte) in row.iter().enumerate() {
+                let x = 7 + k * 3;
+                let text = match byte.is_ascii_graphic() || byte == b' ' {
+                    true => byte as char,
+                    false => '.',
+                };
+                let column = area.x + x as u16;
+                let style = style(address + k);
+                buf.set_stringn(
+                    column,
+                    y,
+                    format!("{byte:02x}"),
+                    width.saturating_sub(x),
+                    style,
+                );
+                let x = 7 + row_len * 3 + 1 + k;
+                buf.set_stringn(
+                    area.x + x as u16,
+                    y,
+                    text.to_string(),
+                    width.saturating_sub(x),
+                    style,
+                );
+            }
+        }
+    }
+}
 
-        spans.enumerate().for_each(|(i, span)| {
-            //.fold("".to_owned(), |acc, x| format!("{acc}{:#1x}", x));
-            let x = widget_area.left() + (i as u16 % 4) * 3;
-            let y = widget_area.top() + (i / 4) as u16;
-            buf.set_span(x, y, &span, 8);
-        });
+/// The sprite `height` bytes of memory from `address` make, each set bit as a
+/// two column block so pixels come out roughly square. A height of 0 is the
+/// SUPER-CHIP 16x16 sprite of two bytes a row, as Dxy0 draws it.
+pub struct SpritePreview<'a> {
+    memory: &'a [u8],
+    address: u16,
+    height: u8,
+    color: Color,
+    block: Option<Block<'a>>,
+}
+impl<'a> SpritePreview<'a> {
+    pub fn new(memory: &'a [u8], address: u16, height: u8) -> Self {
+        SpritePreview {
+            memory,
+            address,
+            height,
+            color: Color::White,
+            block: None,
+        }
+    }
+    /// The color of set bits
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+    /// Rows and pixels per row
+    pub fn size(height: u8) -> (u16, u16) {
+        match height {
+            0 => (16, 16),
+            height => (height.into(), 8),
+        }
+    }
+}
+impl Widget for SpritePreview<'_> {
+    fn render(self, container_area: Rect, buf: &mut Buffer) {
+        self.block.render(container_area, buf);
+        let area = self.block.inner_if_some(container_area);
+        if area.is_empty() {
+            return;
+        }
+        let (rows, width) = Self::size(self.height);
+        let row_bytes = width as usize / 8;
+        for (row, y) in (0..rows as usize).zip(area.top()..area.bottom()) {
+            for bit in 0..width as usize {
+                let at = self.address as usize + row * row_bytes + bit / 8;
+                // past the end of memory shows blank
+                let byte = self.memory.get(at).copied().unwrap_or(0);
+                let x = area.x + bit as u16 * 2;
+                if x + 1 >= area.right() {
+                    break;
+                }
+                let span = match byte & (0x80 >> (bit % 8)) != 0 {
+                    true => Span::from("██").fg(self.color),
+                    false => Span::from("··").fg(Color::DarkGray),
+                };
+                buf.set_span(x, y, &span, 2);
+            }
+        }
+    }
+}
+
+/// Where a scrub through the rewind history is, a track from the oldest state
+/// to the present with a knob at the one shown
+pub struct Timeline<'a> {
+    /// states recorded, the present is one past them
+    len: usize,
+    index: usize,
+    color: Color,
+    block: Option<Block<'a>>,
+}
+impl<'a> Timeline<'a> {
+    pub fn new(len: usize, index: usize) -> Self {
+        Timeline {
+            len,
+            index,
+            color: Color::Green,
+            block: None,
+        }
+    }
+    /// The color of the knob and the track behind it
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+}
+impl Widget for Timeline<'_> {
+    fn render(self, container_area: Rect, buf: &mut Buffer) {
+        self.block.render(container_area, buf);
+        let area = self.block.inner_if_some(container_area);
+        if area.is_empty() {
+            return;
+        }
+        let last = area.width as usize - 1;
+        let knob = match self.len {
+            0 => last,
+            len => self.index.min(len) * last / len,
+        };
+        for x in 0..=last {
+            let span = match x {
+                _ if x == knob => Span::from("●").fg(self.color),
+                0 => Span::from("├").fg(Color::DarkGray),
+                _ if x == last => Span::from("┤").fg(Color::DarkGray),
+                _ if x < knob => Span::from("━").fg(self.color),
+                _ => Span::from("─").fg(Color::DarkGray),
+            };
+            buf.set_span(area.x + x as u16, area.y, &span, 1);
+        }
+    }
+}
+
+/// The display scaled up by a whole number as far as it fits, centred, in half
+/// blocks so pixels come out square
+pub struct ScaledDisplay<'a> {
+    chip8: &'a Chip8,
+    foreground: Color,
+    background: Color,
+    phosphor: Option<&'a Phosphor>,
+}
+impl<'a> ScaledDisplay<'a> {
+    pub fn new(chip8: &'a Chip8) -> Self {
+        ScaledDisplay {
+            chip8,
+            foreground: Color::White,
+            background: Color::Black,
+            phosphor: None,
+        }
+    }
+    /// Draw the glow of `phosphor` rather than only the pixels lit
+    pub fn phosphor(mut self, phosphor: Option<&'a Phosphor>) -> Self {
+        self.phosphor = phosphor;
+        self
+    }
+    /// The colors of lit and unlit pixels
+    pub fn colors(mut self, foreground: Color, background: Color) -> Self {
+        self.foreground = foreground;
+        self.background = background;
+        self
+    }
+    /// Cells a side each pixel takes in `area`, at least 1 even if it doesn't fit
+    pub fn scale(width: usize, height: usize, area: Rect) -> usize {
+        // a cell is two pixels tall
+        let across = area.width as usize / width;
+        let down = area.height as usize * 2 / height;
+        across.min(down).max(1)
+    }
+}
+impl Widget for ScaledDisplay<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = (self.chip8.width(), self.chip8.height());
+        let scale = Self::scale(width, height, area);
+        let columns = (width * scale) as u16;
+        let rows = (height * scale).div_ceil(2) as u16;
+        let left = area.x + area.width.saturating_sub(columns) / 2;
+        let top = area.y + area.height.saturating_sub(rows) / 2;
+        let color = |x: usize, y: usize| {
+            let (x, y) = (x / scale, y / scale);
+            match self.phosphor {
+                _ if y >= height => self.background,
+                Some(phosphor) => {
+                    theme::blend(self.background, self.foreground, phosphor.brightness(x, y))
+                }
+                None if self.chip8.pixel(x, y) => self.foreground,
+                None => self.background,
+            }
+        };
+        for row in 0..rows.min(area.height) {
+            for column in 0..columns.min(area.width) {
+                let (x, y) = (column as usize, row as usize * 2);
+                buf[(left + column, top + row)]
+                    .set_symbol("▀")
+                    .set_fg(color(x, y))
+                    .set_bg(color(x, y + 1));
+            }
+        }
+    }
+}
+
+/// The display drawn in braille characters, for printing to a terminal
+pub fn braille(chip8: &Chip8) -> String {
+    let mut canvas = Canvas::new(chip8.width() as u32, chip8.height() as u32);
+    for y in 0..chip8.height() {
+        for x in 0..chip8.width() {
+            if chip8.pixel(x, y) {
+                canvas.set(x as u32, y as u32);
+            }
+        }
+    }
+    canvas.frame()
+}
+
+/// How the display's pixels are drawn in terminal cells, named as on the command line
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum PixelMarker {
+    /// half blocks for lo-res, braille for the taller hi-res modes
+    #[default]
+    Auto,
+    /// two pixels a cell, one above the other, roughly square
+    HalfBlock,
+    /// a 2x4 grid of dots a cell, sharp and small
+    Braille,
+    /// a full cell a pixel, big and tall
+    Block,
+}
+
+impl PixelMarker {
+    /// The next one, to cycle through them all
+    pub fn next(self) -> Self {
+        match self {
+            PixelMarker::Auto => PixelMarker::HalfBlock,
+            PixelMarker::HalfBlock => PixelMarker::Braille,
+            PixelMarker::Braille => PixelMarker::Block,
+            PixelMarker::Block => PixelMarker::Auto,
+        }
+    }
+
+    /// The canvas marker for a display `height` pixels tall
+    pub fn marker(self, height: usize) -> Marker {
+        match self {
+            // half blocks fit lo-res in the panel, braille's 2x4 dots fit the
+            // taller hi-res modes
+            PixelMarker::Auto if height > HEIGHT_PIX => Marker::Braille,
+            PixelMarker::Auto | PixelMarker::HalfBlock => Marker::HalfBlock,
+            PixelMarker::Braille => Marker::Braille,
+            PixelMarker::Block => Marker::Block,
+        }
+    }
+
+    /// Columns and rows a display `width` by `height` pixels takes
+    pub fn cells(self, width: usize, height: usize) -> (u16, u16) {
+        let (columns, rows) = match self.marker(height) {
+            Marker::Braille => (width.div_ceil(2), height.div_ceil(4)),
+            Marker::HalfBlock => (width, height.div_ceil(2)),
+            _ => (width, height),
+        };
+        (columns as u16, rows as u16)
+    }
+}
+
+/// The lit pixels of the display in `color`
+pub struct Pixels<'a> {
+    pub chip8: &'a Chip8,
+    pub color: Color,
+}
+
+/// Paints one display pixel per canvas grid point, the canvas marker has to give a
+/// grid of at least [`Chip8::width`] by [`Chip8::height`]
+impl Shape for Pixels<'_> {
+    fn draw(&self, painter: &mut Painter) {
+        for y in 0..self.chip8.height() {
+            for x in 0..self.chip8.width() {
+                if self.chip8.pixel(x, y) {
+                    painter.paint(x, y, self.color);
+                }
+            }
+        }
+    }
+}
+
+/// The glow of each pixel, fading from `foreground` to `background`
+pub struct Glow<'a> {
+    pub phosphor: &'a Phosphor,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+/// Paints like [`Pixels`], and the pixels still fading out
+impl Shape for Glow<'_> {
+    fn draw(&self, painter: &mut Painter) {
+        for y in 0..self.phosphor.height() {
+            for x in 0..self.phosphor.width() {
+                let brightness = self.phosphor.brightness(x, y);
+                if brightness > 0.0 {
+                    let color = theme::blend(self.background, self.foreground, brightness);
+                    painter.paint(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+/// The display in white
+impl Shape for Chip8 {
+    fn draw(&self, painter: &mut Painter) {
+        Pixels {
+            chip8: self,
+            color: Color::White,
+        }
+        .draw(painter)
     }
 }
@@ -0,0 +1,56 @@
+//! The assembler reads back what the disassembler writes
+use chipy8::{asm, chip8::Instruction};
+
+#[test]
+fn every_instruction_round_trips_through_its_mnemonic() {
+    for opcode in 0..=u16::MAX {
+        let instruction = Instruction::decode(opcode);
+        if let Instruction::Unknown(_) = instruction {
+            continue;
+        }
+        assert_eq!(Instruction::decode(instruction.encode()), instruction);
+        let rom = asm::assemble(&instruction.to_string()).unwrap();
+        assert_eq!(rom, instruction.encode().to_be_bytes(), "{instruction}");
+    }
+}
+
+#[test]
+fn labels_constants_and_data() {
+    let source = "
+        ; bounce a ball
+        speed = 2
+        start:
+            LD I, ball
+            drw v0, v1, 3  ; lower case works too
+            ADD V0, speed
+            JP start
+        ball: db 0x3c, 0b01000010, 60
+        dw 0x1234
+    ";
+    #[rustfmt::skip]
+    let expected = [
+        0xA2, 0x08,
+        0xD0, 0x13,
+        0x70, 0x02,
+        0x12, 0x00,
+        0x3C, 0x42, 0x3C,
+        0x12, 0x34,
+    ];
+    assert_eq!(asm::assemble(source).unwrap(), expected);
+}
+
+#[test]
+fn errors_name_the_line() {
+    for (source, message) in [
+        ("CLS\nJP nowhere", "line 2: unknown name `nowhere`"),
+        ("LD V0, 0x100", "line 1: 0x100 doesn't fit in 0xff"),
+        (
+            "DRW V0, 1, 2",
+            "line 1: no `DRW` instruction takes these operands",
+        ),
+        ("a:\na:", "line 2: `a` is already defined"),
+    ] {
+        let error = asm::assemble(source).unwrap_err();
+        assert_eq!(error.to_string(), message);
+    }
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use chipy8::{
+    asm,
+    chip8::{Chip8, RunOutcome},
+    rom::Rom,
+};
+use common::maze;
+
+#[test]
+fn runs_to_each_breakpoint_in_turn() {
+    let mut chip8 = maze();
+    // maze's DRW, once per maze cell
+    assert!(chip8.toggle_breakpoint(0x208));
+    let mut draws = 0;
+    while let RunOutcome::Breakpoint { address } = chip8.run_until_break(100).unwrap() {
+        assert_eq!(address, 0x208);
+        assert_eq!(chip8.program_counter, 0x208);
+        draws += 1;
+    }
+    // 16 by 8 cells of 4x4
+    assert_eq!(draws, 128);
+}
+
+#[test]
+fn stops_at_the_step_limit_without_breakpoints() {
+    let mut chip8 = maze();
+    assert!(chip8.toggle_breakpoint(0x208));
+    assert!(!chip8.toggle_breakpoint(0x208));
+    assert_eq!(chip8.run_until_break(50).unwrap(), RunOutcome::StepLimit);
+
+    let mut stepped = maze();
+    for _ in 0..50 {
+        stepped.step().unwrap();
+    }
+    assert!(chip8 == stepped);
+}
+
+fn nested_calls() -> Chip8 {
+    let rom = asm::assemble(
+        "
+        CALL outer
+        ADD V0, 1
+    end:
+        JP end
+    outer:
+        CALL inner
+        ADD V1, 1
+        RET
+    inner:
+        ADD V2, 1
+        ADD V2, 1
+        RET
+        ",
+    )
+    .unwrap();
+    Chip8::new(Rom::from_bytes("nested calls", rom))
+}
+
+#[test]
+fn step_over_runs_whole_calls() {
+    let mut chip8 = nested_calls();
+    assert_eq!(chip8.step_over(100), Ok(RunOutcome::Stepped));
+    assert_eq!(chip8.program_counter, 0x202);
+    assert_eq!(chip8.registers[..3], [0, 1, 2]);
+    // anything else is a single step
+    assert_eq!(chip8.step_over(100), Ok(RunOutcome::Stepped));
+    assert_eq!(chip8.program_counter, 0x204);
+    assert_eq!(chip8.step_over(100), Ok(RunOutcome::Stepped));
+    assert!(chip8.is_halted());
+    assert_eq!(chip8.step_over(100), Ok(RunOutcome::Halted));
+}
+
+#[test]
+fn step_over_stops_at_breakpoints_inside() {
+    let mut chip8 = nested_calls();
+    chip8.toggle_breakpoint(0x20E);
+    assert_eq!(
+        chip8.step_over(100),
+        Ok(RunOutcome::Breakpoint { address: 0x20E })
+    );
+    assert_eq!(chip8.stack_pointer, 2);
+    assert_eq!(chip8.step_over(1), Ok(RunOutcome::Stepped));
+}
+
+#[test]
+fn step_out_returns_from_the_current_call() {
+    let mut chip8 = nested_calls();
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    assert_eq!(chip8.program_counter, 0x20C);
+    assert_eq!(chip8.step_out(100), Ok(RunOutcome::Stepped));
+    assert_eq!(chip8.program_counter, 0x208);
+    assert_eq!(chip8.step_out(100), Ok(RunOutcome::Stepped));
+    assert_eq!(chip8.program_counter, 0x202);
+    assert_eq!(chip8.registers[..3], [0, 1, 2]);
+    // at the top level, just the one step
+    assert_eq!(chip8.step_out(100), Ok(RunOutcome::Stepped));
+    assert_eq!(chip8.program_counter, 0x204);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use chipy8::{
+    chip8::{Chip8, Chip8Error, Platform, Quirks, TimerSource},
+    rom::Rom,
+};
+use common::rom;
+
+#[test]
+fn defaults_match_new() {
+    let rom = rom("LD V0, 0x42");
+    let built = Chip8::builder(rom.clone()).seed(1).build().unwrap();
+    assert_eq!(built, Chip8::new(rom).with_seed(1));
+}
+
+#[test]
+fn settings_apply() {
+    let chip8 = Chip8::builder(rom("LD V0, 0x42"))
+        .platform(Platform::Schip)
+        .speed(600)
+        .start_address(0x600)
+        .build()
+        .unwrap();
+    assert_eq!(chip8.quirks, Quirks::from(Platform::Schip));
+    assert_eq!(chip8.timer_source, TimerSource::Instructions(600));
+    assert_eq!(chip8.program_counter, 0x600);
+    assert_eq!(&chip8.memory[0x600..0x602], [0x60, 0x42]);
+}
+
+#[test]
+fn seeded_machines_roll_the_same_numbers() {
+    let run = || {
+        let mut chip8 = Chip8::builder(rom("RND V0, 0xFF\nRND V1, 0xFF"))
+            .seed(7)
+            .build()
+            .unwrap();
+        chip8.step().unwrap();
+        chip8.step().unwrap();
+        chip8.registers
+    };
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn roms_that_dont_fit_are_refused() {
+    let too_big = Rom::from_bytes("big", vec![0; 0x1000 - 0x200 + 1]);
+    assert_eq!(
+        Chip8::builder(too_big).build(),
+        Err(Chip8Error::RomTooLarge {
+            size: 0xE01,
+            room: 0xE00
+        })
+    );
+    let half = Rom::from_bytes("half", vec![0; 0x400]);
+    assert_eq!(
+        Chip8::builder(half).memory_size(0x400).build(),
+        Err(Chip8Error::RomTooLarge {
+            size: 0x400,
+            room: 0x200
+        })
+    );
+}
+
+#[test]
+fn memory_sizes_past_4k_or_too_small_for_the_font_are_refused() {
+    for size in [5000, 0x10] {
+        assert_eq!(
+            Chip8::builder(rom("CLS")).memory_size(size).build(),
+            Err(Chip8Error::InvalidMemorySize { size })
+        );
+    }
+}
+
+#[test]
+fn smaller_memory_faults_past_its_end() {
+    let mut chip8 = Chip8::builder(rom("LD I, 0x800\nLD [I], V0"))
+        .memory_size(0x800)
+        .build()
+        .unwrap();
+    assert_eq!(chip8.memory.len(), 0x800);
+    chip8.step().unwrap();
+    assert!(matches!(
+        chip8.step(),
+        Err(Chip8Error::MemoryFault { index: 0x800, .. })
+    ));
+}
+
+#[test]
+fn start_addresses_past_the_end_of_memory_are_refused() {
+    let empty = Rom::from_bytes("empty", vec![]);
+    assert_eq!(
+        Chip8::builder(empty)
+            .memory_size(0x400)
+            .start_address(0x800)
+            .build(),
+        Err(Chip8Error::InvalidStartAddress { start: 0x800 })
+    );
+}
+
+#[test]
+fn start_addresses_in_the_fonts_are_refused() {
+    for start in [0, 0xEF] {
+        assert_eq!(
+            Chip8::builder(rom("JP 0x200")).start_address(start).build(),
+            Err(Chip8Error::InvalidStartAddress { start })
+        );
+    }
+    assert!(Chip8::builder(rom("JP 0x200"))
+        .start_address(0xF0)
+        .build()
+        .is_ok());
+}
@@ -0,0 +1,19 @@
+//! Fixtures shared by the integration tests. Each test binary uses a subset.
+#![allow(dead_code)]
+
+use chipy8::{asm, chip8::Chip8, rom::Rom};
+
+/// A rom assembled from `source`
+pub fn rom(source: &str) -> Rom {
+    Rom::from_bytes("test", asm::assemble(source).unwrap())
+}
+
+/// A machine loaded with the program assembled from `source`
+pub fn program(source: &str) -> Chip8 {
+    Chip8::new(rom(source))
+}
+
+/// The bundled maze rom, seeded so it draws the same maze every run
+pub fn maze() -> Chip8 {
+    Chip8::new(Rom::new("ROMS/MAZE").unwrap()).with_seed(7)
+}
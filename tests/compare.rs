@@ -0,0 +1,68 @@
+mod common;
+
+use chipy8::{
+    chip8::{Chip8, Platform},
+    compare::{self, Divergence},
+};
+use common::rom;
+
+fn machine(source: &str, platform: Platform) -> Chip8 {
+    Chip8::builder(rom(source))
+        .platform(platform)
+        .seed(1)
+        .build()
+        .unwrap()
+}
+
+const SHIFT: &str = "
+    LD V1, 0x10
+    LD V0, 1
+    SHR V0, V1
+    RND V2, 0xFF
+end:
+    JP end
+";
+
+#[test]
+fn the_first_quirk_dependent_step_is_reported() {
+    let mut chip8 = machine(SHIFT, Platform::Chip8);
+    let mut schip = machine(SHIFT, Platform::Schip);
+    let divergence = compare::lockstep(&mut chip8, &mut schip, 100, |_| 0).unwrap_err();
+    assert_eq!(
+        divergence,
+        Divergence {
+            step: 2,
+            address: 0x204,
+            opcode: 0x8016,
+            field: "registers",
+            first: format!("{:x?}", chip8.registers),
+            second: format!("{:x?}", schip.registers),
+        }
+    );
+    // the shifted register
+    assert_eq!((chip8.registers[0], schip.registers[0]), (0x08, 0x00));
+}
+
+#[test]
+fn machines_that_agree_run_to_the_end() {
+    let mut first = machine(SHIFT, Platform::Schip);
+    let mut second = machine(SHIFT, Platform::Chip48);
+    assert_eq!(
+        compare::lockstep(&mut first, &mut second, 100, |_| 0),
+        Ok(())
+    );
+    assert!(first.is_halted() && second.is_halted());
+}
+
+#[test]
+fn input_reaches_both() {
+    let source = "LD V0, K\nend: JP end";
+    let mut first = machine(source, Platform::Schip);
+    let mut second = machine(source, Platform::Chip48);
+    let input = |step| if step == 3 { 1 << 5 } else { 0 };
+    assert_eq!(
+        compare::lockstep(&mut first, &mut second, 10, input),
+        Ok(())
+    );
+    assert_eq!((first.registers[0], second.registers[0]), (5, 5));
+}
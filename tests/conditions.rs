@@ -0,0 +1,75 @@
+use chipy8::{
+    asm,
+    chip8::{Chip8, Comparison, Condition, Operand, RunOutcome},
+    rom::Rom,
+};
+
+fn condition(text: &str) -> Condition {
+    text.parse().unwrap()
+}
+
+#[test]
+fn conditions_parse() {
+    assert_eq!(
+        condition("v0 == 3"),
+        Condition::Compare(Operand::V(0), Comparison::Eq, Operand::Literal(3))
+    );
+    assert_eq!(
+        condition("[0x300]!=[i]"),
+        Condition::Compare(Operand::Memory(0x300), Comparison::Ne, Operand::AtI)
+    );
+    assert_eq!(
+        condition("i <= 0x400 && vF > 0 || pc >= 0x2a0"),
+        Condition::Or(
+            Box::new(Condition::And(
+                Box::new(condition("i <= 400")),
+                Box::new(condition("vf > 0")),
+            )),
+            Box::new(condition("pc >= 2a0")),
+        )
+    );
+    assert_eq!(
+        condition("dt < st").to_string(),
+        "dt < st",
+        "shown the way it's typed"
+    );
+    assert_eq!(
+        "v0 = 3".parse::<Condition>(),
+        Err("no comparison in `v0 = 3`".to_string())
+    );
+    assert_eq!(
+        "vx == 3".parse::<Condition>(),
+        Err("no register `vx`".to_string())
+    );
+    assert_eq!(
+        "v0 == ".parse::<Condition>(),
+        Err("missing operand".to_string())
+    );
+}
+
+#[test]
+fn conditional_breakpoint_waits_for_its_condition() {
+    let rom = asm::assemble(
+        "
+    loop:
+        ADD V0, 1
+        LD I, 0x300
+        LD [I], V0
+        JP loop
+        ",
+    )
+    .unwrap();
+    let mut chip8 = Chip8::new(Rom::from_bytes("conditions", rom));
+    chip8.set_breakpoint(0x206, Some(condition("v0 == 3 && [0x300] == 3")));
+    let outcome = chip8.run_until_break(100).unwrap();
+    assert_eq!(outcome, RunOutcome::Breakpoint { address: 0x206 });
+    assert_eq!(chip8.registers[0], 3);
+    // toggling it off forgets the condition
+    assert!(!chip8.toggle_breakpoint(0x206));
+    assert!(chip8.toggle_breakpoint(0x206));
+    assert_eq!(chip8.breakpoint_condition(0x206), None);
+    chip8.step().unwrap();
+    let outcome = chip8.run_until_break(100).unwrap();
+    assert_eq!(outcome, RunOutcome::Breakpoint { address: 0x206 });
+    assert_eq!(chip8.registers[0], 4);
+}
@@ -0,0 +1,211 @@
+use chipy8::{
+    chip8::Platform,
+    cli::{Cli, DEFAULT_SPEED},
+    config::{Config, QuirkOverrides},
+    graphics::Graphics,
+    keymap::Keymap,
+    library::RomSettings,
+    rom::Rom,
+    session::Panels,
+    theme::Theme,
+    widget::PixelMarker,
+};
+use clap::Parser;
+use ratatui::style::Color;
+
+#[test]
+fn empty_config_is_default() {
+    let config = Config::parse("").unwrap();
+    assert!(config.macros.is_empty());
+}
+
+#[test]
+fn macros_expand_to_frames() {
+    let config = Config::parse(
+        r#"
+        [macros]
+        m = "tap 5 x3, hold 6 10"
+        n = "wait 2, tap a"
+        "#,
+    )
+    .unwrap();
+
+    let m: Vec<_> = config.macros[&'m'].frames().collect();
+    let tap = [Some(5), Some(5), None, None];
+    assert_eq!(m[..12], tap.repeat(3)[..]);
+    assert_eq!(m[12..], [Some(6); 10]);
+
+    let n: Vec<_> = config.macros[&'n'].frames().collect();
+    assert_eq!(n, [None, None, Some(0xa), Some(0xa), None, None]);
+}
+
+#[test]
+fn bad_macros_are_rejected() {
+    for bad in [
+        "tap", "tap 10", "hold 5", "hold 5 x", "jump 3", "wait 1 2", "tap 5 3",
+    ] {
+        let toml = format!("[macros]\nm = \"{bad}\"\n");
+        assert!(Config::parse(&toml).is_err(), "{bad} should not parse");
+    }
+    assert!(Config::parse("unknown = 1").is_err());
+}
+
+#[test]
+fn roms_can_have_their_own_keymap() {
+    let config = Config::parse(
+        r#"
+        keymap = "azerty"
+
+        [roms.pong]
+        keymap = "dvorak"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.keymap("pong"), Keymap::DVORAK);
+    assert_eq!(config.keymap("tetris"), Keymap::AZERTY);
+    assert_eq!(Config::default().keymap("pong"), Keymap::QWERTY);
+    assert!(Config::parse("keymap = \"1234\"").is_err());
+    assert!(Config::parse("[roms.pong]\nspeed = 1").is_err());
+}
+
+#[test]
+fn theme_with_colors() {
+    let config = Config::parse(
+        r##"
+        theme = "green"
+
+        [colors]
+        foreground = "#ffb000"
+        highlight = "lightred"
+        "##,
+    )
+    .unwrap();
+    let theme = config.theme(None);
+    assert_eq!(theme.foreground, Color::Rgb(0xFF, 0xB0, 0x00));
+    assert_eq!(theme.highlight, Color::LightRed);
+    assert_eq!(theme.background, Theme::GREEN.background);
+    // --theme picks the preset, the config's colors still apply
+    let theme = config.theme(Some(Theme::AMBER));
+    assert_eq!(theme.timers, Theme::AMBER.timers);
+    assert_eq!(theme.highlight, Color::LightRed);
+    assert_eq!(Config::default().theme(None), Theme::DEFAULT);
+    assert!(Config::parse("theme = \"neon\"").is_err());
+    assert!(Config::parse("[colors]\nforeground = \"glow\"").is_err());
+    assert!(Config::parse("[colors]\nborder = \"red\"").is_err());
+}
+
+#[test]
+fn marker_by_name() {
+    let config = Config::parse("marker = \"half-block\"").unwrap();
+    assert_eq!(config.marker, Some(PixelMarker::HalfBlock));
+    assert!(Config::parse("marker = \"dots\"").is_err());
+}
+
+#[test]
+fn graphics_by_name() {
+    let config = Config::parse("graphics = \"sixel\"").unwrap();
+    assert_eq!(config.graphics, Some(Graphics::Sixel));
+    assert!(Config::parse("graphics = \"iterm\"").is_err());
+}
+
+#[test]
+fn settings_for_every_rom() {
+    let config = Config::parse(
+        r#"
+        speed = 1000
+        platform = "schip"
+        mute = true
+
+        [layout]
+        registers = false
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.speed, Some(1000));
+    assert_eq!(config.platform, Some(Platform::Schip));
+    assert!(config.mute);
+    let layout = config.layout.unwrap();
+    assert!(!layout.registers);
+    assert_eq!(layout.program, Panels::default().program);
+    assert!(Config::parse("[layout]\nsidebar = false").is_err());
+    assert!(Config::parse("platform = \"chip9\"").is_err());
+}
+
+#[test]
+fn speed_defaults_without_the_command_line() {
+    let cli = Cli::parse_from(["chipy8", "rom.ch8"]);
+    assert_eq!((cli.speed, cli.speed()), (None, DEFAULT_SPEED));
+    let cli = Cli::parse_from(["chipy8", "--speed", "1200", "rom.ch8"]);
+    assert_eq!(cli.speed(), 1200);
+}
+
+#[test]
+fn quirks_override_the_platform() {
+    let config =
+        Config::parse("platform = \"chip8\"\n[quirks]\nwrap_sprites = true\nvf_reset = false")
+            .unwrap();
+    let overrides = QuirkOverrides {
+        wrap_sprites: Some(true),
+        vf_reset: Some(false),
+        ..QuirkOverrides::default()
+    };
+    assert_eq!(config.quirks, overrides);
+    let quirks = config.quirks(config.platform);
+    assert!(quirks.wrap_sprites && !quirks.vf_reset);
+    assert!(quirks.shift_uses_vy && quirks.display_wait);
+    assert_eq!(
+        Config::default().quirks(Some(Platform::Xochip)),
+        Platform::Xochip.into()
+    );
+    assert!(Config::parse("[quirks]\nwrap_screen = true").is_err());
+
+    let text = format!("[quirks]\n{}", toml::to_string(&overrides).unwrap());
+    assert_eq!(Config::parse(&text).unwrap().quirks, overrides);
+}
+
+#[test]
+fn the_configs_platform_sets_up_the_machine() {
+    let rom = Rom::from_bytes("hires", vec![0x12, 0x60]);
+    let config = Config::parse("platform = \"hires\"\n[quirks]\nwrap_sprites = true").unwrap();
+    let cli = Cli::parse_from(["chipy8", "rom.ch8"]);
+    let chip8 = cli
+        .builder_with(rom.clone(), &RomSettings::default(), &config)
+        .build()
+        .unwrap();
+    assert!(chip8.two_page);
+    assert_eq!(chip8.program_counter, 0x2C0);
+    assert!(chip8.quirks.wrap_sprites && chip8.quirks.display_wait);
+
+    let cli = Cli::parse_from(["chipy8", "--platform", "schip", "rom.ch8"]);
+    let chip8 = cli
+        .builder_with(rom, &RomSettings::default(), &config)
+        .build()
+        .unwrap();
+    assert!(!chip8.two_page);
+    assert_eq!(chip8.quirks, config.quirks(Some(Platform::Schip)));
+}
+
+#[test]
+fn the_command_line_goes_before_the_rom_before_the_config() {
+    let config = Config::parse("speed = 1000\nkeymap = \"azerty\"").unwrap();
+    let remembered = RomSettings {
+        speed: Some(1500),
+        keymap: Some(Keymap::QWERTY),
+        ..RomSettings::default()
+    };
+    let cli = Cli::parse_from(["chipy8", "rom.ch8"]);
+    assert_eq!(cli.speed_with(&RomSettings::default(), &config), 1000);
+    assert_eq!(cli.speed_with(&remembered, &config), 1500);
+    assert_eq!(
+        cli.keymap_with("rom", &RomSettings::default(), &config),
+        Keymap::AZERTY
+    );
+    assert_eq!(cli.keymap_with("rom", &remembered, &config), Keymap::QWERTY);
+
+    let cli = Cli::parse_from(["chipy8", "--speed", "600", "--keymap", "azerty", "rom.ch8"]);
+    assert_eq!(cli.speed_with(&remembered, &config), 600);
+    assert_eq!(cli.keymap_with("rom", &remembered, &config), Keymap::AZERTY);
+    // a deterministic run doesn't go at a speed played at before
+    let cli = Cli::parse_from(["chipy8", "--deterministic", "rom.ch8"]);
+    assert_eq!(cli.speed_with(&remembered, &config), 1000);
+}
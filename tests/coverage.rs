@@ -0,0 +1,83 @@
+mod common;
+
+use chipy8::chip8::{Chip8, Quirks, Usage};
+use common::{program, rom};
+
+fn run(source: &str) -> Chip8 {
+    let mut chip8 = program(source);
+    while !chip8.is_spinning() {
+        chip8.step().unwrap();
+    }
+    chip8
+}
+
+#[test]
+fn coverage_tells_code_from_sprites_and_dead_code() {
+    let chip8 = run("
+        LD I, sprite
+        DRW V0, V0, 2
+        JP end
+        CLS
+    end:
+        JP end
+    sprite:
+        db 0xFF, 0x81, 0x7E
+    ");
+    let coverage = chip8.coverage();
+    assert!(coverage.executed(0x200) && coverage.executed(0x201));
+    assert_eq!(coverage.usage(0x206), Usage::Unreached);
+    // the jump to itself ends the run before it executes
+    assert_eq!(
+        coverage.regions(0x200..0x20d),
+        [
+            (0x200..0x206, Usage::Code),
+            (0x206..0x20a, Usage::Unreached),
+            (0x20a..0x20c, Usage::Sprite),
+            (0x20c..0x20d, Usage::Unreached),
+        ]
+    );
+}
+
+#[test]
+fn coverage_survives_loading_a_state() {
+    let mut chip8 = run("LD V0, 1\nend:\nJP end");
+    let mut loaded = Chip8::new(chip8.rom.clone());
+    loaded.take_debugging(&mut chip8);
+    assert!(loaded.coverage().executed(0x200));
+}
+
+#[test]
+fn coverage_counts_how_often_each_instruction_ran() {
+    let chip8 = run("
+        LD V0, 3
+    again:
+        ADD V0, 0xFF
+        SE V0, 0
+        JP again
+    end:
+        JP end
+    ");
+    let coverage = chip8.coverage();
+    assert_eq!(coverage.runs(0x200), 1);
+    assert_eq!((coverage.runs(0x202), coverage.runs(0x203)), (3, 3));
+    assert_eq!(coverage.runs(0x206), 2);
+    assert_eq!(coverage.runs(0x20A), 0);
+}
+
+#[test]
+fn sprites_wrap_around_the_memory_there_is() {
+    let mut chip8 = Chip8::builder(rom("LD I, 0x7FE\nDRW V0, V0, 4"))
+        .memory_size(0x800)
+        .quirks(Quirks {
+            wrap_memory: true,
+            ..Quirks::default()
+        })
+        .build()
+        .unwrap();
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    let coverage = chip8.coverage();
+    assert!((0x7FE..0x800).all(|address| coverage.sprite(address)));
+    assert!(coverage.sprite(0x000) && coverage.sprite(0x001));
+    assert!(!coverage.sprite(0x800) && !coverage.sprite(0x801));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use chipy8::chip8::{Instruction, VIP_CYCLES_PER_FRAME};
+use common::program;
+
+#[test]
+fn slow_instructions_cost_more_cycles() {
+    let cycles = |opcode| Instruction::decode(opcode).vip_cycles();
+    assert!(cycles(0x6000) < cycles(0x8014));
+    assert!(cycles(0x8014) < cycles(0xF033));
+    assert!(cycles(0xD001) < cycles(0xD00F));
+    assert!(cycles(0xF055) < cycles(0xF555));
+}
+
+#[test]
+fn a_vip_frame_runs_as_many_instructions_as_its_cycles_allow() {
+    let source = "
+    loop:
+        LD V0, 1
+        LD V1, 2
+        LD V2, 3
+        LD V3, 4
+        JP loop
+    ";
+    let mut chip8 = program(source);
+    let summary = chip8.step_vip_frame();
+    let (mut spent, mut last) = (0, 0);
+    let mut reference = program(source);
+    for _ in 0..summary.executed {
+        last = reference.vip_cycles();
+        spent += last;
+        reference.step().unwrap();
+    }
+    // the last instruction is the one that ran over the end of the frame
+    assert!(spent >= VIP_CYCLES_PER_FRAME);
+    assert!(spent - last < VIP_CYCLES_PER_FRAME);
+    assert_eq!(chip8.program_counter, reference.program_counter);
+}
+
+#[test]
+fn bcd_heavy_code_runs_fewer_instructions_a_frame() {
+    let loads = program("loop:\nLD V0, 1\nJP loop").step_vip_frame();
+    let bcd = program("LD I, 0x300\nloop:\nLD B, V0\nJP loop").step_vip_frame();
+    assert!(bcd.executed * 3 < loads.executed);
+}
@@ -0,0 +1,267 @@
+use chipy8::{
+    asm,
+    chip8::{Chip8, Watchpoint},
+    debugger::{
+        self, DebugCommand, EditCursor, Editor, StackFrame, StateDiff, Target, WatchExpression,
+    },
+    rom::Rom,
+};
+
+fn parse(line: &str) -> Result<DebugCommand, String> {
+    line.parse()
+}
+
+#[test]
+fn commands_parse() {
+    assert_eq!(parse("break 0x230"), Ok(DebugCommand::Break(0x230, None)));
+    assert_eq!(parse("b 230"), Ok(DebugCommand::Break(0x230, None)));
+    assert_eq!(
+        parse("break 0x2A4 if v0 == 3"),
+        Ok(DebugCommand::Break(0x2A4, Some("v0 == 3".parse().unwrap())))
+    );
+    assert_eq!(
+        parse("watch v3"),
+        Ok(DebugCommand::Watch(Watchpoint::Register(3)))
+    );
+    assert_eq!(
+        parse("w 0x300..0x310"),
+        Ok(DebugCommand::Watch(Watchpoint::Memory(0x300..0x310)))
+    );
+    assert_eq!(parse("mem 0x300"), Ok(DebugCommand::Mem(0x300)));
+    assert_eq!(
+        parse("set v1=0xFF"),
+        Ok(DebugCommand::Set(Target::V(1), 0xFF))
+    );
+    assert_eq!(
+        parse("set I = 0x2a0"),
+        Ok(DebugCommand::Set(Target::I, 0x2A0))
+    );
+    assert_eq!(parse("  continue "), Ok(DebugCommand::Continue));
+    assert_eq!(parse("c"), Ok(DebugCommand::Continue));
+    assert_eq!(parse("step 10"), Ok(DebugCommand::Step(10)));
+    assert_eq!(parse("step"), Ok(DebugCommand::Step(1)));
+}
+
+#[test]
+fn mistakes_are_explained() {
+    assert_eq!(parse("jump 0x200"), Err("no command `jump`".to_string()));
+    assert_eq!(parse(""), Err("no command".to_string()));
+    assert_eq!(parse("break"), Err("`break` needs an argument".to_string()));
+    assert_eq!(parse("break zz"), Err("bad number `zz`".to_string()));
+    assert_eq!(parse("set vg=1"), Err("no register `vg`".to_string()));
+    assert_eq!(
+        parse("set v1=0x100"),
+        Err("0x100 doesn't fit in V1".to_string())
+    );
+    assert_eq!(
+        parse("set v1 1"),
+        Err("`set` takes register=value, not `v1 1`".to_string())
+    );
+    assert_eq!(parse("step ten"), Err("bad step count `ten`".to_string()));
+    assert_eq!(
+        parse("break 0x200 if v0"),
+        Err("no comparison in `v0`".to_string())
+    );
+}
+
+#[test]
+fn set_writes_and_mem_shows() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("debugger", vec![0xA2, 0x1E, 0x60]));
+    Target::V(0xF).write(&mut chip8, 0xAB);
+    Target::Pc.write(&mut chip8, 0x202);
+    Target::Sound.write(&mut chip8, 3);
+    assert_eq!(chip8.registers[0xF], 0xAB);
+    assert_eq!(chip8.program_counter, 0x202);
+    assert_eq!(chip8.sound, 3);
+    assert_eq!(
+        debugger::dump(&chip8, 0x200),
+        "0x200  a2 1e 60 00 00 00 00 00 00 00 00 00 00 00 00 00"
+    );
+    assert_eq!(debugger::dump(&chip8, 0xFFE), "0xffe  00 00");
+}
+
+#[test]
+fn call_stack_lists_calls_innermost_first() {
+    let rom = asm::assemble(
+        "
+        CALL outer
+    end:
+        JP end
+    outer:
+        CALL inner
+        RET
+    inner:
+        RET
+        ",
+    )
+    .unwrap();
+    let mut chip8 = Chip8::new(Rom::from_bytes("debugger", rom));
+    assert_eq!(debugger::call_stack(&chip8), []);
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    let stack = debugger::call_stack(&chip8);
+    assert_eq!(
+        stack,
+        [
+            StackFrame {
+                return_address: 0x204,
+                subroutine: Some(0x208),
+            },
+            StackFrame {
+                return_address: 0x200,
+                subroutine: Some(0x204),
+            },
+        ]
+    );
+    assert_eq!(stack[0].to_string(), "0x204 sub_208");
+    chip8.step().unwrap();
+    assert_eq!(debugger::call_stack(&chip8).len(), 1);
+}
+
+#[test]
+fn editor_writes_as_digits_are_typed() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("debugger", vec![0x60, 0x01]));
+    let mut editor = Editor::new(EditCursor::Memory(0x200));
+    assert_eq!(editor.type_digit('a', &mut chip8), None);
+    assert_eq!(editor.pending(), "a");
+    assert_eq!(
+        editor.type_digit('F', &mut chip8),
+        Some("0x200 set to 0xaf".to_string())
+    );
+    // on to the next byte, where one digit is enough with commit
+    assert_eq!(editor.cursor, EditCursor::Memory(0x201));
+    editor.type_digit('7', &mut chip8);
+    assert_eq!(
+        editor.commit(&mut chip8),
+        Some("0x201 set to 0x07".to_string())
+    );
+    assert_eq!(chip8.memory[0x200..0x202], [0xAF, 0x07]);
+    assert_eq!(editor.type_digit('g', &mut chip8), None);
+    editor.move_by(-0x1000, &chip8);
+    assert_eq!(editor.cursor, EditCursor::Memory(0));
+
+    let mut editor = Editor::new(EditCursor::Register(Target::V(0xF)));
+    editor.move_by(3, &chip8);
+    assert_eq!(editor.cursor, EditCursor::Register(Target::I));
+    for digit in "2a0".chars() {
+        assert_eq!(editor.type_digit(digit, &mut chip8), None);
+    }
+    assert_eq!(
+        editor.type_digit('1', &mut chip8),
+        Some("I set to 0x2a01".to_string())
+    );
+    assert_eq!(Target::I.read(&chip8), 0x2A01);
+    // registers stay put after a write, and the cursor stops at the last
+    editor.move_by(10, &chip8);
+    assert_eq!(editor.cursor, EditCursor::Register(Target::Pc));
+}
+
+#[test]
+fn watch_expressions_evaluate() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("debugger", vec![0xA2, 0x1E]));
+    chip8.registers[0] = 3;
+    chip8.registers[1] = 0xFF;
+    chip8.i = 0x200;
+    let watch = |text: &str| text.parse::<WatchExpression>().unwrap();
+    assert_eq!(watch("v0 + v1").evaluate(&chip8), 0x102);
+    assert_eq!(watch("[i] - 1 + [0x201]").evaluate(&chip8), 0xA1 + 0x1E);
+    assert_eq!(watch("-1").evaluate(&chip8), 0xFFFF);
+    assert_eq!(watch("v0 == 3 && i > 0x1ff").evaluate(&chip8), 1);
+    assert_eq!(watch("v1 < v0").evaluate(&chip8), 0);
+    assert_eq!(watch("v0+ [i]-1").to_string(), "v0 + [i] - 0x1");
+    assert_eq!(
+        parse("display v0 + 1"),
+        Ok(DebugCommand::Display(watch("v0 + 1")))
+    );
+    assert_eq!(parse("undisplay 2"), Ok(DebugCommand::Undisplay(2)));
+    assert_eq!(parse("d v0 +"), Err("missing operand".to_string()));
+    assert_eq!(
+        parse("undisplay v0"),
+        Err("bad watch number `v0`".to_string())
+    );
+}
+
+#[test]
+fn find_parses_hex_and_text_and_scans_memory() {
+    assert_eq!(
+        parse("find de ad be ef"),
+        Ok(DebugCommand::Find(Some(vec![0xDE, 0xAD, 0xBE, 0xEF])))
+    );
+    assert_eq!(
+        parse("f 0xdead beef"),
+        Ok(DebugCommand::Find(Some(vec![0xDE, 0xAD, 0xBE, 0xEF])))
+    );
+    assert_eq!(
+        parse("find \"HI 2\""),
+        Ok(DebugCommand::Find(Some(b"HI 2".to_vec())))
+    );
+    assert_eq!(parse("find"), Ok(DebugCommand::Find(None)));
+    assert_eq!(
+        parse("find dea"),
+        Err("`dea` isn't whole bytes".to_string())
+    );
+    assert_eq!(parse("find zz"), Err("bad byte `zz`".to_string()));
+    assert_eq!(parse("find \"\""), Err("bad text `\"\"`".to_string()));
+
+    let rom = vec![0x12, 0x34, 0x00, 0x12, 0x34];
+    let chip8 = Chip8::new(Rom::from_bytes("debugger", rom));
+    assert_eq!(debugger::find(&chip8, &[0x12, 0x34]), [0x200, 0x203]);
+    assert_eq!(debugger::find(&chip8, &[0x34, 0x12]), []);
+    assert_eq!(debugger::bytes(&[0xDE, 0xAD]), "de ad");
+}
+
+#[test]
+fn state_diff_lists_what_changed() {
+    let rom = asm::assemble(
+        "
+        LD V3, 5
+        LD I, 0x300
+        LD [I], V3
+        LD F, V3
+        DRW V0, V0, 5
+        ",
+    )
+    .unwrap();
+    let mut chip8 = Chip8::new(Rom::from_bytes("debugger", rom));
+    let marked = chip8.clone();
+    assert!(StateDiff::between(&marked, &chip8).is_empty());
+    for _ in 0..5 {
+        chip8.step().unwrap();
+    }
+    let diff = StateDiff::between(&marked, &chip8);
+    assert_eq!(
+        diff.registers,
+        [
+            ("V3".to_string(), 0x0, 0x5),
+            ("I".to_string(), 0x0, 0x19),
+            ("PC".to_string(), 0x200, 0x20A),
+        ]
+    );
+    // V0 to V2 were stored too, but unchanged
+    assert_eq!(diff.memory.len(), 1);
+    assert_eq!(diff.memory[0], 0x303..0x304);
+    // the 5's font sprite
+    assert_eq!(diff.display, Some((0..4, 0..5)));
+    assert_eq!(
+        diff.to_string().lines().last(),
+        Some("14 pixels in x 0..4, y 0..5")
+    );
+}
+
+#[test]
+fn program_window_stays_inside_memory() {
+    let memory = 0x1000;
+    // the focus in the middle, 10 instructions down
+    assert_eq!(debugger::program_window(0x300, 10, 20, memory), 0x2EC);
+    // near the start and the end the window stops at them
+    assert_eq!(debugger::program_window(0x004, 10, 20, memory), 0x000);
+    assert_eq!(debugger::program_window(0xFFE, 10, 20, memory), 0xFD8);
+    assert_eq!(debugger::program_window(0xFF0, 10, 20, memory), 0xFD8);
+    // an odd focus keeps to odd addresses
+    assert_eq!(debugger::program_window(0x003, 10, 20, memory), 0x001);
+    assert_eq!(debugger::program_window(0xFFD, 10, 20, memory), 0xFD7);
+    // past the end comes back to the last instruction
+    assert_eq!(debugger::program_window(0x2000, 0, 1, memory), 0xFFE);
+    // more rows than memory has
+    assert_eq!(debugger::program_window(0x008, 2, 100, 0x10), 0x000);
+}
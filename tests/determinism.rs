@@ -0,0 +1,59 @@
+//! Identical inputs must produce identical traces
+use chipy8::{
+    chip8::{Chip8, TimerSource},
+    determinism::{self, InputScript, DEFAULT_SEED},
+    rom::Rom,
+};
+
+const STEPS: u64 = 3000;
+
+fn trace(rom: &[u8], seed: u64, script: &InputScript) -> Vec<determinism::TraceEntry> {
+    let mut chip8 = Chip8::new(Rom::from_bytes("determinism", rom.to_vec()))
+        .with_seed(seed)
+        .with_timer_source(TimerSource::Instructions(500));
+    determinism::run(&mut chip8, script, STEPS)
+}
+
+#[test]
+fn identical_inputs_produce_identical_traces() {
+    // pong reads the keypad and leans on Cxkk for the ball
+    let rom = include_bytes!("../ROMS/PONG");
+    let script = InputScript::parse("# hold up, then down\n200 1\n900 4\n1500 0\n").unwrap();
+
+    let first = trace(rom, DEFAULT_SEED, &script);
+    let second = trace(rom, DEFAULT_SEED, &script);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn seed_changes_random_outcomes() {
+    let rom = include_bytes!("../ROMS/MAZE");
+    let script = InputScript::default();
+    assert_ne!(trace(rom, 1, &script), trace(rom, 2, &script));
+}
+
+#[test]
+fn injected_random_source_feeds_cxkk() {
+    // RND V0, 0xf0 then RND V1, 0xff
+    let rom = [0xC0, 0xF0, 0xC1, 0xFF];
+    let mut bytes = [0xAB, 0x12].into_iter();
+    let mut chip8 = Chip8::new(Rom::from_bytes("random", rom.to_vec()))
+        .with_random(move || bytes.next().unwrap_or(0));
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    assert_eq!(chip8.registers[..2], [0xA0, 0x12]);
+}
+
+#[test]
+fn script_lookup() {
+    let script = InputScript::parse("10 a\n\n# comment\n5 3\n10 b\n20 -\n").unwrap();
+    assert_eq!(script.key_at(0), None);
+    assert_eq!(script.key_at(5), Some(3));
+    assert_eq!(script.key_at(9), Some(3));
+    assert_eq!(script.key_at(10), Some(0xb));
+    assert_eq!(script.keys_at(19), 1 << 0xb);
+    assert_eq!(script.key_at(u64::MAX), None);
+    assert_eq!(script.keys_at(u64::MAX), 0);
+    assert!(InputScript::parse("1 10").is_err());
+    assert!(InputScript::parse("x 1").is_err());
+}
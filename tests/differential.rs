@@ -0,0 +1,102 @@
+//! Runs every bundled rom through the emulator and the reference interpreter in
+//! lockstep, failing on the first instruction where they disagree.
+use std::{fs, path::PathBuf};
+
+use chipy8::{
+    chip8::{Chip8, Quirks},
+    reference,
+    rom::Rom,
+};
+
+const STEPS: usize = 3000;
+
+/// Hold each key in turn for a while, with gaps where none is held, so input
+/// dependent paths get exercised
+fn input(step: usize) -> u16 {
+    match step / 150 % 17 {
+        16 => 0,
+        key => 1 << key,
+    }
+}
+
+fn bundled_roms() -> Vec<PathBuf> {
+    let mut roms: Vec<_> = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/ROMS"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    roms.sort();
+    roms
+}
+
+#[test]
+fn bundled_roms_match_reference() {
+    let failures: Vec<String> = bundled_roms()
+        .iter()
+        .filter_map(|path| {
+            let mut chip8 = Chip8::new(Rom::new(path).unwrap());
+            reference::lockstep(&mut chip8, STEPS, input)
+                .err()
+                .map(|divergence| format!("{}: {divergence}", path.display()))
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn bundled_roms_match_reference_with_every_quirk() {
+    let quirks = Quirks {
+        shift_uses_vy: true,
+        load_store_increments_i: true,
+        jump_uses_vx: true,
+        vf_reset: true,
+        wrap_sprites: true,
+        wrap_memory: true,
+        display_wait: true,
+    };
+    let failures: Vec<String> = bundled_roms()
+        .iter()
+        .filter_map(|path| {
+            let mut chip8 = Chip8::new(Rom::new(path).unwrap()).with_quirks(quirks);
+            reference::lockstep(&mut chip8, STEPS, input)
+                .err()
+                .map(|divergence| format!("{}: {divergence}", path.display()))
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// None of the bundled roms are SUPER-CHIP, so exercise its opcodes by hand
+#[test]
+fn schip_program_matches_reference() {
+    #[rustfmt::skip]
+    let program: [u16; 20] = [
+        0x00FF,         // hi-res
+        0x6005, 0x6103, // V0 = 5, V1 = 3
+        0x6207, 0xF230, // I = large 7
+        0xD01A,         // draw it
+        0xA300, 0xD010, // 16x16 sprite from 0x300
+        0x00FB, 0x00C7, // scroll right, scroll down 7
+        0x607D, 0xD010, // again at x = 125, clipped or wrapped
+        0x00FC, 0x00FC, // scroll left twice
+        0x00FE,         // back to lo-res
+        0x603C, 0x611E, // V0 = 60, V1 = 30
+        0xD010,         // a large sprite in lo-res
+        0x00C1,
+        0x00FD,         // exit
+    ];
+    let mut rom: Vec<u8> = program.iter().flat_map(|op| op.to_be_bytes()).collect();
+    rom.resize(0x100, 0);
+    rom.extend((0..32).map(|b| 0xA5 ^ b));
+
+    for wrap_sprites in [false, true] {
+        let quirks = Quirks {
+            wrap_sprites,
+            ..Quirks::default()
+        };
+        let mut chip8 = Chip8::new(Rom::from_bytes("schip", rom.clone())).with_quirks(quirks);
+        reference::lockstep(&mut chip8, program.len() + 2, |_| 0).unwrap();
+        assert!(chip8.is_spinning());
+    }
+}
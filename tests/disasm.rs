@@ -0,0 +1,63 @@
+//! The disassembler's listing of small hand-assembled programs
+use chipy8::{
+    chip8::Instruction,
+    disasm::{self, Kind, Label},
+    rom::Rom,
+};
+
+fn listing(program: &[u8]) -> disasm::Listing {
+    disasm::disassemble_rom(&Rom::from_bytes("disasm", program.to_vec()))
+}
+
+#[test]
+fn sprite_after_the_code_is_data() {
+    #[rustfmt::skip]
+    let program = [
+        0xA2, 0x06, // LD I, 0x206
+        0xD0, 0x13, // DRW V0, V1, 3
+        0x12, 0x04, // JP 0x204
+        0x3C, 0x42, 0x3C, // the sprite
+    ];
+    let listing = listing(&program);
+    let kinds: Vec<Kind> = listing.lines.iter().map(|line| line.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            Kind::Code(Instruction::LdI(0x206)),
+            Kind::Code(Instruction::Drw(0, 1, 3)),
+            Kind::Code(Instruction::Jp(0x204)),
+            Kind::Data(0x3C),
+            Kind::Data(0x42),
+            Kind::Data(0x3C),
+        ]
+    );
+    assert_eq!(listing.lines[2].label, Some(Label::Jump));
+    assert_eq!(listing.lines[3].label, Some(Label::Data));
+    assert_eq!(
+        listing.lines[3].to_string(),
+        "data_206:\n0x206  3c     DB 0x3c  ; ..####.."
+    );
+}
+
+#[test]
+fn data_stops_at_code() {
+    #[rustfmt::skip]
+    let program = [
+        0x22, 0x08, // CALL 0x208
+        0xA2, 0x06, // LD I, 0x206
+        0x12, 0x04, // JP 0x204
+        0xFF, 0x81, // the sprite
+        0x00, 0xEE, // RET
+    ];
+    let text = listing(&program).to_string();
+    assert!(text.contains("0x207  81     DB 0x81"), "{text}");
+    assert!(text.contains("sub_208:\n0x208  00 ee  RET"), "{text}");
+}
+
+#[test]
+fn listing_covers_every_byte() {
+    let rom = include_bytes!("../ROMS/PONG");
+    let listing = listing(rom);
+    let bytes: Vec<u8> = listing.lines.iter().flat_map(|l| l.bytes.clone()).collect();
+    assert_eq!(bytes, rom);
+}
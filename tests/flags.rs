@@ -0,0 +1,42 @@
+mod common;
+
+use std::{env, fs};
+
+use chipy8::{chip8::Chip8, rom::Rom};
+use common::program;
+
+#[test]
+fn flags_keep_registers_across_runs() {
+    let path = env::temp_dir().join(format!("chipy8-flags-{}.bin", std::process::id()));
+    let mut first = program("LD V0, 7\nLD V1, 9\nLD R, V1");
+    for _ in 0..3 {
+        first.step().unwrap();
+    }
+    assert_eq!(first.flags[..3], [7, 9, 0]);
+    first.save_flags(&path).unwrap();
+
+    let mut second = program("LD V1, R");
+    second.load_flags(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    second.step().unwrap();
+    assert_eq!(second.registers[..2], [7, 9]);
+}
+
+#[test]
+fn unset_flags_write_no_file_and_a_missing_file_is_no_flags() {
+    let path = env::temp_dir().join(format!("chipy8-no-flags-{}.bin", std::process::id()));
+    let mut chip8 = program("LD V0, R");
+    chip8.save_flags(&path).unwrap();
+    assert!(!path.exists());
+    chip8.load_flags(&path).unwrap();
+    assert_eq!(chip8.flags, [0; 16]);
+}
+
+#[test]
+fn flags_are_kept_per_rom_contents() {
+    let a = program("LD V0, 1");
+    let b = program("LD V0, 2");
+    let renamed = Chip8::new(Rom::from_bytes("other", a.rom.contents.clone()));
+    assert_ne!(a.default_flags_path(), b.default_flags_path());
+    assert_eq!(a.default_flags_path(), renamed.default_flags_path());
+}
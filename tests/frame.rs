@@ -0,0 +1,88 @@
+mod common;
+
+use chipy8::chip8::{Chip8Error, FrameSummary};
+use common::program;
+
+#[test]
+fn frame_runs_its_instructions_then_ticks_the_timers_once() {
+    let mut chip8 = program(
+        "
+        LD V0, 3
+        LD DT, V0
+    loop:
+        ADD V1, 1
+        JP loop
+        ",
+    );
+    let summary = chip8.step_frame(10);
+    assert_eq!(
+        summary,
+        FrameSummary {
+            executed: 10,
+            ..FrameSummary::default()
+        }
+    );
+    assert_eq!(chip8.delay, 2);
+    assert_eq!(chip8.registers[1], 4);
+}
+
+#[test]
+fn frame_reports_drawing_sound_and_halting() {
+    let mut chip8 = program(
+        "
+        LD V0, 2
+        LD ST, V0
+        LD F, V0
+        DRW V0, V0, 5
+    end:
+        JP end
+        ",
+    );
+    let summary = chip8.step_frame(20);
+    assert_eq!(summary.executed, 4);
+    assert!(summary.drew && summary.sound_started && summary.halted);
+    assert_eq!(summary.error, None);
+    assert_eq!(chip8.sound, 1);
+}
+
+#[test]
+fn frame_stops_at_an_error() {
+    let mut chip8 = program("LD V0, 1\nRET\nLD V0, 2");
+    let summary = chip8.step_frame(20);
+    assert_eq!(summary.executed, 1);
+    assert_eq!(
+        summary.error,
+        Some(Chip8Error::StackUnderflow { address: 0x202 })
+    );
+}
+
+#[test]
+fn run_until_draw_stops_at_the_first_change() {
+    let mut chip8 = program(
+        "
+        LD V0, 1
+        LD F, V0
+    loop:
+        DRW V0, V0, 5
+        ADD V1, 1
+        JP loop
+        ",
+    );
+    assert_eq!(chip8.run_until_draw(100), Ok(3));
+    assert_eq!(chip8.program_counter, 0x206);
+    // the next draw erases the sprite, a change just the same
+    assert_eq!(chip8.run_until_draw(100), Ok(3));
+    assert!(chip8.display.iter().all(|&byte| byte == 0));
+    assert_eq!(chip8.run_until_draw(2), Ok(2));
+    assert_eq!(chip8.registers[1], 2);
+}
+
+#[test]
+fn run_until_draw_stops_once_nothing_can_draw() {
+    let mut chip8 = program("LD V0, 1\nend: JP end");
+    assert_eq!(chip8.run_until_draw(100), Ok(1));
+    assert!(chip8.is_spinning());
+    let mut chip8 = program("LD V0, K\nCLS");
+    assert_eq!(chip8.run_until_draw(100), Ok(1));
+    assert!(chip8.is_waiting_for_key());
+}
@@ -0,0 +1,37 @@
+use chipy8::{asm, chip8::Chip8, rom::Rom};
+
+#[test]
+fn set_pixel_writes_single_pixels() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("pixels", vec![]));
+    chip8.set_pixel(9, 3, true);
+    chip8.set_pixel(10, 3, true);
+    chip8.set_pixel(9, 3, false);
+    assert!(!chip8.pixel(9, 3));
+    assert!(chip8.pixel(10, 3));
+    assert_eq!(chip8.display.iter().map(|b| b.count_ones()).sum::<u32>(), 1);
+}
+
+#[test]
+fn take_dirty_reports_each_change_once() {
+    let rom = asm::assemble(
+        "
+        LD V0, 62
+        LD V1, 5
+        LD I, dot
+        DRW V0, V1, 1
+        CLS
+    dot:
+        db 0b11000000
+        ",
+    )
+    .unwrap();
+    let mut chip8 = Chip8::new(Rom::from_bytes("dirty", rom));
+    assert_eq!(chip8.take_dirty(), vec![]);
+    for _ in 0..4 {
+        chip8.step().unwrap();
+    }
+    assert_eq!(chip8.take_dirty(), vec![(62, 5), (63, 5)]);
+    assert_eq!(chip8.take_dirty(), vec![]);
+    chip8.step().unwrap();
+    assert_eq!(chip8.take_dirty(), vec![(62, 5), (63, 5)]);
+}
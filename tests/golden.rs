@@ -0,0 +1,84 @@
+//! Golden display snapshots
+//!
+//! Runs bundled roms headlessly and compares the final framebuffer against the plain
+//! PBM images in `tests/golden/`. After an intentional rendering change, re-bless with
+//!
+//! ```sh
+//! CHIPY8_BLESS=1 cargo test --test golden
+//! ```
+//!
+//! and review the changed images before committing them.
+//!
+//! Only roms that never reach Cxkk within the run are included, so snapshots don't
+//! depend on the random number generator.
+use std::{env, fs, path::PathBuf};
+
+use chipy8::{chip8::Chip8, rom::Rom};
+
+const STEPS: usize = 2000;
+
+/// Render the display as a plain (P1) PBM image
+fn to_pbm(chip8: &Chip8) -> String {
+    let mut pbm = format!("P1\n{} {}\n", chip8.width(), chip8.height());
+    for y in 0..chip8.height() {
+        for x in 0..chip8.width() {
+            pbm.push(if chip8.pixel(x, y) { '1' } else { '0' });
+        }
+        pbm.push('\n');
+    }
+    pbm
+}
+
+fn check(name: &str, contents: &[u8]) {
+    let mut chip8 = Chip8::new(Rom::from_bytes(name, contents.to_vec()));
+    for _ in 0..STEPS {
+        chip8.step().unwrap();
+        chip8.tick_timers();
+    }
+    let actual = to_pbm(&chip8);
+
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name]
+        .iter()
+        .collect::<PathBuf>()
+        .with_extension("pbm");
+    if env::var_os("CHIPY8_BLESS").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing golden image {}, run with CHIPY8_BLESS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "{name} display differs from {}\nexpected:\n{expected}\nactual:\n{actual}",
+        path.display()
+    );
+}
+
+macro_rules! golden {
+    ($($test:ident => $rom:literal),* $(,)?) => {
+        $(
+            #[test]
+            fn $test() {
+                check($rom, include_bytes!(concat!("../ROMS/", $rom)));
+            }
+        )*
+    };
+}
+
+golden! {
+    blinky => "BLINKY",
+    connect4 => "CONNECT4",
+    fifteen_puzzle => "15PUZZLE",
+    guess => "GUESS",
+    invaders => "INVADERS",
+    kaleid => "KALEID",
+    missile => "MISSILE",
+    syzygy => "SYZYGY",
+    tictac => "TICTAC",
+    vbrix => "VBRIX",
+    vers => "VERS",
+}
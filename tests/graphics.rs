@@ -0,0 +1,92 @@
+use chipy8::{
+    chip8::Chip8,
+    graphics::{self, Graphics, Raster},
+    phosphor::Phosphor,
+    rom::Rom,
+};
+use ratatui::style::Color;
+
+fn chip8() -> Chip8 {
+    let mut chip8 = Chip8::new(Rom::from_bytes("graphics", vec![0x00, 0xE0]));
+    chip8.display[0] = 0x80;
+    chip8
+}
+
+#[test]
+fn auto_goes_by_the_terminal() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+    let auto = Graphics::Auto;
+    assert_eq!(
+        auto.detect(env(&[("TERM", "xterm-kitty")])),
+        Graphics::Kitty
+    );
+    assert_eq!(
+        auto.detect(env(&[("TERM", "xterm-256color"), ("KITTY_WINDOW_ID", "1")])),
+        Graphics::Kitty
+    );
+    assert_eq!(auto.detect(env(&[("TERM", "foot")])), Graphics::Sixel);
+    assert_eq!(
+        auto.detect(env(&[("TERM", "xterm-256color")])),
+        Graphics::Cells
+    );
+    assert_eq!(
+        Graphics::Sixel.detect(env(&[("TERM", "xterm-kitty")])),
+        Graphics::Sixel
+    );
+    assert_eq!("kitty".parse(), Ok(Graphics::Kitty));
+}
+
+#[test]
+fn images_scale_by_whole_pixels() {
+    // 64 columns of 8 pixels, 16 rows of 16
+    assert_eq!(graphics::scale(64, 32, 64, 16, (8, 16)), 8);
+    assert_eq!(graphics::scale(128, 64, 64, 16, (8, 16)), 4);
+    assert_eq!(graphics::scale(64, 32, 80, 8, (8, 16)), 4);
+    assert_eq!(graphics::scale(64, 32, 4, 2, (8, 16)), 1);
+}
+
+#[test]
+fn sixel_draws_each_color_a_band_at_a_time() {
+    let raster = Raster::new(&chip8(), None, Color::White, Color::Black);
+    let sixel = raster.sixel(1);
+
+    assert!(sixel.starts_with("\x1bP0;1;0q\"1;1;64;32#0;2;0;0;0#1;2;100;100;100"));
+    assert!(sixel.ends_with("\x1b\\"));
+    // the lit pixel is the top of the first sixel, the rest of it unlit
+    assert!(sixel.contains("#0}!63~$#1@!63?$-"), "{sixel}");
+    assert_eq!(sixel.matches('-').count(), 6);
+}
+
+#[test]
+fn phosphor_adds_the_colors_between() {
+    let mut chip8 = chip8();
+    let mut phosphor = Phosphor::new(4);
+    phosphor.update(&chip8, 1);
+    chip8.display[0] = 0;
+    phosphor.update(&chip8, 1);
+
+    let glowing = Raster::new(&chip8, Some(&phosphor), Color::White, Color::Black);
+    let dark = Raster::new(&chip8, None, Color::White, Color::Black);
+    assert_ne!(glowing, dark);
+    assert!(glowing.sixel(1).contains("#3;2;74;74;74"));
+}
+
+#[test]
+fn kitty_sends_a_png_in_chunks() {
+    let raster = Raster::new(&chip8(), None, Color::White, Color::Black);
+    let kitty = raster.kitty(8).unwrap();
+
+    assert!(kitty.starts_with("\x1b_Ga=T,f=100,i=1,p=1,q=2,C=1,m="));
+    // the base64 of the PNG signature
+    assert!(kitty.contains(";iVBORw0KGgo"));
+    assert!(kitty.ends_with("\x1b\\"));
+    let chunks: Vec<&str> = kitty.split("\x1b\\").filter(|c| !c.is_empty()).collect();
+    assert!(chunks.last().unwrap().starts_with("\x1b_Gm=0;") || chunks.len() == 1);
+    assert!(chunks.iter().all(|chunk| chunk.len() <= 4096 + 40));
+}
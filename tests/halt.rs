@@ -0,0 +1,54 @@
+mod common;
+
+use chipy8::chip8::{RunOutcome, State, StepOutcome};
+use common::program;
+
+#[test]
+fn jump_to_self_halts() {
+    let mut chip8 = program("LD V0, 1\nend: JP end");
+    chip8.step().unwrap();
+    assert!(chip8.is_spinning());
+    assert!(!chip8.is_halted());
+    assert_eq!(chip8.step(), Ok(StepOutcome::Executed));
+    assert_eq!(chip8.state, State::Halted);
+    assert_eq!(chip8.program_counter, 0x202);
+    assert_eq!(chip8.step(), Ok(StepOutcome::Halted));
+    assert_eq!(chip8.program_counter, 0x202);
+    assert!(chip8.is_spinning());
+}
+
+#[test]
+fn exit_halts() {
+    let mut chip8 = program("EXIT");
+    chip8.step().unwrap();
+    assert!(chip8.is_halted());
+    assert_eq!(chip8.program_counter, 0x200);
+}
+
+#[test]
+fn other_jumps_run_on() {
+    let mut chip8 = program("JP next\nnext: JP 0x200");
+    for _ in 0..10 {
+        assert_eq!(chip8.step(), Ok(StepOutcome::Executed));
+    }
+    assert_eq!(chip8.state, State::Running);
+}
+
+#[test]
+fn timers_still_run_once_halted() {
+    let mut chip8 = program("LD V0, 2\nLD DT, V0\nend: JP end");
+    for _ in 0..3 {
+        chip8.step().unwrap();
+    }
+    assert!(chip8.is_halted());
+    chip8.tick_timers();
+    assert_eq!(chip8.delay, 1);
+    assert!(chip8.is_halted());
+}
+
+#[test]
+fn running_stops_at_a_halt() {
+    let mut chip8 = program("LD V0, 1\nend: JP end");
+    assert_eq!(chip8.run_until_break(100), Ok(RunOutcome::Halted));
+    assert_eq!(chip8.registers[0], 1);
+}
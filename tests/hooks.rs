@@ -0,0 +1,76 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::program;
+
+/// Run until the program ends or fails, returning what the hooks saw
+fn events(source: &str) -> Vec<String> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut chip8 = program(source);
+    let log = |events: &Arc<Mutex<Vec<String>>>| {
+        let events = events.clone();
+        move |event: String| events.lock().unwrap().push(event)
+    };
+    let push = log(&events);
+    chip8.on_display_update(move |chip8| push(format!("display {}", chip8.pixel(0, 0))));
+    let push = log(&events);
+    chip8.on_sound_start(move || push("sound".into()));
+    let push = log(&events);
+    chip8.on_key_wait(move |register| push(format!("key V{register:X}")));
+    let push = log(&events);
+    chip8.on_unknown_opcode(move |address, opcode| {
+        push(format!("unknown {address:#05x} {opcode:04x}"))
+    });
+    for _ in 0..20 {
+        if chip8.is_spinning() || chip8.is_waiting_for_key() || chip8.step().is_err() {
+            break;
+        }
+    }
+    let events = events.lock().unwrap().clone();
+    events
+}
+
+#[test]
+fn hooks_hear_about_the_display_changing() {
+    assert_eq!(
+        events(
+            "
+            LD V0, 0
+            LD F, V0
+            DRW V0, V0, 5
+            LD V1, 1
+            CLS
+        end:
+            JP end
+            "
+        ),
+        ["display true", "display false"]
+    );
+}
+
+#[test]
+fn hooks_hear_about_sound_starting_once() {
+    assert_eq!(
+        events(
+            "
+            LD V0, 5
+            LD ST, V0
+            LD ST, V0
+        end:
+            JP end
+            "
+        ),
+        ["sound"]
+    );
+}
+
+#[test]
+fn hooks_hear_about_waiting_for_a_key() {
+    assert_eq!(events("LD V0, 1\nLD V3, K"), ["key V3"]);
+}
+
+#[test]
+fn hooks_hear_about_unknown_opcodes() {
+    assert_eq!(events("LD V0, 1\ndb 0xFF, 0xFF"), ["unknown 0x202 ffff"]);
+}
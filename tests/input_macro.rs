@@ -0,0 +1,43 @@
+use std::{env, fs};
+
+use chipy8::input_macro::{InputMacro, RecordedMacros};
+
+#[test]
+fn recorded_frames_write_as_steps() {
+    let frames = [None, None, Some(5), Some(5), Some(5), None, Some(0xA)];
+    let input_macro = InputMacro::from_frames(frames);
+    assert_eq!(input_macro.len(), 7);
+    assert_eq!(
+        input_macro.to_string(),
+        "wait 2, hold 5 3, wait 1, hold a 1"
+    );
+
+    let parsed: InputMacro = input_macro.to_string().parse().unwrap();
+    assert_eq!(parsed, input_macro);
+    assert!(parsed.frames().eq(frames));
+    assert!(InputMacro::from_frames([]).is_empty());
+}
+
+#[test]
+fn recorded_macros_round_trip_through_a_file() {
+    let path = env::temp_dir().join(format!("chipy8-macros-{}.toml", std::process::id()));
+    assert_eq!(
+        RecordedMacros::load(Some(&path)).unwrap(),
+        RecordedMacros::default()
+    );
+
+    let mut recorded = RecordedMacros::default();
+    let menu = InputMacro::from_frames([Some(2), Some(2), None, Some(6)]);
+    recorded.roms.insert("BLITZ".to_string(), menu.clone());
+    recorded.save(Some(&path)).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    let loaded = RecordedMacros::load(Some(&path)).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(
+        text.contains("BLITZ = \"hold 2 2, wait 1, hold 6 1\""),
+        "{text}"
+    );
+    assert_eq!(loaded, recorded);
+    assert_eq!(loaded.roms["BLITZ"], menu);
+}
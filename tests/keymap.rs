@@ -0,0 +1,48 @@
+use chipy8::keymap::{Keymap, FRONTEND_KEYS};
+
+#[test]
+fn presets_and_custom_keymaps_parse() {
+    assert_eq!("qwerty".parse(), Ok(Keymap::QWERTY));
+    assert_eq!("Dvorak".parse(), Ok(Keymap::DVORAK));
+    let keymap: Keymap = "x123qweasdzc4rfv".parse().unwrap();
+    assert_eq!(keymap.key('x'), Some(0));
+    assert_eq!(keymap.key('v'), Some(0xF));
+    assert_eq!(keymap.key('p'), None);
+    assert_eq!(keymap.char(4), 'q');
+    assert_eq!(keymap.to_string(), "x123qweasdzc4rfv");
+    assert_eq!(Keymap::AZERTY.to_string(), "azerty");
+}
+
+#[test]
+fn the_same_keypad_block_under_each_layout() {
+    assert_eq!(Keymap::QWERTY.key('q'), Some(4));
+    assert_eq!(Keymap::AZERTY.key('a'), Some(4));
+    assert_eq!(Keymap::AZERTY.key('é'), Some(1));
+    assert_eq!(Keymap::DVORAK.key('\''), Some(4));
+    assert_eq!(Keymap::default(), Keymap::QWERTY);
+}
+
+#[test]
+fn bad_keymaps_are_explained() {
+    assert_eq!(
+        "colemak".parse::<Keymap>(),
+        Err("`colemak` is neither qwerty, azerty, dvorak nor 16 characters".to_string())
+    );
+    assert_eq!(
+        "1234qwerasdfzxcq".parse::<Keymap>(),
+        Err("`q` is in `1234qwerasdfzxcq` twice".to_string())
+    );
+}
+
+#[test]
+fn no_preset_takes_a_frontend_key() {
+    for (name, keymap) in Keymap::PRESETS {
+        for key in 0..16 {
+            let c = keymap.char(key);
+            assert!(
+                !FRONTEND_KEYS.contains(c),
+                "{name} puts key {key:X} on `{c}`, a frontend key"
+            );
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use chipy8::keypad::{KeypadActivity, PULSE_FRAMES};
+
+#[test]
+fn presses_are_counted_as_keys_go_down() {
+    let mut activity = KeypadActivity::new();
+    activity.update(0b1, 1);
+    activity.update(0b1, 1);
+    activity.update(0b0, 1);
+    activity.update(0b101, 1);
+    assert!(activity.is_held(0) && activity.is_held(2));
+    assert_eq!(activity.presses(0), 2);
+    assert_eq!(activity.presses(2), 1);
+    assert_eq!(activity.presses(1), 0);
+    assert_eq!(activity.most_presses(), 2);
+}
+
+#[test]
+fn keys_flash_when_pressed_and_fade_when_let_go() {
+    let mut activity = KeypadActivity::new();
+    assert_eq!(activity.pulse(5), 0.0);
+    activity.update(1 << 5, 0);
+    assert_eq!(activity.pulse(5), 1.0);
+    activity.update(1 << 5, u32::from(PULSE_FRAMES) / 2);
+    assert_eq!(activity.pulse(5), 0.5);
+    activity.update(1 << 5, 100);
+    assert_eq!(activity.pulse(5), 0.0);
+
+    // and again as it comes up
+    activity.update(0, 0);
+    assert_eq!(activity.pulse(5), 1.0);
+    activity.update(0, u32::from(PULSE_FRAMES));
+    assert_eq!(activity.pulse(5), 0.0);
+}
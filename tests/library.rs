@@ -0,0 +1,107 @@
+use std::{env, fs, path::Path};
+
+use chipy8::{
+    chip8::Platform,
+    cli::Cli,
+    config::Config,
+    keymap::Keymap,
+    library::{self, Library, RomSettings},
+    rom::Rom,
+};
+use clap::Parser;
+
+#[test]
+fn settings_round_trip_through_a_file() {
+    let path = env::temp_dir().join(format!("chipy8-library-{}.toml", std::process::id()));
+    assert_eq!(Library::load(Some(&path)).unwrap(), Library::default());
+
+    let mut library = Library::default();
+    *library.settings_mut(0xC0FFEE) = RomSettings {
+        speed: Some(1000),
+        platform: Some(Platform::HiresChip8),
+        keymap: Some(Keymap::AZERTY),
+        last_played: Some(1_700_000_000),
+    };
+    library.save(Some(&path)).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    let loaded = Library::load(Some(&path)).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(text.contains("platform = \"hires\""), "{text}");
+    assert_eq!(loaded, library);
+    assert_eq!(loaded.settings(0xC0FFEE).speed, Some(1000));
+    assert_eq!(loaded.settings(0xBEEF), RomSettings::default());
+}
+
+#[test]
+fn scan_lists_roms_by_name() {
+    let dir = env::temp_dir().join(format!("chipy8-library-roms-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("pong.ch8"), [0x12, 0x00]).unwrap();
+    fs::write(dir.join("Blitz.sc8"), [0x00, 0xE0, 0x12, 0x02]).unwrap();
+    fs::write(dir.join("notes.txt"), "not a rom").unwrap();
+    let roms = library::scan(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let names: Vec<&str> = roms.iter().map(|rom| rom.name()).collect();
+    assert_eq!(names, ["Blitz", "pong"]);
+    assert_eq!(roms[0].size, 4);
+    assert_eq!(roms[0].platform(), Some(Platform::Schip));
+    assert_eq!(roms[1].platform(), None);
+    assert_eq!(
+        roms[1].hash,
+        Rom::from_bytes("pong", vec![0x12, 0x00]).hash()
+    );
+}
+
+#[test]
+fn extensions_suggest_a_platform() {
+    assert_eq!(
+        library::platform_of(Path::new("roms/Blitz.sc8")),
+        Some(Platform::Schip)
+    );
+    assert_eq!(
+        library::platform_of(Path::new("maze.c8h")),
+        Some(Platform::HiresChip8)
+    );
+    assert_eq!(library::platform_of(Path::new("pong.ch8")), None);
+    assert_eq!(library::platform_of(Path::new("pong")), None);
+}
+
+#[test]
+fn two_page_roms_open_from_the_library_as_two_page() {
+    let dir = env::temp_dir().join(format!("chipy8-library-c8h-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    // the jump to the interpreter extension two page roms begin with
+    fs::write(dir.join("maze.c8h"), [0x12, 0x60]).unwrap();
+    let roms = library::scan(&dir).unwrap();
+    let mut library = Library::default();
+    library.add(&roms[0]);
+    let rom = Rom::new(&roms[0].path).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let settings = library.settings(rom.hash());
+    assert_eq!(settings.platform, Some(Platform::HiresChip8));
+    let cli = Cli::parse_from(["chipy8"]);
+    let chip8 = cli
+        .builder_with(rom, &settings, &Config::default())
+        .build()
+        .unwrap();
+    assert!(chip8.two_page);
+    assert_eq!(chip8.program_counter, 0x2C0);
+}
+
+#[test]
+fn roms_have_a_sha1() {
+    let rom = Rom::from_bytes("abc", b"abc".to_vec());
+    assert_eq!(rom.sha1(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+}
+
+#[test]
+fn times_read_as_how_long_ago() {
+    let now = 1_700_000_000;
+    assert_eq!(library::ago(now - 5, now), "just now");
+    assert_eq!(library::ago(now - 60, now), "1 minute ago");
+    assert_eq!(library::ago(now - 3 * 86400, now), "3 days ago");
+    assert_eq!(library::ago(now - 400 * 86400, now), "1 year ago");
+}
@@ -0,0 +1,44 @@
+use chipy8::{
+    chip8::{Chip8, Tracer},
+    log::{Level, Log},
+    rom::Rom,
+};
+
+#[test]
+fn entries_are_kept_up_to_capacity() {
+    let log = Log::new(3);
+    log.info("one");
+    log.warn("two");
+    log.debug("three");
+    log.error("four");
+    assert_eq!(log.len(), 3);
+    let messages: Vec<String> = log
+        .entries(Level::Debug)
+        .into_iter()
+        .map(|entry| entry.message)
+        .collect();
+    assert_eq!(messages, ["two", "three", "four"]);
+    // only as severe as asked for
+    let warnings = log.entries(Level::Warn);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[1].to_string(), "error four");
+    log.clear();
+    assert!(log.is_empty());
+}
+
+#[test]
+fn traces_are_debug_entries() {
+    let log = Log::default();
+    let mut chip8 = Chip8::new(Rom::from_bytes("log", vec![0x60, 0x05, 0xA2, 0x1E]));
+    chip8.tracer = Some(Tracer::new(log.clone()));
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    let entries = log.entries(Level::Debug);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].level, Level::Debug);
+    assert_eq!(
+        entries[1].message,
+        "0x202  a21e  LD I, 0x21e          I=0x21e"
+    );
+    assert!(log.entries(Level::Info).is_empty());
+}
@@ -0,0 +1,22 @@
+use chipy8::{chip8::Chip8, phosphor::Phosphor, rom::Rom};
+
+#[test]
+fn pixels_fade_out_over_the_frames() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("phosphor", vec![0x00, 0xE0]));
+    let mut phosphor = Phosphor::new(4);
+    chip8.display[0] = 0x80;
+    phosphor.update(&chip8, 1);
+    assert_eq!(phosphor.brightness(0, 0), 1.0);
+    assert_eq!(phosphor.brightness(1, 0), 0.0);
+
+    chip8.display[0] = 0;
+    phosphor.update(&chip8, 1);
+    assert_eq!(phosphor.brightness(0, 0), 0.75);
+    phosphor.update(&chip8, 2);
+    assert_eq!(phosphor.brightness(0, 0), 0.25);
+    phosphor.update(&chip8, 5);
+    assert_eq!(phosphor.brightness(0, 0), 0.0);
+    // off the display is dark
+    assert_eq!(phosphor.brightness(64, 0), 0.0);
+    assert_eq!(Phosphor::new(0).frames(), 1);
+}
@@ -0,0 +1,96 @@
+use chipy8::{
+    asm,
+    chip8::{Chip8, Platform, Quirks},
+    cli::Cli,
+    rom::Rom,
+};
+use clap::Parser;
+use strum::IntoEnumIterator;
+
+#[test]
+fn platforms_parse_by_name() {
+    for platform in Platform::iter() {
+        assert_eq!(platform.to_string().parse(), Ok(platform));
+    }
+    assert_eq!("chip48".parse(), Ok(Platform::Chip48));
+    assert!("chip9".parse::<Platform>().is_err());
+}
+
+#[test]
+fn cli_picks_the_platforms_quirks() {
+    let cli = Cli::parse_from(["chipy8", "--platform", "xochip", "rom.ch8"]);
+    assert_eq!(cli.quirks(), Quirks::from(Platform::Xochip));
+    let cli = Cli::parse_from(["chipy8", "rom.ch8"]);
+    assert_eq!(cli.quirks(), Quirks::default());
+}
+
+#[test]
+fn platforms_are_known_by_their_quirks() {
+    assert_eq!(
+        Platform::of(Platform::Xochip.into()),
+        Some(Platform::Xochip)
+    );
+    assert_eq!(
+        Platform::of(Platform::Chip48.into()),
+        Some(Platform::Chip48)
+    );
+    assert_eq!(Platform::of(Platform::Schip.into()), Some(Platform::Schip));
+    // the hi-res VIP only differs in its display
+    assert_eq!(
+        Platform::of(Platform::HiresChip8.into()),
+        Some(Platform::Chip8)
+    );
+    assert_eq!(Platform::of(Quirks::default()), None);
+}
+
+#[test]
+fn quirks_are_named_as_their_fields() {
+    let quirks = Quirks {
+        vf_reset: true,
+        wrap_memory: true,
+        ..Quirks::default()
+    };
+    assert_eq!(
+        quirks.names().collect::<Vec<_>>(),
+        ["vf_reset", "wrap_memory"]
+    );
+    assert_eq!(Quirks::default().names().count(), 0);
+}
+
+#[test]
+fn chip48_jumps_with_vx_where_the_vip_used_v0() {
+    let source = "
+        LD V0, 0x10
+        LD V3, 0x20
+        JP V0, 0x300
+    ";
+    let pc_after = |platform| {
+        let rom = Rom::from_bytes("bnnn", asm::assemble(source).unwrap());
+        let mut chip8 = Chip8::new(rom).with_quirks(Quirks::from(platform));
+        for _ in 0..3 {
+            chip8.step().unwrap();
+        }
+        chip8.program_counter
+    };
+    assert_eq!(pc_after(Platform::Chip8), 0x310);
+    assert_eq!(pc_after(Platform::Chip48), 0x320);
+    assert_eq!(pc_after(Platform::Schip), 0x320);
+}
+
+#[test]
+fn schip_leaves_i_where_chip48_moved_it() {
+    let source = "
+        LD I, 0x300
+        LD [I], V2
+    ";
+    let i_after = |platform| {
+        let rom = Rom::from_bytes("fx55", asm::assemble(source).unwrap());
+        let mut chip8 = Chip8::new(rom).with_quirks(Quirks::from(platform));
+        for _ in 0..2 {
+            chip8.step().unwrap();
+        }
+        chip8.i
+    };
+    assert_eq!(i_after(Platform::Chip48), 0x303);
+    assert_eq!(i_after(Platform::Schip), 0x300);
+}
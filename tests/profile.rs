@@ -0,0 +1,48 @@
+mod common;
+
+use common::program;
+
+const LOOP: &str = "
+    LD V0, 0
+loop:
+    ADD V0, 1
+    SE V0, 10
+    JP loop
+end:
+    JP end
+";
+
+#[test]
+fn profiling_is_off_by_default() {
+    let mut chip8 = program(LOOP);
+    chip8.step().unwrap();
+    assert_eq!(chip8.profile(), None);
+}
+
+#[test]
+fn profile_counts_each_opcode_family() {
+    let mut chip8 = program(LOOP);
+    chip8.set_profiling(true);
+    while !chip8.is_spinning() {
+        chip8.step().unwrap();
+    }
+    let profile = chip8.profile().unwrap();
+    let count = |family| profile.get(family).map(|stats| stats.count);
+    assert_eq!(count("6xkk"), Some(1));
+    assert_eq!(count("7xkk"), Some(10));
+    assert_eq!(count("3xkk"), Some(10));
+    assert_eq!(count("1nnn"), Some(9));
+    assert_eq!(count("Dxyn"), None);
+    assert_eq!(profile.count(), 30);
+    assert_eq!(profile.hottest().len(), 4);
+    assert_eq!(profile.to_string().lines().count(), 6);
+}
+
+#[test]
+fn restarting_profiling_clears_it() {
+    let mut chip8 = program(LOOP);
+    chip8.set_profiling(true);
+    chip8.step().unwrap();
+    chip8.set_profiling(true);
+    assert_eq!(chip8.profile().unwrap().count(), 0);
+}
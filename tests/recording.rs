@@ -0,0 +1,46 @@
+use std::{env, fs};
+
+use chipy8::{asm, chip8::Chip8, recording::Recording, rom::Rom};
+
+#[test]
+fn only_changes_make_new_frames() {
+    let rom = asm::assemble("LD I, dot\nDRW V0, V0, 1\nend: JP end\ndot: db 0x80").unwrap();
+    let mut chip8 = Chip8::new(Rom::from_bytes("recording", rom));
+    let mut recording = Recording::new();
+    assert!(recording.is_empty());
+    recording.capture(&chip8, 1);
+    recording.capture(&chip8, 2);
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    recording.capture(&chip8, 1);
+    assert_eq!(recording.len(), 2);
+    assert_eq!(recording.ticks(), 4);
+}
+
+#[test]
+fn gif_and_apng_by_extension() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("recording", vec![0x00, 0xE0]));
+    let mut recording = Recording::new();
+    let gif = env::temp_dir().join(format!("chipy8-recording-{}.gif", std::process::id()));
+    assert!(recording.save(1, &gif).is_err(), "nothing to save");
+    recording.capture(&chip8, 3);
+    chip8.display[0] = 0xFF;
+    recording.capture(&chip8, 3);
+
+    recording.save(2, &gif).unwrap();
+    let bytes = fs::read(&gif).unwrap();
+    fs::remove_file(&gif).unwrap();
+    assert_eq!(bytes[..6], *b"GIF89a");
+    // logical screen width and height, little endian
+    assert_eq!(bytes[6..10], [128, 0, 64, 0]);
+
+    let apng = gif.with_extension("png");
+    recording.save(1, &apng).unwrap();
+    let bytes = fs::read(&apng).unwrap();
+    fs::remove_file(&apng).unwrap();
+    assert_eq!(bytes[..8], *b"\x89PNG\r\n\x1a\n");
+    assert!(
+        bytes.windows(4).any(|chunk| chunk == b"acTL"),
+        "not animated"
+    );
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use chipy8::{
+    chip8::{Chip8, Platform, Quirks},
+    rom::Rom,
+};
+use common::rom;
+
+#[test]
+fn reset_is_the_rom_just_loaded() {
+    let rom = rom("
+        LD V0, 9
+        LD DT, V0
+        LD ST, V0
+        LD F, V0
+        DRW V0, V0, 5
+        LD I, 0x200
+        LD [I], V0
+        CALL sub
+        sub: JP sub
+    ");
+    let fresh = Chip8::new(rom.clone()).with_seed(1);
+    let mut chip8 = fresh.clone();
+    for _ in 0..9 {
+        chip8.step().unwrap();
+    }
+    assert!(chip8.is_halted());
+    assert_ne!(chip8, fresh);
+    chip8.reset();
+    assert_eq!(chip8, fresh);
+}
+
+#[test]
+fn reset_keeps_the_setup() {
+    let rom = rom("LD V0, 1\nLD R, V0\nend: JP end");
+    let mut chip8 = Chip8::builder(rom)
+        .platform(Platform::Schip)
+        .start_address(0x600)
+        .build()
+        .unwrap();
+    chip8.toggle_breakpoint(0x602);
+    for _ in 0..3 {
+        chip8.step().unwrap();
+    }
+    chip8.reset();
+    assert_eq!(chip8.program_counter, 0x600);
+    assert_eq!(chip8.registers, [0; 16]);
+    assert_eq!(chip8.quirks, Quirks::from(Platform::Schip));
+    // the persistent flags are meant to outlive the program
+    assert_eq!(chip8.flags[0], 1);
+    assert!(chip8.breakpoints.contains(&0x602));
+}
+
+#[test]
+fn two_page_roms_restart_past_their_jump() {
+    let rom = Rom::from_bytes("two page", vec![0x12, 0x60]);
+    let mut chip8 = Chip8::new(rom).with_platform(Platform::HiresChip8);
+    assert_eq!(chip8.program_counter, 0x2C0);
+    chip8.program_counter = 0x300;
+    chip8.reset();
+    assert_eq!(chip8.program_counter, 0x2C0);
+    assert!(chip8.two_page);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use chipy8::{
+    chip8::{Chip8, TimerSource},
+    rewind::History,
+};
+
+/// A maze run with timers on the instruction clock, so history is deterministic
+fn maze() -> Chip8 {
+    common::maze().with_timer_source(TimerSource::Instructions(500))
+}
+
+#[test]
+fn stepping_back_retraces_the_run() {
+    let mut chip8 = maze();
+    let mut history = History::new(100);
+    let mut states = vec![chip8.clone()];
+    for _ in 0..50 {
+        chip8.step_recorded(&mut history).unwrap();
+        states.push(chip8.clone());
+    }
+    states.pop();
+    while let Some(expected) = states.pop() {
+        assert!(chip8.step_back(&mut history));
+        assert!(chip8 == expected, "{} steps in", states.len());
+    }
+    assert!(!chip8.step_back(&mut history));
+    assert!(chip8 == maze());
+}
+
+#[test]
+fn history_keeps_only_the_most_recent_states() {
+    let mut chip8 = maze();
+    let mut history = History::new(10);
+    for _ in 0..25 {
+        chip8.step_recorded(&mut history).unwrap();
+    }
+    assert_eq!(history.len(), 10);
+
+    let mut replay = maze();
+    for _ in 0..15 {
+        replay.step().unwrap();
+    }
+    while chip8.step_back(&mut history) {}
+    assert!(chip8 == replay, "oldest state kept isn't 15 steps in");
+}
+
+#[test]
+fn scrubbing_looks_back_without_forgetting() {
+    let mut chip8 = maze();
+    let mut history = History::new(100);
+    let mut states = vec![chip8.clone()];
+    for _ in 0..20 {
+        chip8.step_recorded(&mut history).unwrap();
+        states.push(chip8.clone());
+    }
+    assert!(*history.get(0).unwrap() == states[0]);
+    assert!(*history.get(12).unwrap() == states[12]);
+    assert!(history.get(20).is_none());
+
+    // resuming from the 12th state forgets the ones after it
+    let mut resumed = history.get(12).unwrap().clone();
+    history.truncate(12);
+    resumed.step_recorded(&mut history).unwrap();
+    assert_eq!(history.len(), 13);
+    assert!(resumed == states[13]);
+}
@@ -0,0 +1,111 @@
+use std::{env, fs};
+
+use chipy8::{
+    chip8::{Chip8, Chip8Error, Quirks, TimerSource},
+    rom::Rom,
+};
+
+#[test]
+fn state_round_trips_and_runs_on_identically() {
+    let path = env::temp_dir().join(format!("chipy8-state-{}.toml", std::process::id()));
+    // maze draws from Cxkk, so a restored rng that lost its place would show
+    let mut chip8 = Chip8::new(Rom::new("ROMS/MAZE").unwrap())
+        .with_seed(7)
+        .with_quirks(Quirks {
+            vf_reset: true,
+            ..Quirks::default()
+        })
+        .with_timer_source(TimerSource::Instructions(500));
+    for _ in 0..1000 {
+        chip8.step().unwrap();
+    }
+    chip8.save_state(&path).unwrap();
+    let mut loaded = Chip8::load_state(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(loaded == chip8, "loaded state differs");
+
+    for _ in 0..1000 {
+        chip8.step().unwrap();
+        loaded.step().unwrap();
+    }
+    assert!(loaded == chip8, "runs diverged after loading");
+}
+
+#[test]
+fn damaged_state_is_invalid_data() {
+    let path = env::temp_dir().join(format!("chipy8-bad-state-{}.toml", std::process::id()));
+    fs::write(&path, "memory = [\"zz\"]").unwrap();
+    let error = Chip8::load_state(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn slots_after_the_first_are_numbered() {
+    let chip8 = Chip8::new(Rom::from_bytes("PONG", vec![0x12, 0x00]));
+    assert_eq!(chip8.state_slot_path(1), chip8.default_state_path());
+    if let Some(path) = chip8.state_slot_path(3) {
+        assert!(path.ends_with("states/PONG.3.toml"), "{}", path.display());
+    }
+}
+
+#[test]
+fn stack_pointer_past_the_stack_is_refused() {
+    let path = env::temp_dir().join(format!("chipy8-stack-{}.toml", std::process::id()));
+    let mut chip8 = Chip8::new(Rom::from_bytes("RET", vec![0x00, 0xEE]));
+    chip8.stack_pointer = 200;
+    assert_eq!(
+        chip8.step(),
+        Err(Chip8Error::StackPointerOutOfRange {
+            address: 0x200,
+            stack_pointer: 200
+        })
+    );
+    chip8.save_state(&path).unwrap();
+    let error = Chip8::load_state(&path).unwrap_err();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(
+        error.into_inner().unwrap().downcast_ref::<Chip8Error>(),
+        Some(&Chip8Error::InvalidState {
+            field: "stack_pointer",
+            value: 200
+        })
+    );
+}
+
+#[test]
+fn registers_and_memory_out_of_range_are_refused() {
+    let path = env::temp_dir().join(format!("chipy8-range-{}.toml", std::process::id()));
+    let refusal = |edit: &dyn Fn(&mut Chip8)| {
+        let mut chip8 = Chip8::new(Rom::from_bytes("range", vec![0x12, 0x00]));
+        edit(&mut chip8);
+        chip8.save_state(&path).unwrap();
+        let error = Chip8::load_state(&path).unwrap_err();
+        error
+            .into_inner()
+            .unwrap()
+            .downcast::<Chip8Error>()
+            .ok()
+            .map(|e| *e)
+    };
+    assert_eq!(
+        refusal(&|chip8| chip8.memory.clear()),
+        Some(Chip8Error::InvalidMemorySize { size: 0 })
+    );
+    assert_eq!(
+        refusal(&|chip8| chip8.program_counter = 0x1000),
+        Some(Chip8Error::InvalidState {
+            field: "program_counter",
+            value: 0x1000
+        })
+    );
+    assert_eq!(
+        refusal(&|chip8| chip8.i = 0xFFFF),
+        Some(Chip8Error::InvalidState {
+            field: "i",
+            value: 0xFFFF
+        })
+    );
+    fs::remove_file(&path).unwrap();
+}
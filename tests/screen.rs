@@ -0,0 +1,79 @@
+use chipy8::{
+    screen::{KeyAction, Screen, Screens},
+    session::Panels,
+};
+use crossterm::event::KeyCode;
+
+#[test]
+fn screens_close_back_to_what_they_opened_over() {
+    let mut screens = Screens::of(&Panels::default());
+    assert_eq!(screens.top(), Screen::Debugger);
+    assert_eq!(screens.close(), None);
+
+    screens.open(Screen::RomBrowser { selected: 0 });
+    screens.open(Screen::Help);
+    assert_eq!(screens.top(), Screen::Help);
+    assert_eq!(screens.close(), Some(Screen::Help));
+    assert_eq!(screens.top(), Screen::RomBrowser { selected: 0 });
+    assert_eq!(screens.close(), Some(Screen::RomBrowser { selected: 0 }));
+    assert_eq!(screens.top(), Screen::Debugger);
+}
+
+#[test]
+fn reopening_a_screen_brings_it_to_the_top_afresh() {
+    let mut screens = Screens::new(Screen::Emulator);
+    screens.open(Screen::Settings { selected: 0 });
+    *screens.top_mut().selected_mut().unwrap() = 3;
+    screens.open(Screen::Help);
+    screens.open(Screen::Settings { selected: 0 });
+
+    let open: Vec<Screen> = screens.iter().collect();
+    assert_eq!(
+        open,
+        [
+            Screen::Emulator,
+            Screen::Help,
+            Screen::Settings { selected: 0 }
+        ]
+    );
+    screens.open(Screen::Debugger);
+    assert_eq!(screens.iter().count(), 3);
+}
+
+#[test]
+fn the_base_swaps_under_what_is_open() {
+    let zen = Panels {
+        zen: true,
+        ..Panels::default()
+    };
+    let mut screens = Screens::of(&zen);
+    assert_eq!(screens.base(), Screen::Emulator);
+    screens.open(Screen::Help);
+    screens.toggle_base();
+    assert_eq!(screens.base(), Screen::Debugger);
+    assert_eq!(screens.top(), Screen::Help);
+    assert!(!Screen::Help.is_base());
+}
+
+#[test]
+fn keys_move_the_selection_choose_and_close() {
+    let mut screens = Screens::new(Screen::Debugger);
+    assert_eq!(screens.on_key(KeyCode::Enter, 0), KeyAction::Base);
+
+    screens.open(Screen::RomBrowser { selected: 0 });
+    assert_eq!(screens.on_key(KeyCode::Up, 3), KeyAction::Done);
+    for _ in 0..5 {
+        screens.on_key(KeyCode::Down, 3);
+    }
+    assert_eq!(screens.top(), Screen::RomBrowser { selected: 2 });
+    assert_eq!(screens.on_key(KeyCode::Enter, 3), KeyAction::Choose(2));
+    // nothing to choose from an empty list
+    assert_eq!(screens.on_key(KeyCode::Enter, 0), KeyAction::Done);
+
+    screens.open(Screen::Help);
+    assert_eq!(screens.on_key(KeyCode::Char('L'), 0), KeyAction::Done);
+    assert_eq!(screens.top(), Screen::Help);
+    screens.on_key(KeyCode::Char('?'), 0);
+    screens.on_key(KeyCode::Esc, 3);
+    assert_eq!(screens.top(), Screen::Debugger);
+}
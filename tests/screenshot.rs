@@ -0,0 +1,55 @@
+use std::{env, fs};
+
+use chipy8::{
+    asm,
+    chip8::Chip8,
+    rom::Rom,
+    screenshot::{self, ImageFormat},
+};
+
+/// The top left pixel lit, from a one row sprite of 0x80
+fn one_pixel() -> Chip8 {
+    let rom = asm::assemble("LD I, dot\nDRW V0, V0, 1\nend: JP end\ndot: db 0x80").unwrap();
+    let mut chip8 = Chip8::new(Rom::from_bytes("screenshot", rom));
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    chip8
+}
+
+#[test]
+fn pbm_scales_each_pixel() {
+    let mut pbm = Vec::new();
+    screenshot::write_pbm(&one_pixel(), 2, &mut pbm).unwrap();
+    let pbm = String::from_utf8(pbm).unwrap();
+    let lines: Vec<&str> = pbm.lines().collect();
+    assert_eq!(lines[..2], ["P1", "128 64"]);
+    assert_eq!(lines.len(), 2 + 64);
+    assert!(lines[2].starts_with("0 0 1 1"));
+    assert_eq!(lines[3], lines[2]);
+    assert!(lines[4].starts_with("1 1 1"));
+}
+
+#[test]
+fn png_is_written_by_extension() {
+    let path = env::temp_dir().join(format!("chipy8-screenshot-{}.png", std::process::id()));
+    screenshot::save(&one_pixel(), 3, &path).unwrap();
+    let png = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+    // the IHDR's width and height
+    assert_eq!(png[16..24], [0, 0, 0, 192, 0, 0, 0, 96]);
+    assert_eq!(ImageFormat::of(&path), ImageFormat::Png);
+    assert_eq!(ImageFormat::of("bug.PBM".as_ref()), ImageFormat::Pbm);
+}
+
+#[test]
+fn names_carry_the_rom_and_the_time() {
+    assert_eq!(
+        screenshot::file_name("PONG", 0),
+        "PONG-1970-01-01-000000.png"
+    );
+    assert_eq!(
+        screenshot::file_name("PONG", 1_715_938_212),
+        "PONG-2024-05-17-093012.png"
+    );
+}
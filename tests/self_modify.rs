@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+
+use chipy8::{
+    asm,
+    chip8::{Chip8, Tracer},
+    rom::Rom,
+};
+
+/// Overwrites the `LD V0, 1` it already ran with Fx55, then writes to data
+const PROGRAM: &str = "
+start:
+    LD V0, 1
+    LD I, start
+    LD V0, V0
+    LD [I], V0
+    LD I, data
+    LD [I], V0
+end:
+    JP end
+data:
+    db 0
+";
+
+fn program() -> Chip8 {
+    Chip8::new(Rom::from_bytes("smc", asm::assemble(PROGRAM).unwrap()))
+}
+
+#[test]
+fn writes_over_executed_code_are_flagged() {
+    let mut chip8 = program();
+    let mut flagged = Vec::new();
+    while !chip8.is_spinning() {
+        chip8.step().unwrap();
+        flagged.push(chip8.self_modified);
+    }
+    assert_eq!(flagged, [None, None, None, Some(0x200), None, None]);
+}
+
+#[test]
+fn hooks_and_traces_hear_about_self_modification() {
+    let mut chip8 = program();
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let hook = written.clone();
+    chip8.on_self_modify(move |address| hook.lock().unwrap().push(address));
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let trace = lines.clone();
+    chip8.tracer = Some(Tracer::new(move |entry: &_| {
+        trace.lock().unwrap().push(format!("{entry}"))
+    }));
+    while !chip8.is_spinning() {
+        chip8.step().unwrap();
+    }
+    assert_eq!(*written.lock().unwrap(), [0x200]);
+    let lines = lines.lock().unwrap();
+    assert!(lines[3].ends_with("SMC=0x200"), "{}", lines[3]);
+    assert!(!lines[5].contains("SMC"));
+}
@@ -0,0 +1,67 @@
+use std::{env, fs, path::Path};
+
+use chipy8::session::{Breakpoint, Panels, Session, TabSession};
+
+#[test]
+fn session_round_trips_through_a_file() {
+    let path = env::temp_dir().join(format!("chipy8-session-{}.toml", std::process::id()));
+    assert_eq!(Session::load(Some(&path)).unwrap(), None);
+
+    let session = Session {
+        tabs: vec![
+            TabSession::new(Path::new("ROMS/PONG")),
+            TabSession {
+                break_on_draw: true,
+                breakpoints: vec![
+                    Breakpoint {
+                        address: 0x200,
+                        condition: None,
+                    },
+                    Breakpoint {
+                        address: 0x21A,
+                        condition: Some("v0 == 3 && [i] != 0".parse().unwrap()),
+                    },
+                ],
+                ..TabSession::new(Path::new("ROMS/MAZE"))
+            },
+        ],
+        active: 1,
+        show_timing: true,
+        panels: Panels {
+            program: false,
+            zen: true,
+            ..Panels::default()
+        },
+    };
+    session.save(Some(&path)).unwrap();
+    let loaded = Session::load(Some(&path)).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, Some(session));
+}
+
+#[test]
+fn opening_another_rom_replaces_the_session() {
+    let mut session = Session {
+        tabs: vec![
+            TabSession::new(Path::new("ROMS/PONG")),
+            TabSession::new(Path::new("ROMS/MAZE")),
+        ],
+        active: 1,
+        show_timing: true,
+        panels: Panels::default(),
+    };
+    let restored = session.clone();
+
+    session.opened(Path::new("ROMS/MAZE"));
+    assert_eq!(session, restored);
+
+    session.opened(Path::new("ROMS/PONG"));
+    assert_eq!(session.tabs, [TabSession::new(Path::new("ROMS/PONG"))]);
+    assert!(!session.show_timing);
+}
+
+#[test]
+fn empty_session_is_default() {
+    assert_eq!(Session::parse("").unwrap(), Session::default());
+}
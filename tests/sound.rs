@@ -0,0 +1,17 @@
+use std::time::{Duration, Instant};
+
+use chipy8::sound::{Bell, BELL_INTERVAL};
+
+#[test]
+fn bell_rings_while_sounding_but_not_too_often() {
+    let start = Instant::now();
+    let mut bell = Bell::new();
+    assert!(!bell.ring(false, start));
+    assert!(bell.ring(true, start));
+    assert!(!bell.ring(true, start + Duration::from_millis(100)));
+    assert!(bell.ring(true, start + BELL_INTERVAL));
+    // a buzz soon after the last ring waits its turn
+    assert!(!bell.ring(false, start + BELL_INTERVAL * 3 / 2));
+    assert!(!bell.ring(true, start + BELL_INTERVAL * 3 / 2));
+    assert!(bell.ring(true, start + BELL_INTERVAL * 2));
+}
@@ -0,0 +1,34 @@
+use chipy8::{
+    chip8::{Chip8, ETI_660_START},
+    cli::Cli,
+    rom::Rom,
+};
+use clap::Parser;
+
+#[test]
+fn roms_load_and_start_at_the_address_given() {
+    // LD V0, 0x42
+    let rom = Rom::from_bytes("eti", vec![0x60, 0x42]);
+    let mut chip8 = Chip8::new_at(rom, ETI_660_START as u16);
+    assert_eq!(chip8.program_counter, 0x600);
+    assert_eq!(chip8.start_address, 0x600);
+    assert_eq!(&chip8.memory[0x600..0x602], [0x60, 0x42]);
+    assert_eq!(&chip8.memory[0x200..0x202], [0, 0]);
+    chip8.step().unwrap();
+    assert_eq!(chip8.registers[0], 0x42);
+}
+
+#[test]
+fn start_addr_is_hex_and_defaults_to_0x200() {
+    let start = |args: &[&str]| Cli::try_parse_from(args).map(|cli| cli.start_addr);
+    assert_eq!(start(&["chipy8", "rom.ch8"]).unwrap(), 0x200);
+    assert_eq!(
+        start(&["chipy8", "--start-addr", "0x600", "rom.ch8"]).unwrap(),
+        0x600
+    );
+    assert_eq!(
+        start(&["chipy8", "--start-addr", "600", "rom.ch8"]).unwrap(),
+        0x600
+    );
+    assert!(start(&["chipy8", "--start-addr", "0x1000", "rom.ch8"]).is_err());
+}
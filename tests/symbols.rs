@@ -0,0 +1,81 @@
+use std::{env, fs};
+
+use chipy8::{
+    chip8::Instruction,
+    debugger::{self, DebugCommand, StackFrame},
+    symbols::Symbols,
+};
+
+fn symbols() -> Symbols {
+    Symbols::parse(
+        "
+        # labels of a pong
+        main 0x200
+        draw_ball: 2E6
+        ; a constant with the same address wins when showing it
+        ball = 0x2e6
+        ",
+    )
+    .unwrap()
+}
+
+#[test]
+fn names_and_addresses_both_ways() {
+    let symbols = symbols();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!(symbols.address("draw_ball"), Some(0x2E6));
+    assert_eq!(symbols.address("ball"), Some(0x2E6));
+    assert_eq!(symbols.name(0x2E6), Some("ball"));
+    assert_eq!(symbols.name(0x200), Some("main"));
+    assert_eq!(symbols.name(0x202), None);
+
+    let error = Symbols::parse("main\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "symbol file line 1: expected `<name> <address>`"
+    );
+    let error = Symbols::parse("\nmain 0xZZ").unwrap_err();
+    assert_eq!(error.to_string(), "symbol file line 2: bad address");
+}
+
+#[test]
+fn symbols_are_found_beside_the_rom() {
+    let rom = env::temp_dir().join(format!("chipy8-symbols-{}.ch8", std::process::id()));
+    assert_eq!(Symbols::beside(&rom).unwrap(), Symbols::default());
+    let path = rom.with_extension("sym");
+    fs::write(&path, "main 0x200\n").unwrap();
+    let symbols = Symbols::beside(&rom);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(symbols.unwrap().address("main"), Some(0x200));
+}
+
+#[test]
+fn the_debugger_uses_names() {
+    let symbols = symbols();
+    assert_eq!(
+        DebugCommand::parse("break draw_ball", &symbols),
+        Ok(DebugCommand::Break(0x2E6, None))
+    );
+    assert_eq!(
+        DebugCommand::parse("mem main", &symbols),
+        Ok(DebugCommand::Mem(0x200))
+    );
+    assert_eq!(
+        DebugCommand::parse("break paddle", &symbols),
+        Err("bad number `paddle`".to_string())
+    );
+    assert_eq!(
+        debugger::mnemonic(Instruction::Call(0x2E6), &symbols),
+        "CALL ball"
+    );
+    assert_eq!(
+        debugger::mnemonic(Instruction::LdI(0x300), &symbols),
+        "LD I, 0x300"
+    );
+    let frame = StackFrame {
+        return_address: 0x202,
+        subroutine: Some(0x2E6),
+    };
+    assert_eq!(frame.describe(&symbols), "0x202 ball");
+    assert_eq!(frame.describe(&Symbols::default()), "0x202 sub_2e6");
+}
@@ -0,0 +1,49 @@
+use chipy8::theme::{blend, Colors, Theme};
+use ratatui::style::Color;
+
+#[test]
+fn presets_parse_by_name() {
+    assert_eq!("amber".parse::<Theme>().unwrap(), Theme::AMBER);
+    assert_eq!(
+        "High-Contrast".parse::<Theme>().unwrap(),
+        Theme::HIGH_CONTRAST
+    );
+    assert_eq!(Theme::GREEN.to_string(), "green");
+    assert_eq!(Theme::default(), Theme::DEFAULT);
+    assert!("solarized".parse::<Theme>().is_err());
+}
+
+#[test]
+fn colors_change_only_what_they_set() {
+    let colors = Colors {
+        background: Some(Color::Black),
+        ..Colors::default()
+    };
+    let theme = Theme::AMBER.with(&colors);
+    assert_eq!(theme.background, Color::Black);
+    assert_eq!(theme.foreground, Theme::AMBER.foreground);
+    assert_eq!(theme.to_string(), "custom");
+    assert_eq!(Theme::GREEN.with(&Colors::default()), Theme::GREEN);
+}
+
+#[test]
+fn blend_mixes_rgb_values() {
+    assert_eq!(
+        blend(Color::Black, Color::White, 0.5),
+        Color::Rgb(128, 128, 128)
+    );
+    assert_eq!(
+        blend(Color::Reset, Color::Rgb(0xFF, 0xB0, 0x00), 0.25),
+        Color::Rgb(64, 44, 0)
+    );
+    assert_eq!(
+        blend(Color::Black, Color::Green, 1.0),
+        Color::Rgb(0, 128, 0)
+    );
+    // no RGB value to mix
+    assert_eq!(
+        blend(Color::Indexed(17), Color::White, 0.25),
+        Color::Indexed(17)
+    );
+    assert_eq!(blend(Color::Indexed(17), Color::White, 0.75), Color::White);
+}
@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+
+use chipy8::timing::{Clock, Rate, TIMER_PERIOD};
+
+#[test]
+fn timer_ticks_follow_the_wall_clock() {
+    let start = Instant::now();
+    let mut clock = Clock::timers(start);
+
+    assert_eq!(clock.due(start), 0);
+    assert_eq!(clock.due(start + TIMER_PERIOD / 2), 0);
+    assert_eq!(clock.due(start + TIMER_PERIOD), 1);
+    assert_eq!(clock.due(start + TIMER_PERIOD), 0);
+    assert_eq!(clock.next(), start + TIMER_PERIOD * 2);
+}
+
+#[test]
+fn timer_ticks_catch_up_after_a_stall() {
+    let start = Instant::now();
+    let mut clock = Clock::timers(start);
+
+    // a second without looking still adds up to 60 ticks
+    assert_eq!(clock.due(start + TIMER_PERIOD * 30), 30);
+    assert_eq!(clock.due(start + TIMER_PERIOD * 60 + TIMER_PERIOD / 2), 30);
+}
+
+#[test]
+fn clock_runs_at_its_rate() {
+    let start = Instant::now();
+    let mut clock = Clock::new(start, 700);
+    assert_eq!(clock.due(start + Duration::from_millis(100)), 70);
+    assert_eq!(clock.due(start + Duration::from_secs(1)), 630);
+}
+
+#[test]
+fn rate_counts_over_its_window() {
+    let start = Instant::now();
+    let mut rate = Rate::new(Duration::from_secs(1));
+    assert_eq!(rate.per_second(start), 0.0);
+    rate.record(start, 5);
+    assert_eq!(rate.per_second(start), 0.0);
+    for tick in 1..=60 {
+        rate.record(start + TIMER_PERIOD * tick, 10);
+    }
+    let now = start + TIMER_PERIOD * 60;
+    assert!((rate.per_second(now) - 600.0).abs() < 1.0);
+    // older samples drop out
+    let later = now + Duration::from_secs(2);
+    rate.record(later, 1);
+    assert_eq!(rate.per_second(later), 0.0);
+}
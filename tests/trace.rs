@@ -0,0 +1,40 @@
+use std::sync::mpsc;
+
+use chipy8::{
+    asm,
+    chip8::{Chip8, Instruction, TraceEntry, Tracer},
+    rom::Rom,
+};
+
+#[test]
+fn trace_records_each_instruction_and_its_changes() {
+    let rom = asm::assemble(
+        "
+        LD V1, 7
+        LD I, 0x300
+        ADD V1, V1
+        JP 0x206
+        ",
+    )
+    .unwrap();
+    let mut chip8 = Chip8::new(Rom::from_bytes("trace", rom));
+    let (sender, entries) = mpsc::channel();
+    chip8.tracer = Some(Tracer::new(move |entry: &TraceEntry| {
+        sender.send(entry.clone()).unwrap()
+    }));
+    for _ in 0..4 {
+        chip8.step().unwrap();
+    }
+    let entries: Vec<TraceEntry> = entries.try_iter().collect();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[0].registers, vec![(1, 7)]);
+    assert_eq!(entries[1].i, Some(0x300));
+    assert_eq!(entries[2].instruction, Instruction::AddVxVy(1, 1));
+    // VF gets the carry, but was 0 already
+    assert_eq!(entries[2].registers, vec![(1, 14)]);
+    assert_eq!(entries[3].registers, vec![]);
+    assert_eq!(
+        entries[1].to_string(),
+        "0x202  a300  LD I, 0x300          I=0x300"
+    );
+}
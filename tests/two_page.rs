@@ -0,0 +1,49 @@
+use chipy8::{
+    chip8::{Chip8, Platform},
+    rom::Rom,
+};
+
+/// A two page hi-res rom: the jump into the interpreter extension, then the
+/// program proper at 0x2C0
+fn hires_rom(program: &[u8]) -> Rom {
+    let mut contents = vec![0x12, 0x60];
+    contents.resize(0xC0, 0);
+    contents.extend_from_slice(program);
+    Rom::from_bytes("hires", contents)
+}
+
+#[test]
+fn two_page_roms_start_at_0x2c0_with_a_64x64_display() {
+    let chip8 = Chip8::new(hires_rom(&[])).with_platform(Platform::HiresChip8);
+    assert_eq!(chip8.program_counter, 0x2C0);
+    assert_eq!((chip8.width(), chip8.height()), (64, 64));
+}
+
+#[test]
+fn two_page_draws_below_row_32_and_clears_with_0230() {
+    let program = [
+        0x60, 0x00, // LD V0, 0
+        0x61, 0x28, // LD V1, 40
+        0xF0, 0x29, // LD F, V0
+        0xD0, 0x15, // DRW V0, V1, 5
+        0x02, 0x30, // clear
+    ];
+    let mut chip8 = Chip8::new(hires_rom(&program)).with_platform(Platform::HiresChip8);
+    for _ in 0..4 {
+        chip8.step().unwrap();
+    }
+    assert!(chip8.pixel(0, 40));
+    // the VIP's display wait holds the clear back until the next frame
+    chip8.tick_timers();
+    chip8.step().unwrap();
+    assert!(!chip8.pixel(0, 40));
+}
+
+#[test]
+fn other_platforms_neither_clear_with_0230_nor_skip_ahead() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("lores", vec![0x02, 0x30]));
+    assert!(chip8.step().is_err());
+    let chip8 =
+        Chip8::new(Rom::from_bytes("plain", vec![0x60, 0x00])).with_platform(Platform::HiresChip8);
+    assert_eq!(chip8.program_counter, 0x200);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use chipy8::chip8::{RunOutcome, Watchpoint};
+use common::program;
+
+#[test]
+fn register_watchpoint_fires_on_change_only() {
+    let mut chip8 = program(
+        "
+        LD V5, 0
+        LD V5, 0
+        ADD V5, 1
+        JP 0x206
+        ",
+    );
+    chip8.add_watchpoint(Watchpoint::Register(5));
+    // writing the value already there isn't a change
+    let outcome = chip8.run_until_break(10).unwrap();
+    assert_eq!(outcome, RunOutcome::Watchpoint { index: 0 });
+    assert_eq!(chip8.program_counter, 0x206);
+    assert_eq!(chip8.registers[5], 1);
+}
+
+#[test]
+fn memory_watchpoint_fires_on_overlapping_writes() {
+    let mut chip8 = program(
+        "
+        LD I, 0x2fe
+        LD [I], V1
+        LD I, 0x310
+        LD B, V0
+        LD I, 0x30e
+        LD [I], V3
+        ",
+    );
+    chip8.add_watchpoint(Watchpoint::Register(0xA));
+    chip8.add_watchpoint(Watchpoint::Memory(0x300..0x310));
+    // 0x2fe..0x300 and 0x310..0x313 only touch the range's edges
+    for _ in 0..4 {
+        chip8.step().unwrap();
+        assert_eq!(chip8.watch_hit, None);
+    }
+    chip8.step().unwrap();
+    chip8.step().unwrap();
+    assert_eq!(chip8.watch_hit, Some(1));
+}
+
+#[test]
+fn watchpoints_parse_from_the_command_line_forms() {
+    assert_eq!("v5".parse(), Ok(Watchpoint::Register(5)));
+    assert_eq!("VF".parse(), Ok(Watchpoint::Register(15)));
+    assert_eq!("0x300".parse(), Ok(Watchpoint::Memory(0x300..0x301)));
+    assert_eq!("0x300..0x310".parse(), Ok(Watchpoint::Memory(0x300..0x310)));
+    assert!("v10".parse::<Watchpoint>().is_err());
+    assert!("0x310..0x300".parse::<Watchpoint>().is_err());
+    assert_eq!(
+        Watchpoint::Memory(0x300..0x310).to_string(),
+        "write to 0x300..0x310"
+    );
+}
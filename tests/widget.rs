@@ -0,0 +1,196 @@
+use chipy8::{
+    chip8::Chip8,
+    keypad::KeypadActivity,
+    rom::Rom,
+    widget::{HexInput, MemoryView, PixelMarker, ScaledDisplay, SpritePreview, Timeline},
+};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, symbols::Marker, widgets::Widget};
+
+fn row(buffer: &Buffer, y: u16) -> String {
+    (0..buffer.area.width)
+        .map(|x| buffer[(x, y)].symbol())
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+#[test]
+fn memory_view_dumps_hex_and_text() {
+    let mut memory = vec![0; 0x1000];
+    memory[0x200..0x204].copy_from_slice(b"Hi!\x7f");
+    let area = Rect::new(0, 0, 80, 3);
+    let mut buffer = Buffer::empty(area);
+    MemoryView::new(&memory)
+        .scroll(0x205)
+        .pc(0x200)
+        .i(0x203)
+        .render(area, &mut buffer);
+    assert_eq!(
+        row(&buffer, 0),
+        "0x200  48 69 21 7f 00 00 00 00 00 00 00 00 00 00 00 00  Hi!............."
+    );
+    assert!(row(&buffer, 2).starts_with("0x220  00"));
+    // the instruction at the PC and the byte at I stand out
+    assert_eq!(buffer[(7, 0)].bg, Color::Green);
+    assert_eq!(buffer[(10, 0)].bg, Color::Green);
+    assert_eq!(buffer[(13, 0)].bg, Color::Reset);
+    assert_eq!(buffer[(16, 0)].bg, Color::Yellow);
+}
+
+#[test]
+fn memory_view_narrows_to_eight_bytes() {
+    let memory = vec![0xAA; 0x1000];
+    let area = Rect::new(0, 0, 40, 2);
+    let mut buffer = Buffer::empty(area);
+    MemoryView::new(&memory)
+        .scroll(0xFF8)
+        .render(area, &mut buffer);
+    assert_eq!(row(&buffer, 0), "0xff8  aa aa aa aa aa aa aa aa  ........");
+    assert_eq!(row(&buffer, 1), "");
+}
+
+#[test]
+fn memory_view_scrolls_to_the_cursor() {
+    let memory = vec![0; 0x1000];
+    let area = Rect::new(0, 0, 80, 2);
+    let mut buffer = Buffer::empty(area);
+    MemoryView::new(&memory)
+        .scroll(0x200)
+        .cursor(0x234)
+        .render(area, &mut buffer);
+    assert!(row(&buffer, 0).starts_with("0x220"));
+    assert_eq!(buffer[(7 + 4 * 3, 1)].bg, Color::Cyan);
+}
+
+#[test]
+fn memory_view_marks_a_range() {
+    let memory = vec![0; 0x1000];
+    let area = Rect::new(0, 0, 80, 1);
+    let mut buffer = Buffer::empty(area);
+    MemoryView::new(&memory)
+        .scroll(0x200)
+        .mark(0x201..0x203)
+        .render(area, &mut buffer);
+    assert_eq!(buffer[(7, 0)].bg, Color::Reset);
+    assert_eq!(buffer[(10, 0)].bg, Color::Magenta);
+    assert_eq!(buffer[(13, 0)].bg, Color::Magenta);
+    assert_eq!(buffer[(16, 0)].bg, Color::Reset);
+}
+
+#[test]
+fn sprite_preview_draws_the_bits_at_i() {
+    let mut memory = vec![0; 0x1000];
+    memory[0x300..0x302].copy_from_slice(&[0b1000_0001, 0b0110_0000]);
+    let area = Rect::new(0, 0, 16, 3);
+    let mut buffer = Buffer::empty(area);
+    SpritePreview::new(&memory, 0x300, 2).render(area, &mut buffer);
+    assert_eq!(row(&buffer, 0), "██············██");
+    assert_eq!(row(&buffer, 1), "··████··········");
+    assert_eq!(row(&buffer, 2), "");
+    assert_eq!(SpritePreview::size(0), (16, 16));
+    assert_eq!(SpritePreview::size(5), (5, 8));
+}
+
+#[test]
+fn memory_view_colors_bytes() {
+    let memory = vec![0; 0x1000];
+    let colors = [Color::Red, Color::Blue];
+    let area = Rect::new(0, 0, 80, 1);
+    let mut buffer = Buffer::empty(area);
+    MemoryView::new(&memory)
+        .colors(&colors)
+        .render(area, &mut buffer);
+    assert_eq!(buffer[(7, 0)].fg, Color::Red);
+    assert_eq!(buffer[(10, 0)].fg, Color::Blue);
+    assert_eq!(buffer[(13, 0)].fg, Color::Reset);
+    // the text column too
+    assert_eq!(buffer[(7 + 16 * 3 + 1, 0)].fg, Color::Red);
+}
+
+#[test]
+fn timeline_puts_the_knob_along_the_history() {
+    let area = Rect::new(0, 0, 11, 1);
+    let mut buffer = Buffer::empty(area);
+    Timeline::new(100, 50).render(area, &mut buffer);
+    assert_eq!(row(&buffer, 0), "├━━━━●────┤");
+    let mut buffer = Buffer::empty(area);
+    Timeline::new(100, 100).render(area, &mut buffer);
+    assert_eq!(row(&buffer, 0), "├━━━━━━━━━●");
+}
+
+#[test]
+fn pixel_markers_size_the_display() {
+    let auto = PixelMarker::Auto;
+    assert_eq!(auto.marker(32), Marker::HalfBlock);
+    assert_eq!(auto.marker(64), Marker::Braille);
+    assert_eq!(auto.cells(64, 32), (64, 16));
+    assert_eq!(auto.cells(128, 64), (64, 16));
+    assert_eq!(PixelMarker::HalfBlock.cells(128, 64), (128, 32));
+    assert_eq!(PixelMarker::Braille.cells(64, 32), (32, 8));
+    assert_eq!(PixelMarker::Block.cells(64, 32), (64, 32));
+    // g cycles back round
+    let cycled = (0..4).fold(auto, |marker, _| marker.next());
+    assert_eq!(cycled, auto);
+    assert_eq!("half-block".parse(), Ok(PixelMarker::HalfBlock));
+    assert_eq!(PixelMarker::Braille.to_string(), "braille");
+}
+
+#[test]
+fn scaled_display_fills_the_area() {
+    let mut chip8 = Chip8::new(Rom::from_bytes("scaled", vec![0x00, 0xE0]));
+    chip8.display[0] = 0x80;
+    let area = Rect::new(0, 0, 140, 40);
+    // 2 cells a pixel across, 2 half rows down
+    assert_eq!(ScaledDisplay::scale(64, 32, area), 2);
+    assert_eq!(ScaledDisplay::scale(128, 64, Rect::new(0, 0, 10, 10)), 1);
+
+    let mut buffer = Buffer::empty(area);
+    ScaledDisplay::new(&chip8)
+        .colors(Color::Red, Color::Black)
+        .render(area, &mut buffer);
+    // centred, 128 columns by 32 rows
+    let (left, top) = (6, 4);
+    assert_eq!(buffer[(left, top)].fg, Color::Red);
+    assert_eq!(buffer[(left + 1, top)].bg, Color::Red);
+    assert_eq!(buffer[(left + 2, top)].fg, Color::Black);
+    assert_eq!(buffer[(left, top + 1)].fg, Color::Black);
+    assert_eq!(buffer[(left - 1, top)].symbol(), " ");
+}
+
+#[test]
+fn hex_input_finds_the_clicked_key() {
+    let area = Rect::new(10, 5, 12, 4);
+    assert_eq!(HexInput::key_at(area, 10, 5), Some(0));
+    assert_eq!(HexInput::key_at(area, 14, 5), Some(1));
+    assert_eq!(HexInput::key_at(area, 19, 8), Some(0xF));
+    assert_eq!(HexInput::key_at(area, 9, 5), None);
+    assert_eq!(HexInput::key_at(area, 10, 9), None);
+    assert_eq!(HexInput::key_at(Rect::default(), 0, 0), None);
+}
+
+#[test]
+fn hex_input_flashes_held_keys_and_shows_presses() {
+    let mut activity = KeypadActivity::new();
+    activity.update(0b11, 1);
+    activity.update(0b01, 100);
+    activity.update(0b00, 100);
+    activity.update(0b01 | 1 << 0xF, 0);
+
+    let area = Rect::new(0, 0, 12, 4);
+    let mut buffer = Buffer::empty(area);
+    HexInput::new(0b01 | 1 << 0xF)
+        .colors(Color::Green, Color::Blue)
+        .activity(&activity)
+        .heatmap(true)
+        .render(area, &mut buffer);
+
+    assert_eq!(row(&buffer, 0), " 1  2  3  4");
+    // both held keys just went down and flash
+    assert_eq!(buffer[(1, 0)].bg, Color::Green);
+    assert_eq!(buffer[(10, 3)].bg, Color::Green);
+    // 2 is up, behind it half the presses of 1
+    assert_eq!(buffer[(4, 0)].fg, Color::Blue);
+    assert_eq!(buffer[(4, 0)].bg, Color::Rgb(128, 0, 0));
+    // never pressed
+    assert_eq!(buffer[(7, 0)].bg, Color::Reset);
+}